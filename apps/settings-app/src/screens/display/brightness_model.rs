@@ -1,19 +1,26 @@
+use futures::StreamExt;
 use lazy_static::lazy_static;
 use mctk_core::context::Context;
 use mctk_macros::Model;
-use mechanix_system_dbus_client::display::{Display, NotificationStream};
+use mechanix_system_dbus_client::display::Display;
 use tokio::runtime::Runtime;
 
 lazy_static! {
     static ref RUNTIME: Runtime = Runtime::new().unwrap();
     static ref BATTERY_MODEL: BrightnessModel = BrightnessModel {
-        brightness_percentage: Context::new(5. as u8)
+        brightness_percentage: Context::new(5. as u8),
+        is_streaming: Context::new(false),
+        has_ambient_light_sensor: Context::new(false),
+        auto_brightness: Context::new(false),
     };
 }
 
 #[derive(Model)]
 pub struct BrightnessModel {
     pub brightness_percentage: Context<u8>,
+    is_streaming: Context<bool>,
+    pub has_ambient_light_sensor: Context<bool>,
+    pub auto_brightness: Context<bool>,
 }
 
 impl BrightnessModel {
@@ -23,9 +30,7 @@ impl BrightnessModel {
 
     pub fn set_brightness(value: u8) {
         RUNTIME.spawn(async move {
-            let result =
-                Display::set_brightness_percentage((value as f32 / 100. * 254.).max(5.) as u8)
-                    .await;
+            let result = Display::set_brightness_percentage(value.max(2)).await;
 
             match result {
                 Ok(v) => {
@@ -45,7 +50,7 @@ impl BrightnessModel {
                 Ok(value) => {
                     BrightnessModel::get()
                         .brightness_percentage
-                        .set(((value as f32 / 254. * 100.) as u8).into());
+                        .set(value.into());
                 }
                 Err(e) => {
                     eprintln!(
@@ -54,28 +59,71 @@ impl BrightnessModel {
                     );
                 }
             };
+            match Display::has_ambient_light_sensor().await {
+                Ok(value) => {
+                    BrightnessModel::get().has_ambient_light_sensor.set(value);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "BrightnessModel::error while checking ambient light sensor {}",
+                        e
+                    );
+                }
+            };
+            match Display::get_auto_brightness().await {
+                Ok(value) => {
+                    BrightnessModel::get().auto_brightness.set(value);
+                }
+                Err(e) => {
+                    eprintln!("BrightnessModel::error while getting auto brightness {}", e);
+                }
+            };
+        });
+    }
+
+    pub fn set_auto_brightness(enabled: bool) {
+        RUNTIME.spawn(async move {
+            match Display::set_auto_brightness(enabled).await {
+                Ok(_) => {
+                    BrightnessModel::get().auto_brightness.set(enabled);
+                }
+                Err(e) => {
+                    eprintln!("BrightnessModel::error while setting auto brightness {}", e);
+                }
+            };
+        });
+    }
+
+    /// Follows the brightness signal of the display service so changes from
+    /// the shell or the brightness keys show up without polling
+    pub fn run_brightness_stream() {
+        if *BrightnessModel::get().is_streaming.get() {
+            return;
+        }
+        BrightnessModel::get().is_streaming.set(true);
+
+        RUNTIME.spawn(async {
+            let mut stream = match Display::get_notification_stream().await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!(
+                        "BrightnessModel::error while getting brightness stream {}",
+                        e
+                    );
+                    BrightnessModel::get().is_streaming.set(false);
+                    return;
+                }
+            };
+
+            while let Some(signal) = stream.next().await {
+                if let Ok(args) = signal.args() {
+                    BrightnessModel::get()
+                        .brightness_percentage
+                        .set(args.event.brightness_percentage);
+                }
+            }
 
-            // let mut stream_res = BrightnessService::get_notification_stream().await;
-            // if let Err(e) = stream_res.as_ref() {
-            //     error!(task, "error while getting brightness stream {}", e);
-            //     BrightnessModel::get().brightness_percentage.set(0.0);
-            //     return;
-            // }
-            // loop {
-            //     select! {
-            //         signal = stream_res.as_mut().unwrap().next() => {
-            //             if signal.is_none() {
-            //                 continue;
-            //             }
-            //
-            //             if let Ok(args) = signal.unwrap().args() {
-            //                 let event = args.event;
-            //                 BrightnessModel::get().brightness_percentage.set(event.brightness_percentage);
-            //             }
-            //
-            //         }
-            //     }
-            // }
+            BrightnessModel::get().is_streaming.set(false);
         });
     }
 }
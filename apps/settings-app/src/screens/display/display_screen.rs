@@ -37,6 +37,7 @@ impl DisplayScreen {
 impl Component for DisplayScreen {
     fn init(&mut self) {
         BrightnessModel::update();
+        BrightnessModel::run_brightness_stream();
         self.state_mut().route = DisplayScreenRoute::DisplayScreen;
     }
 
@@ -87,6 +88,22 @@ impl Component for DisplayScreen {
             lay![size: [Auto, 45], margin:[5., 15., 35., 5.]]
         );
 
+        let has_ambient_light_sensor = *BrightnessModel::get().has_ambient_light_sensor.get();
+        let auto_brightness = *BrightnessModel::get().auto_brightness.get();
+        let auto_brightness_toggle = tab_item_node!(
+            [text_node("Auto Brightness")],
+            [node!(
+                Toggle::new(auto_brightness)
+                    .toggle_type(widgets::ToggleType::Type3)
+                    .on_change(Box::new(|value| {
+                        BrightnessModel::set_auto_brightness(value);
+                        Box::new(())
+                    })),
+                lay![]
+            )],
+            on_click: None,
+        );
+
         let screen_off_time = tab_item_node!(
             [text_node("Screen Time")],
             [text_bold_node("30s")],
@@ -107,6 +124,13 @@ impl Component for DisplayScreen {
             size: 0.8,
             color: Color::rgba(83., 83., 83., 1.)
         }));
+        if has_ambient_light_sensor {
+            main_node = main_node.push(auto_brightness_toggle);
+            main_node = main_node.push(node!(HDivider {
+                size: 0.8,
+                color: Color::rgba(83., 83., 83., 1.)
+            }));
+        }
         main_node = main_node.push(screen_off_time);
         main_node = main_node.push(node!(HDivider {
             size: 0.8,
@@ -46,15 +46,20 @@ impl Component for InputDeviceSelector {
         let mut options: Vec<(Vec<font_cache::TextSegment>, Vec<font_cache::TextSegment>)> = vec![];
 
         for (i, device) in input_devices.clone().into_iter().enumerate() {
+            // full name as the value, only the label is shortened
             options.push((
                 txt!(truncate(device.name.clone(), 30)),
-                txt!(truncate(device.name.clone(), 30)),
+                txt!(device.name.clone()),
             ));
         }
 
         if (options.len() > 0) {
-            // println!("CHECK  01--------------- {:?}", options[0].1.clone());
-            main_node = main_node.push(radio_node!(options, txt!("")));
+            let default_input = SoundModel::get().default_input.get().clone();
+            main_node = main_node.push(radio_node!(
+                options,
+                txt!(default_input),
+                Box::new(|x| msg!(SoundModel::set_default_input(&x)))
+            ));
         }
 
         base = base.push(main_node);
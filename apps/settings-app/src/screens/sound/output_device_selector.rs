@@ -46,14 +46,20 @@ impl Component for OutputDeviceSelector {
         let mut options = vec![];
 
         for (i, device) in output_devices.clone().into_iter().enumerate() {
+            // full name as the value, only the label is shortened
             options.push((
                 txt!(truncate(device.name.clone(), 30)),
-                txt!(truncate(device.name.clone(), 30)),
+                txt!(device.name.clone()),
             ));
         }
 
         if (options.len() > 0) {
-            main_node = main_node.push(radio_node!(options, txt!("Speakers")));
+            let default_output = SoundModel::get().default_output.get().clone();
+            main_node = main_node.push(radio_node!(
+                options,
+                txt!(default_output),
+                Box::new(|x| msg!(SoundModel::set_default_output(&x)))
+            ));
         }
 
         base = base.push(main_node);
@@ -31,6 +31,8 @@ lazy_static! {
         input_volume: Context::new(50.0),
         output_devices: Context::new(vec![]),
         output_volume: Context::new(0.0),
+        default_input: Context::new("".to_string()),
+        default_output: Context::new("".to_string()),
         listen_to_stream: Context::new(false)
     };
 }
@@ -43,6 +45,9 @@ pub struct SoundModel {
     pub output_devices: Context<Vec<SourceInformationResponse>>,
     pub output_volume: Context<f64>,
 
+    pub default_input: Context<String>,
+    pub default_output: Context<String>,
+
     pub listen_to_stream: Context<bool>,
 }
 
@@ -115,6 +120,10 @@ impl SoundModel {
                 .await
                 .unwrap();
             SoundModel::get().input_devices.set(input_devices);
+
+            if let Ok(default_input) = Sound::get_default_input().await {
+                SoundModel::get().default_input.set(default_input);
+            }
         });
     }
 
@@ -125,6 +134,30 @@ impl SoundModel {
                 .unwrap();
 
             SoundModel::get().output_devices.set(output_devices);
+
+            if let Ok(default_output) = Sound::get_default_output().await {
+                SoundModel::get().default_output.set(default_output);
+            }
+        });
+    }
+
+    pub fn set_default_input(device: &str) {
+        let device = device.to_string();
+        RUNTIME.spawn(async move {
+            match Sound::set_default_input(device.clone()).await {
+                Ok(_) => SoundModel::get().default_input.set(device),
+                Err(e) => println!("Error setting default input {:?}", e),
+            }
+        });
+    }
+
+    pub fn set_default_output(device: &str) {
+        let device = device.to_string();
+        RUNTIME.spawn(async move {
+            match Sound::set_default_output(device.clone()).await {
+                Ok(_) => SoundModel::get().default_output.set(device),
+                Err(e) => println!("Error setting default output {:?}", e),
+            }
         });
     }
 }
@@ -1,6 +1,6 @@
 use zbus::proxy;
 
-use crate::types::{Identity, Subject};
+use crate::types::{AuthorizationResult, Identity, Subject};
 #[proxy(
     interface = "org.freedesktop.PolicyKit1.Authority",
     default_service = "org.freedesktop.PolicyKit1",
@@ -15,6 +15,16 @@ trait Authority {
         identity: Identity,
     ) -> zbus::Result<()>;
 
+    /// CheckAuthorization method
+    fn check_authorization(
+        &self,
+        subject: Subject<'_>,
+        action_id: &str,
+        details: std::collections::HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<AuthorizationResult>;
+
     /// CancelCheckAuthorization method
     fn cancel_check_authorization(&self, cancellation_id: &str) -> zbus::Result<()>;
 
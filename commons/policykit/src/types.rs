@@ -24,3 +24,19 @@ impl<'a> zvariant::Type for Subject<'a> {
         unsafe { zvariant::Signature::from_bytes_unchecked(b"(sa{sv})") }
     }
 }
+
+/// Flag of `CheckAuthorization` that lets polkit ask the user to authenticate
+pub const CHECK_AUTHORIZATION_ALLOW_USER_INTERACTION: u32 = 1;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct AuthorizationResult {
+    pub is_authorized: bool,
+    pub is_challenge: bool,
+    pub details: HashMap<String, String>,
+}
+
+impl zvariant::Type for AuthorizationResult {
+    fn signature() -> zvariant::Signature<'static> {
+        unsafe { zvariant::Signature::from_bytes_unchecked(b"(bba{ss})") }
+    }
+}
@@ -26,4 +26,56 @@ pub trait Device {
     /// Type property
     #[zbus(property)]
     fn type_(&self) -> Result<u32>;
+
+    /// NativePath property
+    #[zbus(property)]
+    fn native_path(&self) -> Result<String>;
+
+    /// Vendor property
+    #[zbus(property)]
+    fn vendor(&self) -> Result<String>;
+
+    /// Model property
+    #[zbus(property)]
+    fn model(&self) -> Result<String>;
+
+    /// Serial property
+    #[zbus(property)]
+    fn serial(&self) -> Result<String>;
+
+    /// PowerSupply property
+    #[zbus(property)]
+    fn power_supply(&self) -> Result<bool>;
+
+    /// Online property
+    #[zbus(property)]
+    fn online(&self) -> Result<bool>;
+
+    /// IsPresent property
+    #[zbus(property)]
+    fn is_present(&self) -> Result<bool>;
+
+    /// EnergyRate property
+    #[zbus(property)]
+    fn energy_rate(&self) -> Result<f64>;
+
+    /// TimeToEmpty property
+    #[zbus(property)]
+    fn time_to_empty(&self) -> Result<i64>;
+
+    /// TimeToFull property
+    #[zbus(property)]
+    fn time_to_full(&self) -> Result<i64>;
+
+    /// Capacity property
+    #[zbus(property)]
+    fn capacity(&self) -> Result<f64>;
+
+    /// ChargeCycles property
+    #[zbus(property)]
+    fn charge_cycles(&self) -> Result<i32>;
+
+    /// Technology property
+    #[zbus(property)]
+    fn technology(&self) -> Result<u32>;
 }
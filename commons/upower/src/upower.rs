@@ -10,4 +10,8 @@ pub trait UPower {
 
     /// GetDisplayDevice method
     fn get_display_device(&self) -> Result<OwnedObjectPath>;
+
+    /// OnBattery property
+    #[zbus(property)]
+    fn on_battery(&self) -> Result<bool>;
 }
@@ -1,3 +1,82 @@
-fn main() {
-    println!("Hello, world!");
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use futures_util::StreamExt;
+use mechanix_config_client::{split_path, ConfigClient};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "mechanix-config",
+    about = "Get and set settings of Mechanix components"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Prints the value of <component>.<key> as JSON
+    Get { path: String },
+    /// Sets <component>.<key>, the value is parsed as YAML (e.g. `true`, `10`, `[1, 2]`)
+    Set { path: String, value: String },
+    /// Lists all keys of a component, or all components when omitted
+    List { component: Option<String> },
+    /// Prints every change under <component> or <component>.<key>
+    Watch { path: String },
+    /// Re-reads the settings.yml of a component after it was edited by hand
+    Reload { component: String },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Get { path } => {
+            let (component, key) = split_path(&path)?;
+            println!("{}", ConfigClient::get_raw(component, key).await?);
+        }
+        Command::Set { path, value } => {
+            let (component, key) = split_path(&path)?;
+            let value: serde_json::Value = serde_yaml::from_str(&value)?;
+            ConfigClient::set(component, key, &value).await?;
+        }
+        Command::List { component: None } => {
+            for component in ConfigClient::list_components().await? {
+                println!("{}", component);
+            }
+        }
+        Command::List {
+            component: Some(component),
+        } => {
+            let mut keys_vals: Vec<(String, String)> =
+                ConfigClient::list(&component).await?.into_iter().collect();
+            keys_vals.sort();
+            for (key, value) in keys_vals {
+                println!("{}.{} = {}", component, key, value);
+            }
+        }
+        Command::Watch { path } => {
+            let (component, key) = match path.split_once('.') {
+                Some((component, key)) => (component, key),
+                None => (path.as_str(), ""),
+            };
+            let mut stream = ConfigClient::get_notify().await?;
+            while let Some(signal) = stream.next().await {
+                let event = signal.args()?.event;
+                // the key itself or the keys nested under it, not `foobar` for `foo`
+                let is_watched = key.is_empty()
+                    || event.key == key
+                    || event.key.starts_with(&format!("{}.", key));
+                if event.component == component && is_watched {
+                    println!("{}.{} = {}", event.component, event.key, event.value);
+                }
+            }
+        }
+        Command::Reload { component } => {
+            ConfigClient::reload(&component).await?;
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,5 @@
+mod proxies;
+
+pub use mechanix_config_server::registry::ConfigEvent;
+pub use proxies::config_proxy;
+pub use proxies::config_proxy::{split_path, ConfigClient};
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use mechanix_config_server::registry::ConfigEvent;
+use serde::{de::DeserializeOwned, Serialize};
+use zbus::{proxy, Connection};
+
+#[proxy(
+    interface = "org.mechanix.config",
+    default_service = "org.mechanix.config",
+    default_path = "/org/mechanix/config"
+)]
+trait Config {
+    async fn list_components(&self) -> zbus::Result<Vec<String>>;
+    async fn get(&self, component: &str, key: &str) -> zbus::Result<String>;
+    async fn set(&self, component: &str, key: &str, value: &str) -> zbus::Result<()>;
+    async fn list(&self, component: &str) -> zbus::Result<HashMap<String, String>>;
+    async fn reload(&self, component: &str) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn notify(&self, event: ConfigEvent) -> zbus::Result<()>;
+}
+
+/// Splits a `<component>.<key>` path, e.g. `launcher.modules.clock`
+pub fn split_path(path: &str) -> Result<(&str, &str)> {
+    match path.split_once('.') {
+        Some((component, key)) if !component.is_empty() && !key.is_empty() => Ok((component, key)),
+        _ => bail!("Expected <component>.<key>, found {}", path),
+    }
+}
+
+pub struct ConfigClient;
+
+impl ConfigClient {
+    pub async fn list_components() -> Result<Vec<String>> {
+        let connection = Connection::session().await?;
+        let proxy = ConfigProxy::new(&connection).await?;
+        let reply = proxy.list_components().await?;
+        Ok(reply)
+    }
+
+    /// Gets the value of a key as JSON
+    pub async fn get_raw(component: &str, key: &str) -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = ConfigProxy::new(&connection).await?;
+        let reply = proxy.get(component, key).await?;
+        Ok(reply)
+    }
+
+    pub async fn get<T: DeserializeOwned>(component: &str, key: &str) -> Result<T> {
+        let value = Self::get_raw(component, key).await?;
+        Ok(serde_json::from_str(&value)?)
+    }
+
+    /// Sets the value of a key from JSON, the server rejects values that do
+    /// not match the type of the current value
+    pub async fn set_raw(component: &str, key: &str, value: &str) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = ConfigProxy::new(&connection).await?;
+        proxy.set(component, key, value).await?;
+        Ok(())
+    }
+
+    pub async fn set<T: Serialize>(component: &str, key: &str, value: &T) -> Result<()> {
+        let value = serde_json::to_string(value)?;
+        Self::set_raw(component, key, &value).await
+    }
+
+    pub async fn list(component: &str) -> Result<HashMap<String, String>> {
+        let connection = Connection::session().await?;
+        let proxy = ConfigProxy::new(&connection).await?;
+        let reply = proxy.list(component).await?;
+        Ok(reply)
+    }
+
+    pub async fn reload(component: &str) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = ConfigProxy::new(&connection).await?;
+        proxy.reload(component).await?;
+        Ok(())
+    }
+
+    pub async fn get_notify() -> Result<NotifyStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = ConfigProxy::new(&connection).await?;
+        let stream = proxy.receive_notify().await?;
+        Ok(stream)
+    }
+}
@@ -0,0 +1 @@
+pub mod config_proxy;
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use zbus::{fdo::Error as ZbusError, interface, SignalContext};
+
+use mechanix_config_server::registry::{from_json, to_json, ConfigEvent, Registry};
+
+#[derive(Clone)]
+pub struct ConfigInterface {
+    pub registry: Registry,
+}
+
+#[interface(name = "org.mechanix.config")]
+impl ConfigInterface {
+    pub async fn list_components(&self) -> Result<Vec<String>, ZbusError> {
+        Ok(self.registry.components())
+    }
+
+    pub async fn get(&self, component: &str, key: &str) -> Result<String, ZbusError> {
+        let value = match self.registry.get(component, key) {
+            Ok(value) => value,
+            Err(e) => {
+                println!("Error while getting config {:?}", e);
+                return Err(create_err(e.to_string()));
+            }
+        };
+
+        to_json(&value).map_err(|e| create_err(e.to_string()))
+    }
+
+    pub async fn set(
+        &mut self,
+        component: &str,
+        key: &str,
+        value: &str,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<(), ZbusError> {
+        let new_value = match from_json(value) {
+            Ok(value) => value,
+            Err(e) => return Err(create_err(format!("Value is not valid JSON: {}", e))),
+        };
+
+        if let Err(e) = self.registry.set(component, key, new_value) {
+            println!("Error while setting config {:?}", e);
+            return Err(create_err(e.to_string()));
+        }
+
+        let _ = self
+            .notify(
+                &ctxt,
+                ConfigEvent {
+                    component: component.to_string(),
+                    key: key.to_string(),
+                    value: value.to_string(),
+                },
+            )
+            .await;
+
+        Ok(())
+    }
+
+    pub async fn list(&self, component: &str) -> Result<HashMap<String, String>, ZbusError> {
+        self.registry
+            .list(component)
+            .map_err(|e| create_err(e.to_string()))
+    }
+
+    pub async fn reload(
+        &mut self,
+        component: &str,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<(), ZbusError> {
+        let events = match self.registry.reload(component) {
+            Ok(events) => events,
+            Err(e) => {
+                println!("Error while reloading config {:?}", e);
+                return Err(create_err(e.to_string()));
+            }
+        };
+
+        for event in events {
+            let _ = self.notify(&ctxt, event).await;
+        }
+
+        Ok(())
+    }
+
+    #[zbus(signal)]
+    async fn notify(&self, ctxt: &SignalContext<'_>, event: ConfigEvent)
+        -> Result<(), zbus::Error>;
+}
+
+fn create_err<S: Into<String>>(msg: S) -> ZbusError {
+    ZbusError::Failed(msg.into())
+}
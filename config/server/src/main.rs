@@ -1,3 +1,35 @@
-fn main() {
-    println!("Hello, world!");
+use std::future;
+
+use anyhow::Result;
+mod interfaces;
+mod settings;
+use interfaces::config_interface::ConfigInterface;
+use mechanix_config_server::registry::Registry;
+use settings::{read_settings_yml, ConfigServerSettings};
+use zbus::connection;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let settings = match read_settings_yml() {
+        Ok(settings) => settings,
+        Err(e) => {
+            println!("error while reading settings.yml {:?}", e);
+            ConfigServerSettings::default()
+        }
+    };
+
+    let mut registry = Registry::new(settings.components);
+    for (component, e) in registry.load() {
+        println!("error while loading settings of {} {}", component, e);
+    }
+
+    let config_bus = ConfigInterface { registry };
+    let _config_bus_connection = connection::Builder::session()?
+        .name("org.mechanix.config")?
+        .serve_at("/org/mechanix/config", config_bus)?
+        .build()
+        .await?;
+    let future = future::pending();
+    let () = future.await;
+    Ok(())
 }
@@ -0,0 +1,344 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use zbus::zvariant::Type;
+
+/// Emitted whenever a value of a component changes, `value` is JSON encoded
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct ConfigEvent {
+    pub component: String,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+struct Component {
+    path: PathBuf,
+    value: Value,
+}
+
+/// # Registry
+///
+/// Holds the parsed settings of every registered component, keys are dotted
+/// paths into the settings.yml (e.g. `modules.clock.format`), sequence items
+/// are addressed by index (e.g. `window.size.0`)
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    components: BTreeMap<String, Component>,
+}
+
+impl Registry {
+    pub fn new(components: BTreeMap<String, PathBuf>) -> Self {
+        let components = components
+            .into_iter()
+            .map(|(name, path)| {
+                (
+                    name,
+                    Component {
+                        path,
+                        value: Value::Mapping(Mapping::new()),
+                    },
+                )
+            })
+            .collect();
+        Self { components }
+    }
+
+    /// Reads and validates the settings.yml of every component, returns the
+    /// components that could not be loaded along with the reason
+    pub fn load(&mut self) -> Vec<(String, String)> {
+        let mut errors = vec![];
+        for (name, component) in self.components.iter_mut() {
+            match read_yml(&component.path) {
+                Ok(value) => component.value = value,
+                Err(e) => errors.push((name.clone(), e.to_string())),
+            }
+        }
+        errors
+    }
+
+    /// Re-reads a component from disk and returns the events for the keys that changed
+    pub fn reload(&mut self, name: &str) -> Result<Vec<ConfigEvent>> {
+        let old = self.list(name)?;
+        let component = self.get_component_mut(name)?;
+        component.value = read_yml(&component.path)?;
+        let new = self.list(name)?;
+
+        let events = new
+            .into_iter()
+            .filter(|(key, value)| old.get(key) != Some(value))
+            .map(|(key, value)| ConfigEvent {
+                component: name.to_string(),
+                key,
+                value,
+            })
+            .collect();
+        Ok(events)
+    }
+
+    pub fn components(&self) -> Vec<String> {
+        self.components.keys().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str, key: &str) -> Result<Value> {
+        let component = self.get_component(name)?;
+        let mut value = &component.value;
+        for segment in split_key(key)? {
+            value = match child(value, segment) {
+                Some(value) => value,
+                None => bail!("Key {}.{} not found", name, key),
+            };
+        }
+        Ok(value.clone())
+    }
+
+    /// Validates the new value against the current one and writes the
+    /// settings.yml of the component back to disk
+    pub fn set(&mut self, name: &str, key: &str, new_value: Value) -> Result<()> {
+        let segments = split_key(key)?;
+        let component = self.get_component_mut(name)?;
+
+        let mut updated = component.value.clone();
+        let mut value = &mut updated;
+        for segment in segments {
+            value = match child_mut(value, segment) {
+                Some(value) => value,
+                None => bail!("Key {}.{} not found", name, key),
+            };
+        }
+        if let Err(e) = validate(value, &new_value) {
+            bail!("Invalid value for {}.{}: {}", name, key, e);
+        }
+        *value = new_value;
+
+        write_yml(&component.path, &updated)?;
+        component.value = updated;
+        Ok(())
+    }
+
+    /// Flattens the settings of a component to `key -> JSON value`, sequences
+    /// are reported as a single value
+    pub fn list(&self, name: &str) -> Result<HashMap<String, String>> {
+        let component = self.get_component(name)?;
+        let mut keys_vals = HashMap::new();
+        flatten("", &component.value, &mut keys_vals);
+        Ok(keys_vals)
+    }
+
+    fn get_component(&self, name: &str) -> Result<&Component> {
+        match self.components.get(name) {
+            Some(component) => Ok(component),
+            None => bail!("Unknown component {}", name),
+        }
+    }
+
+    fn get_component_mut(&mut self, name: &str) -> Result<&mut Component> {
+        match self.components.get_mut(name) {
+            Some(component) => Ok(component),
+            None => bail!("Unknown component {}", name),
+        }
+    }
+}
+
+pub fn to_json(value: &Value) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+pub fn from_json(value: &str) -> Result<Value> {
+    Ok(serde_json::from_str(value)?)
+}
+
+fn read_yml(path: &PathBuf) -> Result<Value> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => bail!("Cannot read {:?} - {}", path, e),
+    };
+    let value: Value = match serde_yaml::from_reader(file) {
+        Ok(value) => value,
+        Err(e) => bail!("Error parsing {:?} - {}", path, e),
+    };
+    if !value.is_mapping() {
+        bail!("Expected a mapping at the root of {:?}", path);
+    }
+    Ok(value)
+}
+
+fn write_yml(path: &PathBuf, value: &Value) -> Result<()> {
+    let contents = serde_yaml::to_string(value)?;
+    let tmp_path = path.with_extension("yml.tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn split_key(key: &str) -> Result<Vec<&str>> {
+    if key.is_empty() {
+        return Ok(vec![]);
+    }
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        bail!("Invalid key {}", key);
+    }
+    Ok(segments)
+}
+
+fn child<'a>(value: &'a Value, segment: &str) -> Option<&'a Value> {
+    match value {
+        Value::Mapping(mapping) => mapping.get(segment),
+        Value::Sequence(sequence) => sequence.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+fn child_mut<'a>(value: &'a mut Value, segment: &str) -> Option<&'a mut Value> {
+    match value {
+        Value::Mapping(mapping) => mapping.get_mut(segment),
+        Value::Sequence(sequence) => sequence.get_mut(segment.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, keys_vals: &mut HashMap<String, String>) {
+    match value {
+        Value::Mapping(mapping) if !mapping.is_empty() => {
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::String(key) => key.clone(),
+                    key => serde_yaml::to_string(key)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                };
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, value, keys_vals);
+            }
+        }
+        value => {
+            if let Ok(json) = to_json(value) {
+                keys_vals.insert(prefix.to_string(), json);
+            }
+        }
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "sequence",
+        Value::Mapping(_) => "mapping",
+        Value::Tagged(_) => "tagged",
+    }
+}
+
+/// Checks that `new` can be parsed into the same struct field as `current`,
+/// `null` values are treated as unset options and accept anything
+fn validate(current: &Value, new: &Value) -> Result<()> {
+    match (current, new) {
+        (Value::Null, _) | (_, Value::Null) => Ok(()),
+        (Value::Bool(_), Value::Bool(_)) | (Value::String(_), Value::String(_)) => Ok(()),
+        (Value::Number(c), Value::Number(n)) => {
+            if !c.is_f64() && n.is_f64() {
+                bail!("expected int, found float");
+            }
+            Ok(())
+        }
+        (Value::Sequence(c), Value::Sequence(n)) => {
+            if let Some(first) = c.first() {
+                for item in n {
+                    validate(first, item)?;
+                }
+            }
+            Ok(())
+        }
+        (Value::Mapping(c), Value::Mapping(n)) => {
+            for (key, value) in n {
+                match c.get(key) {
+                    Some(current) => validate(current, value)?,
+                    None => bail!("unknown key {:?}", key),
+                }
+            }
+            for key in c.keys() {
+                if !n.contains_key(key) {
+                    bail!("missing key {:?}", key);
+                }
+            }
+            Ok(())
+        }
+        (Value::Tagged(_), Value::Tagged(_)) => Ok(()),
+        (current, new) => bail!("expected {}, found {}", kind(current), kind(new)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(name: &str, yml: &str) -> (Registry, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("mechanix-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.yml", name));
+        fs::write(&path, yml).unwrap();
+
+        let mut registry = Registry::new(BTreeMap::from([("launcher".to_string(), path.clone())]));
+        assert!(registry.load().is_empty());
+        (registry, path)
+    }
+
+    #[test]
+    fn set_validates_and_persists() {
+        let (mut registry, path) = registry(
+            "set",
+            "modules:\n  clock:\n    enabled: true\n    size: 12\n",
+        );
+
+        assert!(registry
+            .set("launcher", "modules.clock.enabled", Value::from("yes"))
+            .is_err());
+        assert!(registry
+            .set("launcher", "modules.clock.size", Value::from(1.5))
+            .is_err());
+        assert!(registry
+            .set("launcher", "modules.clock.missing", Value::from(1))
+            .is_err());
+
+        registry
+            .set("launcher", "modules.clock.enabled", Value::from(false))
+            .unwrap();
+        assert_eq!(
+            registry.get("launcher", "modules.clock.enabled").unwrap(),
+            Value::from(false)
+        );
+        assert_eq!(
+            read_yml(&path).unwrap(),
+            serde_yaml::from_str::<Value>("modules:\n  clock:\n    enabled: false\n    size: 12\n")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn list_flattens_mappings() {
+        let (registry, _) = registry("list", "window:\n  size: [480, 440]\ntitle: Launcher\n");
+
+        let keys_vals = registry.list("launcher").unwrap();
+        assert_eq!(keys_vals.get("window.size").unwrap(), "[480,440]");
+        assert_eq!(keys_vals.get("title").unwrap(), "\"Launcher\"");
+        assert_eq!(
+            registry.get("launcher", "window.size.1").unwrap(),
+            Value::from(440)
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs::File, path::PathBuf};
+use tracing::{debug, info};
+
+/// # Config Server Settings
+///
+/// Maps every component name (`launcher`, `desktop-server`, ...) to the
+/// settings.yml it reads on startup
+#[derive(Debug, Deserialize, Clone, Serialize, Default)]
+pub struct ConfigServerSettings {
+    pub components: BTreeMap<String, PathBuf>,
+}
+
+/// # Reads Settings path from arg
+///
+/// Reads the `-s` or `--settings` argument for the path
+pub fn read_settings_path_from_args() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 2 && (args[1] == "-s" || args[1] == "--settings") {
+        debug!("Using settings path from argument - {}", args[2]);
+        return Some(args[2].clone());
+    }
+    None
+}
+
+/// # Reads Settings YML
+///
+/// Reads the `settings.yml` and parsers to ConfigServerSettings
+pub fn read_settings_yml() -> Result<ConfigServerSettings> {
+    let mut file_path = PathBuf::from(
+        std::env::var("MECHANIX_CONFIG_SERVER_SETTINGS_PATH")
+            .unwrap_or(String::from("settings.yml")),
+    );
+
+    // read from args
+    if let Some(file_path_in_args) = read_settings_path_from_args() {
+        file_path = PathBuf::from(file_path_in_args);
+    }
+
+    info!(
+        task = "read_settings",
+        "settings file location - {:?}", file_path
+    );
+
+    let settings_file = match File::open(&file_path) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(anyhow!("Error opening file: {:?}", e));
+        }
+    };
+
+    let settings: ConfigServerSettings = match serde_yaml::from_reader(settings_file) {
+        Ok(settings) => settings,
+        Err(e) => {
+            return Err(anyhow!("Error parsing file: {:?}", e));
+        }
+    };
+
+    Ok(settings)
+}
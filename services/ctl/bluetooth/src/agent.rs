@@ -0,0 +1,442 @@
+use anyhow::{anyhow, bail, Result};
+use futures::{future::BoxFuture, FutureExt};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time,
+};
+
+use bluer::{
+    agent::{
+        Agent, AgentHandle, AuthorizeService, DisplayPasskey, DisplayPinCode, ReqError, ReqResult,
+        RequestAuthorization, RequestConfirmation, RequestPasskey, RequestPinCode,
+    },
+    Address, Session,
+};
+
+/// BlueZ gives up on the agent after the D-Bus timeout of 25 seconds, stop
+/// waiting on the user before that
+pub const PAIRING_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Largest passkey, passkeys have 6 digits
+const MAX_PASSKEY: u32 = 999_999;
+
+/// PIN codes have 1 to 16 characters
+const MAX_PIN_CODE_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairingRequestKind {
+    /// Reply with a `PinCode`, for legacy devices
+    RequestPinCode,
+    /// Show the PIN code to type on the device, needs no reply
+    DisplayPinCode(String),
+    /// Reply with the `Passkey` shown on the device
+    RequestPasskey,
+    /// Show the passkey to type on the device, needs no reply
+    DisplayPasskey { passkey: u32, entered: u16 },
+    /// Accept when the device shows the same passkey
+    RequestConfirmation(u32),
+    /// Accept pairing without a passkey
+    RequestAuthorization,
+    /// Accept a connection to the service with the UUID
+    AuthorizeService(String),
+}
+
+impl PairingRequestKind {
+    pub fn needs_reply(&self) -> bool {
+        !matches!(
+            self,
+            PairingRequestKind::DisplayPinCode(_) | PairingRequestKind::DisplayPasskey { .. }
+        )
+    }
+
+    /// Whether `reply` answers the request, `Reject` answers all of them
+    pub fn accepts(&self, reply: &PairingReply) -> bool {
+        match (self, reply) {
+            (_, PairingReply::Reject) => true,
+            (PairingRequestKind::RequestPinCode, PairingReply::PinCode(pin_code)) => {
+                (1..=MAX_PIN_CODE_LENGTH).contains(&pin_code.len())
+                    && pin_code.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+            (PairingRequestKind::RequestPasskey, PairingReply::Passkey(passkey)) => {
+                *passkey <= MAX_PASSKEY
+            }
+            (PairingRequestKind::RequestPinCode, _) | (PairingRequestKind::RequestPasskey, _) => {
+                false
+            }
+            (_, reply) => *reply == PairingReply::Accept,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairingRequest {
+    /// Sent in the public `PairingRequested` signal, so knowing it is no
+    /// permission to answer, the bus interface checks that with polkit
+    pub id: u32,
+    /// Address of the device
+    pub device: String,
+    pub kind: PairingRequestKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairingEvent {
+    Request(PairingRequest),
+    /// The request timed out or BlueZ canceled it, the UI should close it
+    Canceled {
+        id: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairingReply {
+    Accept,
+    Reject,
+    PinCode(String),
+    Passkey(u32),
+}
+
+struct PendingRequest {
+    kind: PairingRequestKind,
+    reply: oneshot::Sender<PairingReply>,
+}
+
+struct Inner {
+    timeout: Duration,
+    pending: Mutex<HashMap<u32, PendingRequest>>,
+    events: mpsc::UnboundedSender<PairingEvent>,
+}
+
+/// Removes the request when it is not answered, because it timed out or
+/// BlueZ dropped the call
+struct PendingGuard<'a> {
+    inner: &'a Inner,
+    id: u32,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if self
+            .inner
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&self.id)
+            .is_some()
+        {
+            let _ = self
+                .inner
+                .events
+                .send(PairingEvent::Canceled { id: self.id });
+        }
+    }
+}
+
+/// # Pairing Agent
+///
+/// Hands the requests of BlueZ while pairing, such as comparing a passkey,
+/// to the UI as `PairingEvent`s and waits for the `reply` to them. Requests
+/// without a reply within the timeout are canceled.
+#[derive(Clone)]
+pub struct PairingAgent {
+    inner: Arc<Inner>,
+}
+
+/// Keeps the agent registered with BlueZ until dropped
+pub struct PairingAgentHandle {
+    _session: Session,
+    _handle: AgentHandle,
+}
+
+type Handler<R, T> = Box<dyn Fn(R) -> BoxFuture<'static, ReqResult<T>> + Send + Sync>;
+
+impl PairingAgent {
+    pub fn new(timeout: Duration) -> (Self, mpsc::UnboundedReceiver<PairingEvent>) {
+        let (events, rx) = mpsc::unbounded_channel();
+        let agent = PairingAgent {
+            inner: Arc::new(Inner {
+                timeout,
+                pending: Mutex::new(HashMap::new()),
+                events,
+            }),
+        };
+        (agent, rx)
+    }
+
+    /// Forwards a request to the UI, `None` when it was not answered in time
+    pub async fn request(&self, device: &str, kind: PairingRequestKind) -> Option<PairingReply> {
+        let (tx, rx) = oneshot::channel();
+        let id = {
+            let mut pending = self.inner.pending.lock().unwrap();
+            let id = new_id(&pending);
+            if kind.needs_reply() {
+                pending.insert(
+                    id,
+                    PendingRequest {
+                        kind: kind.clone(),
+                        reply: tx,
+                    },
+                );
+            }
+            id
+        };
+        let request = PairingRequest {
+            id,
+            device: device.to_string(),
+            kind: kind.clone(),
+        };
+        if !kind.needs_reply() {
+            let _ = self.inner.events.send(PairingEvent::Request(request));
+            return Some(PairingReply::Accept);
+        }
+
+        let _guard = PendingGuard {
+            inner: &self.inner,
+            id,
+        };
+        if self
+            .inner
+            .events
+            .send(PairingEvent::Request(request))
+            .is_err()
+        {
+            // nobody listens
+            return None;
+        }
+
+        match time::timeout(self.inner.timeout, rx).await {
+            Ok(Ok(reply)) => Some(reply),
+            _ => None,
+        }
+    }
+
+    /// Answers the pending request `id`
+    pub fn reply(&self, id: u32, reply: PairingReply) -> Result<()> {
+        let mut pending = self.inner.pending.lock().unwrap();
+        let request = pending
+            .get(&id)
+            .ok_or_else(|| anyhow!("No pending pairing request {}", id))?;
+        if !request.kind.accepts(&reply) {
+            bail!("{:?} does not answer {:?}", reply, request.kind);
+        }
+        if let Some(request) = pending.remove(&id) {
+            let _ = request.reply.send(reply);
+        }
+        Ok(())
+    }
+
+    /// Registers the agent as the default agent of BlueZ
+    pub async fn register(&self) -> Result<PairingAgentHandle> {
+        let session = Session::new().await?;
+        let handle = session.register_agent(self.bluer_agent()).await?;
+        Ok(PairingAgentHandle {
+            _session: session,
+            _handle: handle,
+        })
+    }
+
+    fn bluer_agent(&self) -> Agent {
+        Agent {
+            request_default: true,
+            request_pin_code: Some(self.handler(
+                |request: RequestPinCode| (request.device, PairingRequestKind::RequestPinCode),
+                |reply| match reply {
+                    PairingReply::PinCode(pin_code) => Some(pin_code),
+                    _ => None,
+                },
+            )),
+            display_pin_code: Some(self.handler(
+                |request: DisplayPinCode| {
+                    (
+                        request.device,
+                        PairingRequestKind::DisplayPinCode(request.pincode),
+                    )
+                },
+                accepted,
+            )),
+            request_passkey: Some(self.handler(
+                |request: RequestPasskey| (request.device, PairingRequestKind::RequestPasskey),
+                |reply| match reply {
+                    PairingReply::Passkey(passkey) => Some(passkey),
+                    _ => None,
+                },
+            )),
+            display_passkey: Some(self.handler(
+                |request: DisplayPasskey| {
+                    (
+                        request.device,
+                        PairingRequestKind::DisplayPasskey {
+                            passkey: request.passkey,
+                            entered: request.entered,
+                        },
+                    )
+                },
+                accepted,
+            )),
+            request_confirmation: Some(self.handler(
+                |request: RequestConfirmation| {
+                    (
+                        request.device,
+                        PairingRequestKind::RequestConfirmation(request.passkey),
+                    )
+                },
+                accepted,
+            )),
+            request_authorization: Some(self.handler(
+                |request: RequestAuthorization| {
+                    (request.device, PairingRequestKind::RequestAuthorization)
+                },
+                accepted,
+            )),
+            authorize_service: Some(self.handler(
+                |request: AuthorizeService| {
+                    (
+                        request.device,
+                        PairingRequestKind::AuthorizeService(request.service.to_string()),
+                    )
+                },
+                accepted,
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn handler<R, T: Send + 'static>(
+        &self,
+        describe: fn(R) -> (Address, PairingRequestKind),
+        answer: fn(PairingReply) -> Option<T>,
+    ) -> Handler<R, T> {
+        let agent = self.clone();
+        Box::new(move |request| {
+            let agent = agent.clone();
+            let (device, kind) = describe(request);
+            async move {
+                match agent.request(&device.to_string(), kind).await {
+                    Some(PairingReply::Reject) => Err(ReqError::Rejected),
+                    Some(reply) => answer(reply).ok_or(ReqError::Rejected),
+                    None => Err(ReqError::Canceled),
+                }
+            }
+            .boxed()
+        })
+    }
+}
+
+/// Random id that is not taken by a pending request
+fn new_id(pending: &HashMap<u32, PendingRequest>) -> u32 {
+    loop {
+        let id = rand::random();
+        if id != 0 && !pending.contains_key(&id) {
+            return id;
+        }
+    }
+}
+
+fn accepted(reply: PairingReply) -> Option<()> {
+    (reply == PairingReply::Accept).then_some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE: &str = "00:11:22:33:44:55";
+
+    async fn next_request(events: &mut mpsc::UnboundedReceiver<PairingEvent>) -> PairingRequest {
+        match events.recv().await {
+            Some(PairingEvent::Request(request)) => request,
+            event => panic!("expected a request, got {:?}", event),
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_requests_and_replies() {
+        let (agent, mut events) = PairingAgent::new(PAIRING_TIMEOUT);
+
+        // stands in for BlueZ calling the agent
+        let caller = agent.clone();
+        let confirmation = tokio::spawn(async move {
+            caller
+                .request(DEVICE, PairingRequestKind::RequestConfirmation(123456))
+                .await
+        });
+        let request = next_request(&mut events).await;
+        assert_eq!(request.device, DEVICE);
+        assert_eq!(
+            request.kind,
+            PairingRequestKind::RequestConfirmation(123456)
+        );
+        assert!(agent
+            .reply(request.id, PairingReply::Passkey(123456))
+            .is_err());
+        agent.reply(request.id, PairingReply::Accept).unwrap();
+        assert_eq!(confirmation.await.unwrap(), Some(PairingReply::Accept));
+        assert!(agent.reply(request.id, PairingReply::Accept).is_err());
+
+        let caller = agent.clone();
+        let passkey = tokio::spawn(async move {
+            caller
+                .request(DEVICE, PairingRequestKind::RequestPasskey)
+                .await
+        });
+        let request = next_request(&mut events).await;
+        assert!(agent
+            .reply(request.id, PairingReply::Passkey(1_000_000))
+            .is_err());
+        agent.reply(request.id, PairingReply::Passkey(42)).unwrap();
+        assert_eq!(passkey.await.unwrap(), Some(PairingReply::Passkey(42)));
+
+        // shown on the UI, BlueZ does not wait
+        let displayed = agent
+            .request(
+                DEVICE,
+                PairingRequestKind::DisplayPinCode("0000".to_string()),
+            )
+            .await;
+        assert_eq!(displayed, Some(PairingReply::Accept));
+        let request = next_request(&mut events).await;
+        assert!(agent.reply(request.id, PairingReply::Accept).is_err());
+    }
+
+    #[tokio::test]
+    async fn cancels_unanswered_requests() {
+        let (agent, mut events) = PairingAgent::new(Duration::from_millis(50));
+
+        let caller = agent.clone();
+        let authorization = tokio::spawn(async move {
+            caller
+                .request(DEVICE, PairingRequestKind::RequestAuthorization)
+                .await
+        });
+        let request = next_request(&mut events).await;
+        assert_eq!(authorization.await.unwrap(), None);
+        assert_eq!(
+            events.recv().await,
+            Some(PairingEvent::Canceled { id: request.id })
+        );
+        assert!(agent.reply(request.id, PairingReply::Accept).is_err());
+
+        // BlueZ dropping the call cancels the request as well
+        let caller = agent.clone();
+        let service = tokio::spawn(async move {
+            caller
+                .request(
+                    DEVICE,
+                    PairingRequestKind::AuthorizeService(
+                        "0000110b-0000-1000-8000-00805f9b34fb".to_string(),
+                    ),
+                )
+                .await
+        });
+        let request = next_request(&mut events).await;
+        service.abort();
+        assert_eq!(
+            events.recv().await,
+            Some(PairingEvent::Canceled { id: request.id })
+        );
+    }
+}
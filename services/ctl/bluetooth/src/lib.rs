@@ -1,2 +1,8 @@
 mod bluetooth;
 pub use bluetooth::{Bluetooth, BluetoothAdapterInfo, BluetoothDeviceInfo};
+
+mod agent;
+pub use agent::{
+    PairingAgent, PairingAgentHandle, PairingEvent, PairingReply, PairingRequest,
+    PairingRequestKind, PAIRING_TIMEOUT,
+};
@@ -0,0 +1,275 @@
+use crate::errors::{DisplayError, DisplayErrorCodes};
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{error as trace_error, info, warn};
+
+/// Directory the kernel exposes IIO devices in
+pub const IIO_DEVICES_PATH: &str = "/sys/bus/iio/devices";
+
+/// # Ambient Light Sensor
+///
+/// Illuminance channel of an IIO device, either a processed `_input` channel
+/// in lux or a `_raw` channel converted with its `_scale` and `_offset`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbientLightSensor {
+    pub name: String,
+    pub path: PathBuf,
+    channel: PathBuf,
+    scale: f64,
+    offset: f64,
+}
+
+impl AmbientLightSensor {
+    /// First IIO device with an illuminance channel
+    pub fn discover() -> Result<Option<Self>> {
+        discover_in(Path::new(IIO_DEVICES_PATH))
+    }
+
+    /// Reads the illuminance channel of the IIO device at `path`, `None`
+    /// when the device has none
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let mut files: Vec<String> = fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|file_name| file_name.starts_with("in_illuminance"))
+            .collect();
+        files.sort();
+
+        let name = fs::read_to_string(path.join("name"))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_else(|_| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+
+        // processed channels are already in lux
+        if let Some(input) = files.iter().find(|file| file.ends_with("_input")) {
+            return Ok(Some(AmbientLightSensor {
+                name,
+                path: path.to_path_buf(),
+                channel: path.join(input),
+                scale: 1.,
+                offset: 0.,
+            }));
+        }
+
+        let raw = match files.iter().find(|file| file.ends_with("_raw")) {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+        // `in_illuminance0_raw` uses `in_illuminance0_scale` or the shared
+        // `in_illuminance_scale`
+        let channel = raw.trim_end_matches("_raw");
+        let read_attribute = |attribute: &str, default: f64| -> f64 {
+            [
+                format!("{}_{}", channel, attribute),
+                format!("in_illuminance_{}", attribute),
+            ]
+            .iter()
+            .find_map(|file| fs::read_to_string(path.join(file)).ok())
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(default)
+        };
+
+        Ok(Some(AmbientLightSensor {
+            name,
+            path: path.to_path_buf(),
+            channel: path.join(raw),
+            scale: read_attribute("scale", 1.),
+            offset: read_attribute("offset", 0.),
+        }))
+    }
+
+    /// Illuminance in lux
+    pub fn read_lux(&self) -> Result<f64> {
+        let value = fs::read_to_string(&self.channel).with_context(|| {
+            trace_error!(task = "read_lux", "failed to read {:?}", self.channel);
+            DisplayError::new(
+                DisplayErrorCodes::AmbientLightSensorError,
+                format!("Failed to read {:?}", self.channel),
+            )
+        })?;
+        let value = value.trim().parse::<f64>().with_context(|| {
+            trace_error!(task = "read_lux", "failed to parse {:?}", self.channel);
+            DisplayError::new(
+                DisplayErrorCodes::AmbientLightSensorError,
+                format!("Failed to parse {:?}", self.channel),
+            )
+        })?;
+        Ok(((value + self.offset) * self.scale).max(0.))
+    }
+}
+
+/// First IIO device in `dir` with an illuminance channel
+pub fn discover_in(dir: &Path) -> Result<Option<AmbientLightSensor>> {
+    let mut devices: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(e) => {
+            warn!(task = "discover_als", "cannot read {:?} - {}", dir, e);
+            return Ok(None);
+        }
+    };
+    devices.sort();
+
+    for device in devices {
+        match AmbientLightSensor::read(&device) {
+            Ok(Some(sensor)) => {
+                info!(task = "discover_als", "using {:?}", sensor.path);
+                return Ok(Some(sensor));
+            }
+            Ok(None) => (),
+            Err(e) => warn!(task = "discover_als", "skipping {:?} - {}", device, e),
+        }
+    }
+    Ok(None)
+}
+
+/// # Brightness Curve
+///
+/// Points of illuminance in lux and the brightness in percent to use at
+/// that illuminance, brightness is interpolated between the points and
+/// clamped to the first and last one outside them
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrightnessCurve {
+    points: Vec<(f64, u8)>,
+}
+
+impl Default for BrightnessCurve {
+    fn default() -> Self {
+        BrightnessCurve {
+            points: vec![(0., 10), (10., 20), (100., 40), (1000., 70), (10000., 100)],
+        }
+    }
+}
+
+impl BrightnessCurve {
+    pub fn new(mut points: Vec<(f64, u8)>) -> Result<Self> {
+        if points.is_empty() {
+            bail!(DisplayError::new(
+                DisplayErrorCodes::InvalidBrightnessCurveError,
+                "brightness curve has no points".to_string(),
+            ));
+        }
+        if points.iter().any(|(_, brightness)| *brightness > 100) {
+            bail!(DisplayError::new(
+                DisplayErrorCodes::InvalidBrightnessCurveError,
+                "brightness curve goes above 100%".to_string(),
+            ));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(BrightnessCurve { points })
+    }
+
+    pub fn brightness_for(&self, lux: f64) -> u8 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        if lux <= first.0 {
+            return first.1;
+        }
+        if lux >= last.0 {
+            return last.1;
+        }
+        for pair in self.points.windows(2) {
+            let ((lux_a, brightness_a), (lux_b, brightness_b)) = (pair[0], pair[1]);
+            if lux <= lux_b {
+                let t = (lux - lux_a) / (lux_b - lux_a);
+                let brightness =
+                    brightness_a as f64 + (brightness_b as f64 - brightness_a as f64) * t;
+                return brightness.round() as u8;
+            }
+        }
+        last.1
+    }
+}
+
+/// # Auto Brightness
+///
+/// Turns illuminance readings into brightness changes, readings within
+/// `hysteresis` (a fraction of the illuminance the brightness was last set
+/// for) are ignored so the backlight does not flicker around a threshold
+#[derive(Debug, Clone)]
+pub struct AutoBrightness {
+    curve: BrightnessCurve,
+    hysteresis: f64,
+    last_lux: Option<f64>,
+}
+
+impl AutoBrightness {
+    pub fn new(curve: BrightnessCurve, hysteresis: f64) -> Self {
+        AutoBrightness {
+            curve,
+            hysteresis: hysteresis.max(0.),
+            last_lux: None,
+        }
+    }
+
+    /// Brightness to change to for a reading, `None` while the reading
+    /// stays within the hysteresis band
+    pub fn update(&mut self, lux: f64) -> Option<u8> {
+        if let Some(last_lux) = self.last_lux {
+            // at least 1 lux so darkness does not make every reading a change
+            let band = (last_lux * self.hysteresis).max(1.);
+            if (lux - last_lux).abs() < band {
+                return None;
+            }
+        }
+        self.last_lux = Some(lux);
+        Some(self.curve.brightness_for(lux))
+    }
+
+    /// Forgets the last reading so the next one always sets the brightness
+    pub fn reset(&mut self) {
+        self.last_lux = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discovers_illuminance_channel_in_fake_iio_tree() {
+        let dir = std::env::temp_dir().join(format!("mechanix-iio-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let accel = dir.join("iio:device0");
+        fs::create_dir_all(&accel).unwrap();
+        fs::write(accel.join("name"), "accel\n").unwrap();
+        fs::write(accel.join("in_accel_x_raw"), "12\n").unwrap();
+        let als = dir.join("iio:device1");
+        fs::create_dir_all(&als).unwrap();
+        fs::write(als.join("name"), "als\n").unwrap();
+        fs::write(als.join("in_illuminance0_raw"), "200\n").unwrap();
+        fs::write(als.join("in_illuminance_scale"), "0.5\n").unwrap();
+
+        let sensor = discover_in(&dir).unwrap().unwrap();
+        assert_eq!(sensor.name, "als");
+        assert_eq!(sensor.read_lux().unwrap(), 100.);
+
+        fs::write(als.join("in_illuminance_input"), "42.5\n").unwrap();
+        let sensor = discover_in(&dir).unwrap().unwrap();
+        assert_eq!(sensor.read_lux().unwrap(), 42.5);
+
+        assert!(discover_in(&dir.join("missing")).unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn maps_lux_with_hysteresis() {
+        let curve = BrightnessCurve::new(vec![(1000., 100), (0., 0)]).unwrap();
+        assert_eq!(curve.brightness_for(-5.), 0);
+        assert_eq!(curve.brightness_for(250.), 25);
+        assert_eq!(curve.brightness_for(5000.), 100);
+        assert!(BrightnessCurve::new(vec![]).is_err());
+
+        let mut auto_brightness = AutoBrightness::new(curve, 0.2);
+        assert_eq!(auto_brightness.update(500.), Some(50));
+        assert_eq!(auto_brightness.update(550.), None);
+        assert_eq!(auto_brightness.update(450.), None);
+        assert_eq!(auto_brightness.update(700.), Some(70));
+        auto_brightness.reset();
+        assert_eq!(auto_brightness.update(700.), Some(70));
+    }
+}
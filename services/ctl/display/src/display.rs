@@ -1,17 +1,121 @@
 use crate::errors::{DisplayError, DisplayErrorCodes};
 use anyhow::{bail, Context, Result};
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{BufRead, BufReader, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    time::Duration,
 };
 use tracing::{error as trace_error, info, instrument, trace, warn};
+
+/// Interval between two writes of an animated brightness change, about the
+/// refresh rate of the panel
+pub const ANIMATION_STEP: Duration = Duration::from_millis(16);
+
+/// Directory the kernel exposes backlight devices in
+pub const BACKLIGHT_CLASS_PATH: &str = "/sys/class/backlight";
+
+/// How the backlight is controlled, read from the `type` file of the device,
+/// used to pick the default device the same way logind and most desktops do
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BacklightType {
+    /// Controlled through the firmware (ACPI), preferred when present
+    Firmware,
+    /// Controlled through a platform specific interface
+    Platform,
+    /// Writes the registers of the panel or the PWM directly
+    #[default]
+    Raw,
+}
+
+impl BacklightType {
+    fn parse(value: &str) -> Self {
+        match value.trim() {
+            "firmware" => BacklightType::Firmware,
+            "platform" => BacklightType::Platform,
+            _ => BacklightType::Raw,
+        }
+    }
+}
+
+impl std::fmt::Display for BacklightType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            BacklightType::Firmware => write!(f, "firmware"),
+            BacklightType::Platform => write!(f, "platform"),
+            BacklightType::Raw => write!(f, "raw"),
+        }
+    }
+}
+
+/// A directory of `/sys/class/backlight`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BacklightDevice {
+    pub name: String,
+    pub path: PathBuf,
+    pub backlight_type: BacklightType,
+    pub max_brightness: u32,
+}
+
+impl BacklightDevice {
+    /// Reads the device at `path`, either the device directory or its
+    /// `brightness` file
+    pub fn read(path: &Path) -> Result<Self> {
+        let path = match path.file_name().and_then(|name| name.to_str()) {
+            Some("brightness") => path.parent().unwrap_or(path),
+            _ => path,
+        };
+        if !path.join("brightness").exists() {
+            bail!(DisplayError::new(
+                DisplayErrorCodes::InvalidBrightnessPathError,
+                format!("{:?} is not a backlight device", path),
+            ));
+        }
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let backlight_type = match fs::read_to_string(path.join("type")) {
+            Ok(value) => BacklightType::parse(&value),
+            Err(_) => BacklightType::Raw,
+        };
+        let max_brightness = read_value(&path.join("max_brightness"))?;
+        if max_brightness == 0 {
+            bail!(DisplayError::new(
+                DisplayErrorCodes::InvalidMaxBrightnessError,
+                format!("max_brightness of {} is 0", name),
+            ));
+        }
+
+        Ok(BacklightDevice {
+            name,
+            path: path.to_path_buf(),
+            backlight_type,
+            max_brightness,
+        })
+    }
+
+    pub fn brightness_path(&self) -> PathBuf {
+        self.path.join("brightness")
+    }
+
+    /// Brightness the hardware reports, can differ from the requested one
+    /// while the driver ramps or when it rounds the value
+    pub fn actual_brightness_path(&self) -> PathBuf {
+        self.path.join("actual_brightness")
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Display {
     pub path: String,
+    pub device: BacklightDevice,
 }
 
 impl Display {
+    /// Display for the backlight device at `path`, either the device
+    /// directory or its `brightness` file
     pub fn new(path: &str) -> Result<Self, DisplayError> {
         // Check if the path is valid
         if !Path::new(path).exists() {
@@ -21,17 +125,55 @@ impl Display {
             ));
         }
 
+        let device = match BacklightDevice::read(Path::new(path)) {
+            Ok(device) => device,
+            Err(e) => {
+                return Err(DisplayError::new(
+                    DisplayErrorCodes::InvalidBrightnessPathError,
+                    e.to_string(),
+                ))
+            }
+        };
+
         trace!(task = "display_ctrl instance", "init");
-        Ok(Display {
-            path: String::from(path),
-        })
+        Ok(Display::from_device(device))
+    }
+
+    pub fn from_device(device: BacklightDevice) -> Self {
+        Display {
+            path: device.brightness_path().to_string_lossy().to_string(),
+            device,
+        }
+    }
+
+    /// Display for the default backlight device of the system
+    pub fn discover() -> Result<Self> {
+        let devices = Display::list_devices()?;
+        match default_device(devices) {
+            Some(device) => Ok(Display::from_device(device)),
+            None => bail!(DisplayError::new(
+                DisplayErrorCodes::NoBacklightDeviceError,
+                format!("no backlight device found in {}", BACKLIGHT_CLASS_PATH),
+            )),
+        }
+    }
+
+    /// Backlight devices of the system, devices that cannot be read are
+    /// skipped
+    pub fn list_devices() -> Result<Vec<BacklightDevice>> {
+        list_devices_in(Path::new(BACKLIGHT_CLASS_PATH))
     }
 
+    pub fn max_brightness(&self) -> u32 {
+        self.device.max_brightness
+    }
+
+    /// Sets the brightness in percent of the maximum of the device
     #[instrument(skip(self))]
-    pub fn set_brightness(&self, brightness: u8) -> Result<()> {
+    pub fn set_brightness(&self, percentage: u8) -> Result<()> {
         trace!(task = "set_display_brightness", "init");
         // Check if the brightness value is valid
-        if brightness > 254 {
+        if percentage > 100 {
             warn!(task = "set_display_brightness", "invalid brightness value");
             bail!(DisplayError::new(
                 DisplayErrorCodes::InvalidBrightnessValueError,
@@ -39,103 +181,107 @@ impl Display {
             ));
         }
 
-        let mut file = File::create(&self.path).with_context(|| {
-            trace_error!(
-                task = "set_display_brightness",
-                "failed to open brightness file"
-            );
-            DisplayError::new(
-                DisplayErrorCodes::InvalidBrightnessPathError,
-                "failed to open brightness file".to_string(),
-            )
-        })?;
+        let brightness = percentage_to_raw(percentage, self.max_brightness());
+        self.set_raw_brightness(brightness)?;
 
-        // Try to write the brightness value to the file or return an error
-        if let Err(e) = write!(file, "{}", brightness) {
-            trace_error!(
-                task = "set_display_brightness",
-                "unable to write brightness value: {}",
-                e
-            );
+        info!(
+            task = "set_display_brightness",
+            "set brightness to {}% ({})", percentage, brightness
+        );
+
+        Ok(())
+    }
+
+    /// Moves the brightness to `percentage` over `duration`, the brightness
+    /// is only written when it changes so short ranges take fewer steps
+    #[instrument(skip(self))]
+    pub async fn set_brightness_animated(&self, percentage: u8, duration: Duration) -> Result<()> {
+        trace!(task = "set_brightness_animated", "init");
+        if percentage > 100 {
+            warn!(task = "set_brightness_animated", "invalid brightness value");
             bail!(DisplayError::new(
                 DisplayErrorCodes::InvalidBrightnessValueError,
-                format!("unable to write brightness value: {}", e),
+                "invalid brightness value".to_string(),
             ));
         }
 
+        let from = self.get_raw_brightness()?.min(self.max_brightness());
+        let to = percentage_to_raw(percentage, self.max_brightness());
+        let steps = (duration.as_millis() / ANIMATION_STEP.as_millis()) as u32;
+
+        let mut interval = tokio::time::interval(ANIMATION_STEP);
+        for brightness in animation_steps(from, to, steps) {
+            interval.tick().await;
+            self.set_raw_brightness(brightness)?;
+        }
+
         info!(
-            task = "set_display_brightness",
-            "set brightness to {}", brightness
+            task = "set_brightness_animated",
+            "set brightness to {}% ({}) over {:?}", percentage, to, duration
         );
 
         Ok(())
     }
 
+    /// Brightness in percent of the maximum of the device
     #[instrument(skip(self))]
     pub fn get_brightness(&self) -> Result<u8> {
         trace!(task = "get_display_brightness", "init");
-        let file = File::open(&self.path).with_context(|| {
-            trace_error!(
-                task = "get_display_brightness",
-                "failed to open brightness file"
-            );
-            DisplayError::new(
-                DisplayErrorCodes::InvalidBrightnessPathError,
-                "Failed to open brightness file".to_string(),
-            )
-        })?;
-
-        let buffer = BufReader::new(file);
-        let buffer_value = buffer.lines().next().with_context(|| {
-            trace_error!(
-                task = "get_display_brightness",
-                "failed to read brightness value"
-            );
-            DisplayError::new(
-                DisplayErrorCodes::InvalidBrightnessValueError,
-                "Failed to read brightness value".to_string(),
-            )
-        })?;
-
-        let value = buffer_value?
-            .trim() // Use the ? operator to extract the String and propagate errors if any.
-            .parse::<u8>()
-            .with_context(|| {
-                trace_error!(
-                    task = "get_display_brightness",
-                    "failed to parse brightness value"
-                );
-                DisplayError::new(
-                    DisplayErrorCodes::InvalidBrightnessValueError,
-                    "Failed to parse brightness value".to_string(),
-                )
-            })?;
+        let brightness = self.get_raw_brightness()?;
+        let percentage = raw_to_percentage(brightness, self.max_brightness());
 
         info!(
             task = "get_display_brightness",
-            "brightness value: {}", value
+            "brightness value: {}% ({})", percentage, brightness
         );
 
-        Ok(value)
+        Ok(percentage)
     }
 
+    /// Brightness the hardware reports in percent of the maximum, falls back
+    /// to the requested brightness for drivers without `actual_brightness`
     #[instrument(skip(self))]
-    //set backlight on
-    pub fn set_backlight_on(&self) -> Result<()> {
-        trace!(task = "set_backlight_on", "init");
+    pub fn get_actual_brightness(&self) -> Result<u8> {
+        trace!(task = "get_actual_brightness", "init");
+        let actual_brightness_path = self.device.actual_brightness_path();
+        if !actual_brightness_path.exists() {
+            return self.get_brightness();
+        }
+
+        let brightness = read_value(&actual_brightness_path)?;
+        Ok(raw_to_percentage(brightness, self.max_brightness()))
+    }
+
+    /// Writes the raw value of the device, between 0 and `max_brightness`
+    #[instrument(skip(self))]
+    pub fn set_raw_brightness(&self, brightness: u32) -> Result<()> {
+        if brightness > self.max_brightness() {
+            warn!(task = "set_raw_brightness", "invalid brightness value");
+            bail!(DisplayError::new(
+                DisplayErrorCodes::InvalidBrightnessValueError,
+                format!(
+                    "brightness {} is above the maximum {}",
+                    brightness,
+                    self.max_brightness()
+                ),
+            ));
+        }
+
         let mut file = File::create(&self.path).with_context(|| {
-            trace_error!(task = "set_backlight_on", "failed to open brightness file");
+            trace_error!(
+                task = "set_raw_brightness",
+                "failed to open brightness file"
+            );
             DisplayError::new(
                 DisplayErrorCodes::InvalidBrightnessPathError,
                 "failed to open brightness file".to_string(),
             )
         })?;
-        let brightness: u8 = 255;
-        // Try to write the brightness value to the file or return an error
 
+        // Try to write the brightness value to the file or return an error
         if let Err(e) = write!(file, "{}", brightness) {
             trace_error!(
-                task = "set_display_brightness",
+                task = "set_raw_brightness",
                 "unable to write brightness value: {}",
                 e
             );
@@ -145,6 +291,20 @@ impl Display {
             ));
         }
 
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub fn get_raw_brightness(&self) -> Result<u32> {
+        read_value(Path::new(&self.path))
+    }
+
+    #[instrument(skip(self))]
+    //set backlight on
+    pub fn set_backlight_on(&self) -> Result<()> {
+        trace!(task = "set_backlight_on", "init");
+        self.set_raw_brightness(self.max_brightness())?;
+
         info!(task = "set_backlight_on", "set backlight on");
 
         Ok(())
@@ -154,30 +314,142 @@ impl Display {
     //set backlight off
     pub fn set_backlight_off(&self) -> Result<()> {
         trace!(task = "set_backlight_off", "init");
-        let mut file = File::create(&self.path).with_context(|| {
-            trace_error!(task = "set_backlight_off", "failed to open brightness file");
-            DisplayError::new(
-                DisplayErrorCodes::InvalidBrightnessPathError,
-                "failed to open brightness file".to_string(),
-            )
-        })?;
-        let brightness: u8 = 0;
-        // Try to write the brightness value to the file or return an error
-
-        if let Err(e) = write!(file, "{}", brightness) {
-            trace_error!(
-                task = "set_display_brightness",
-                "unable to write brightness value: {}",
-                e
-            );
-            bail!(DisplayError::new(
-                DisplayErrorCodes::InvalidBrightnessValueError,
-                format!("unable to write brightness value: {}", e),
-            ));
-        }
+        self.set_raw_brightness(0)?;
 
         info!(task = "set_backlight_off", "set backlight off");
 
         Ok(())
     }
 }
+
+/// Picks the device to control when none is configured, firmware over
+/// platform over raw interfaces and the largest range among equals
+pub fn default_device(devices: Vec<BacklightDevice>) -> Option<BacklightDevice> {
+    devices.into_iter().min_by(|a, b| {
+        a.backlight_type
+            .cmp(&b.backlight_type)
+            .then(b.max_brightness.cmp(&a.max_brightness))
+            .then(a.name.cmp(&b.name))
+    })
+}
+
+/// Backlight devices in `dir`, sorted by name
+pub fn list_devices_in(dir: &Path) -> Result<Vec<BacklightDevice>> {
+    let entries = fs::read_dir(dir).with_context(|| {
+        trace_error!(task = "list_devices", "failed to read {:?}", dir);
+        DisplayError::new(
+            DisplayErrorCodes::NoBacklightDeviceError,
+            format!("failed to read {:?}", dir),
+        )
+    })?;
+
+    let mut devices = vec![];
+    for entry in entries.flatten() {
+        match BacklightDevice::read(&entry.path()) {
+            Ok(device) => devices.push(device),
+            Err(e) => warn!(task = "list_devices", "skipping {:?} - {}", entry.path(), e),
+        }
+    }
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(devices)
+}
+
+/// Raw values to write to go from `from` to `to` in at most `steps` writes,
+/// always ends on `to`
+fn animation_steps(from: u32, to: u32, steps: u32) -> Vec<u32> {
+    let steps = steps.clamp(1, from.abs_diff(to).max(1)) as i64;
+    let (from, to) = (from as i64, to as i64);
+    (1..=steps)
+        .map(|step| (from + (to - from) * step / steps) as u32)
+        .collect()
+}
+
+fn percentage_to_raw(percentage: u8, max_brightness: u32) -> u32 {
+    ((percentage as u64 * max_brightness as u64 + 50) / 100) as u32
+}
+
+fn raw_to_percentage(brightness: u32, max_brightness: u32) -> u8 {
+    let brightness = brightness.min(max_brightness) as u64;
+    ((brightness * 100 + max_brightness as u64 / 2) / max_brightness as u64) as u8
+}
+
+fn read_value(path: &Path) -> Result<u32> {
+    let file = File::open(path).with_context(|| {
+        trace_error!(task = "read_value", "failed to open {:?}", path);
+        DisplayError::new(
+            DisplayErrorCodes::InvalidBrightnessPathError,
+            format!("Failed to open {:?}", path),
+        )
+    })?;
+
+    let buffer = BufReader::new(file);
+    let buffer_value = buffer.lines().next().with_context(|| {
+        trace_error!(task = "read_value", "failed to read {:?}", path);
+        DisplayError::new(
+            DisplayErrorCodes::InvalidBrightnessValueError,
+            format!("Failed to read {:?}", path),
+        )
+    })?;
+
+    let value = buffer_value?.trim().parse::<u32>().with_context(|| {
+        trace_error!(task = "read_value", "failed to parse {:?}", path);
+        DisplayError::new(
+            DisplayErrorCodes::InvalidBrightnessValueError,
+            format!("Failed to parse {:?}", path),
+        )
+    })?;
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_device(dir: &Path, name: &str, backlight_type: &str, max: u32, value: u32) {
+        let path = dir.join(name);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("type"), format!("{}\n", backlight_type)).unwrap();
+        fs::write(path.join("max_brightness"), format!("{}\n", max)).unwrap();
+        fs::write(path.join("brightness"), format!("{}\n", value)).unwrap();
+    }
+
+    #[test]
+    fn scales_percentage_against_max_brightness() {
+        let dir = std::env::temp_dir().join(format!("mechanix-display-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fake_device(&dir, "backlight-dsi", "raw", 4095, 4095);
+        fake_device(&dir, "intel_backlight", "platform", 100, 50);
+        fs::create_dir_all(dir.join("not-a-backlight")).unwrap();
+
+        let devices = list_devices_in(&dir).unwrap();
+        assert_eq!(devices.len(), 2);
+        let default = default_device(devices.clone()).unwrap();
+        assert_eq!(default.name, "intel_backlight");
+
+        let display = Display::new(dir.join("backlight-dsi/brightness").to_str().unwrap()).unwrap();
+        assert_eq!(display.max_brightness(), 4095);
+        assert_eq!(display.get_brightness().unwrap(), 100);
+        display.set_brightness(50).unwrap();
+        assert_eq!(display.get_raw_brightness().unwrap(), 2048);
+        assert_eq!(display.get_brightness().unwrap(), 50);
+        assert_eq!(display.get_actual_brightness().unwrap(), 50);
+        assert!(display.set_brightness(101).is_err());
+
+        let display = Display::from_device(default);
+        assert_eq!(display.get_brightness().unwrap(), 50);
+        display.set_backlight_on().unwrap();
+        assert_eq!(display.get_raw_brightness().unwrap(), 100);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn animation_ends_on_target() {
+        assert_eq!(animation_steps(0, 100, 4), vec![25, 50, 75, 100]);
+        assert_eq!(animation_steps(100, 0, 4), vec![75, 50, 25, 0]);
+        assert_eq!(animation_steps(10, 12, 30), vec![11, 12]);
+        assert_eq!(animation_steps(10, 10, 30), vec![10]);
+        assert_eq!(animation_steps(0, 4095, 0), vec![4095]);
+    }
+}
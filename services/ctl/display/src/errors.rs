@@ -5,6 +5,11 @@ pub enum DisplayErrorCodes {
     #[default]
     InvalidBrightnessValueError,
     InvalidBrightnessPathError,
+    InvalidMaxBrightnessError,
+    NoBacklightDeviceError,
+    BrightnessWatchError,
+    AmbientLightSensorError,
+    InvalidBrightnessCurveError,
 }
 
 //impl fmt::Display  for DisplayErrorCodes
@@ -17,6 +22,21 @@ impl std::fmt::Display for DisplayErrorCodes {
             DisplayErrorCodes::InvalidBrightnessPathError => {
                 write!(f, "InvalidBrightnessPathError")
             }
+            DisplayErrorCodes::InvalidMaxBrightnessError => {
+                write!(f, "InvalidMaxBrightnessError")
+            }
+            DisplayErrorCodes::NoBacklightDeviceError => {
+                write!(f, "NoBacklightDeviceError")
+            }
+            DisplayErrorCodes::BrightnessWatchError => {
+                write!(f, "BrightnessWatchError")
+            }
+            DisplayErrorCodes::AmbientLightSensorError => {
+                write!(f, "AmbientLightSensorError")
+            }
+            DisplayErrorCodes::InvalidBrightnessCurveError => {
+                write!(f, "InvalidBrightnessCurveError")
+            }
         }
     }
 }
@@ -4,4 +4,13 @@ mod errors;
 pub use errors::{DisplayError, DisplayErrorCodes};
 
 mod display;
-pub use display::Display;
+pub use display::{
+    default_device, list_devices_in, BacklightDevice, BacklightType, Display, ANIMATION_STEP,
+    BACKLIGHT_CLASS_PATH,
+};
+
+mod watcher;
+pub use watcher::BrightnessWatcher;
+
+mod als;
+pub use als::{AmbientLightSensor, AutoBrightness, BrightnessCurve, IIO_DEVICES_PATH};
@@ -0,0 +1,115 @@
+use crate::{
+    display::Display,
+    errors::{DisplayError, DisplayErrorCodes},
+};
+use anyhow::{bail, Result};
+use futures_util::StreamExt;
+use inotify::{EventStream, Inotify, WatchMask};
+use tracing::{error as trace_error, info};
+
+/// # Brightness Watcher
+///
+/// Yields the brightness percentage of a display whenever it changes,
+/// whichever process or key caused it.
+///
+/// Watches both `brightness`, which changes when a process writes it, and
+/// `actual_brightness`, which the backlight core notifies when the firmware
+/// changes the level on its own (brightness keys handled by ACPI).
+pub struct BrightnessWatcher {
+    display: Display,
+    events: EventStream<[u8; 1024]>,
+    last: Option<u8>,
+}
+
+impl BrightnessWatcher {
+    pub fn new(display: Display) -> Result<Self> {
+        let inotify = Inotify::init()?;
+        inotify
+            .watches()
+            .add(display.device.brightness_path(), WatchMask::MODIFY)?;
+        let actual_brightness_path = display.device.actual_brightness_path();
+        if actual_brightness_path.exists() {
+            inotify
+                .watches()
+                .add(actual_brightness_path, WatchMask::MODIFY)?;
+        }
+
+        let device_path = &display.device.path;
+        info!(task = "brightness_watcher", "watching {:?}", device_path);
+
+        let last = display.get_actual_brightness().ok();
+        Ok(BrightnessWatcher {
+            display,
+            events: inotify.into_event_stream([0; 1024])?,
+            last,
+        })
+    }
+
+    /// Brightness when the watcher was created or last changed
+    pub fn current(&self) -> Option<u8> {
+        self.last
+    }
+
+    /// Waits for the brightness percentage to change and returns it, writes
+    /// that keep the same percentage are skipped
+    pub async fn next(&mut self) -> Result<u8> {
+        loop {
+            match self.events.next().await {
+                Some(Ok(_)) => (),
+                Some(Err(e)) => {
+                    trace_error!(task = "brightness_watcher", "inotify error: {}", e);
+                    bail!(e);
+                }
+                None => bail!(DisplayError::new(
+                    DisplayErrorCodes::BrightnessWatchError,
+                    "brightness watch ended".to_string(),
+                )),
+            };
+
+            let brightness = match self.display.get_actual_brightness() {
+                Ok(brightness) => brightness,
+                Err(e) => {
+                    trace_error!(task = "brightness_watcher", "read error: {}", e);
+                    continue;
+                }
+            };
+            if self.last == Some(brightness) {
+                continue;
+            }
+            self.last = Some(brightness);
+            return Ok(brightness);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, time::Duration};
+
+    #[tokio::test]
+    async fn yields_changed_brightness() {
+        let dir =
+            std::env::temp_dir().join(format!("mechanix-display-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("max_brightness"), "200\n").unwrap();
+        fs::write(dir.join("brightness"), "100\n").unwrap();
+
+        let display = Display::new(dir.to_str().unwrap()).unwrap();
+        let writer = Display::new(dir.to_str().unwrap()).unwrap();
+        let mut watcher = BrightnessWatcher::new(display).unwrap();
+        assert_eq!(watcher.current(), Some(50));
+
+        // same percentage, only the second write is reported
+        writer.set_raw_brightness(100).unwrap();
+        writer.set_brightness(80).unwrap();
+        let brightness = tokio::time::timeout(Duration::from_secs(5), watcher.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(brightness, 80);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
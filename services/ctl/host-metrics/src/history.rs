@@ -0,0 +1,270 @@
+use anyhow::{bail, Result};
+use std::{
+    collections::VecDeque,
+    fmt, fs,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    str::FromStr,
+};
+use tracing::warn;
+
+/// Metric that can be queried from the history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Percent of all cores
+    Cpu,
+    /// Percent of the total memory in use
+    Memory,
+    /// Bytes per second received over all interfaces
+    NetworkReceived,
+    /// Bytes per second transmitted over all interfaces
+    NetworkTransmitted,
+    /// Battery charge in percent
+    Battery,
+}
+
+impl FromStr for Metric {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cpu" => Ok(Metric::Cpu),
+            "memory" => Ok(Metric::Memory),
+            "network_received" => Ok(Metric::NetworkReceived),
+            "network_transmitted" => Ok(Metric::NetworkTransmitted),
+            "battery" => Ok(Metric::Battery),
+            _ => bail!("unknown metric {}", s),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Metric::Cpu => write!(f, "cpu"),
+            Metric::Memory => write!(f, "memory"),
+            Metric::NetworkReceived => write!(f, "network_received"),
+            Metric::NetworkTransmitted => write!(f, "network_transmitted"),
+            Metric::Battery => write!(f, "battery"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsSample {
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub cpu_usage: f32,
+    pub used_memory: u64,
+    pub total_memory: u64,
+    /// Bytes per second since the previous sample
+    pub network_received: u64,
+    pub network_transmitted: u64,
+    pub battery_percentage: Option<f32>,
+}
+
+impl MetricsSample {
+    pub fn value(&self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::Cpu => Some(self.cpu_usage as f64),
+            Metric::Memory => match self.total_memory {
+                0 => None,
+                total => Some(self.used_memory as f64 / total as f64 * 100.),
+            },
+            Metric::NetworkReceived => Some(self.network_received as f64),
+            Metric::NetworkTransmitted => Some(self.network_transmitted as f64),
+            Metric::Battery => self.battery_percentage.map(|value| value as f64),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.timestamp,
+            self.cpu_usage,
+            self.used_memory,
+            self.total_memory,
+            self.network_received,
+            self.network_transmitted,
+            self.battery_percentage
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        if fields.len() != 7 {
+            return None;
+        }
+        Some(MetricsSample {
+            timestamp: fields[0].parse().ok()?,
+            cpu_usage: fields[1].parse().ok()?,
+            used_memory: fields[2].parse().ok()?,
+            total_memory: fields[3].parse().ok()?,
+            network_received: fields[4].parse().ok()?,
+            network_transmitted: fields[5].parse().ok()?,
+            battery_percentage: match fields[6] {
+                "" => None,
+                value => Some(value.parse().ok()?),
+            },
+        })
+    }
+}
+
+/// # Metrics History
+///
+/// Ring buffer of the last `capacity` samples, the oldest sample is dropped
+/// when a new one is pushed into a full history
+#[derive(Debug, Clone)]
+pub struct MetricsHistory {
+    capacity: usize,
+    samples: VecDeque<MetricsSample>,
+}
+
+impl MetricsHistory {
+    pub fn new(capacity: usize) -> Self {
+        MetricsHistory {
+            capacity: capacity.max(1),
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, sample: MetricsSample) {
+        while self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &MetricsSample> {
+        self.samples.iter()
+    }
+
+    /// Values of `metric` from `since` on as `(timestamp, value)`, averaged
+    /// over buckets of `resolution` seconds (`0` returns every sample).
+    /// Samples without a value (no battery) are left out.
+    pub fn query(&self, metric: Metric, since: u64, resolution: u64) -> Vec<(u64, f64)> {
+        let values = self
+            .samples
+            .iter()
+            .filter(|sample| sample.timestamp >= since)
+            .filter_map(|sample| Some((sample.timestamp, sample.value(metric)?)));
+
+        if resolution == 0 {
+            return values.collect();
+        }
+
+        let mut buckets: Vec<(u64, f64, u32)> = vec![];
+        for (timestamp, value) in values {
+            let bucket = timestamp - timestamp % resolution;
+            match buckets.last_mut() {
+                Some((last, sum, count)) if *last == bucket => {
+                    *sum += value;
+                    *count += 1;
+                }
+                _ => buckets.push((bucket, value, 1)),
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(bucket, sum, count)| (bucket, sum / count as f64))
+            .collect()
+    }
+
+    /// Writes the samples to `path`, one per line, through a temporary file
+    /// so a crash never leaves a half-written history
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        for sample in &self.samples {
+            writeln!(file, "{}", sample.to_line())?;
+        }
+        file.sync_all()?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Reads a history saved with `save`, lines that fail to parse are skipped
+    pub fn load(path: &Path, capacity: usize) -> Result<Self> {
+        let file = fs::File::open(path)?;
+        let mut history = MetricsHistory::new(capacity);
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            match MetricsSample::from_line(&line) {
+                Some(sample) => history.push(sample),
+                None => warn!(task = "load_history", "skipping line {:?}", line),
+            }
+        }
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64, cpu_usage: f32, battery_percentage: Option<f32>) -> MetricsSample {
+        MetricsSample {
+            timestamp,
+            cpu_usage,
+            used_memory: 256,
+            total_memory: 1024,
+            network_received: 100,
+            network_transmitted: 10,
+            battery_percentage,
+        }
+    }
+
+    #[test]
+    fn keeps_capacity_and_averages_buckets() {
+        let mut history = MetricsHistory::new(4);
+        for (i, cpu_usage) in [5., 10., 20., 30., 40.].into_iter().enumerate() {
+            history.push(sample(100 + i as u64 * 10, cpu_usage, None));
+        }
+        assert_eq!(history.len(), 4);
+
+        assert_eq!(
+            history.query(Metric::Cpu, 0, 0),
+            vec![(110, 10.), (120, 20.), (130, 30.), (140, 40.)]
+        );
+        assert_eq!(
+            history.query(Metric::Cpu, 120, 20),
+            vec![(120, 25.), (140, 40.)]
+        );
+        assert_eq!(history.query(Metric::Memory, 140, 0), vec![(140, 25.)]);
+        assert!(history.query(Metric::Battery, 0, 0).is_empty());
+        assert!("disk".parse::<Metric>().is_err());
+    }
+
+    #[test]
+    fn saves_and_loads_history() {
+        let path = std::env::temp_dir()
+            .join(format!("mechanix-metrics-{}", std::process::id()))
+            .join("history");
+        let mut history = MetricsHistory::new(8);
+        history.push(sample(100, 12.5, Some(80.)));
+        history.push(sample(110, 15., None));
+        history.save(&path).unwrap();
+
+        let loaded = MetricsHistory::load(&path, 1).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.samples().next(), Some(&sample(110, 15., None)));
+
+        let loaded = MetricsHistory::load(&path, 8).unwrap();
+        assert_eq!(loaded.query(Metric::Battery, 0, 0), vec![(100, 80.)]);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
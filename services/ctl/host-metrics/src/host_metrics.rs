@@ -1,6 +1,24 @@
-use sysinfo::{Disks, MacAddr, Networks, System};
+use std::{cmp::Reverse, path::Path, time::Instant};
 
-pub struct HostMetrics {}
+use sysinfo::{Disk, Disks, Networks, System};
+
+use crate::network::{NetworkChanges, NetworkMeter, NetworkUsage};
+use crate::thermal::{
+    battery_capacity_in, battery_temperature_in, thermal_zones_in, ThermalZone,
+    POWER_SUPPLY_CLASS_PATH, THERMAL_CLASS_PATH,
+};
+
+/// # Host Metrics
+///
+/// Keeps one sampler for the lifetime of the metrics, CPU usage (global,
+/// per core and per process) is the difference between two refreshes so a
+/// fresh `System` per call only ever reports the first sample.
+pub struct HostMetrics {
+    system: System,
+    disks: Disks,
+    networks: Networks,
+    network_meter: NetworkMeter,
+}
 
 pub struct MemoryInfo {
     pub total_memory: u64,
@@ -10,6 +28,10 @@ pub struct MemoryInfo {
 }
 
 pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub is_removable: bool,
     pub available_space: u64,
     pub total_space: u64,
     pub used_space: u64,
@@ -21,64 +43,92 @@ pub struct LoadAverage {
     pub fifteen: f64,
 }
 
-pub struct NetworkDataInfo {
-    pub interface_name: String,
-    pub mac_address: MacAddr,
-    pub received: u64,
-    pub transmitted: u64,
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// Percent of one core, a process using several cores goes above 100
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSort {
+    Cpu,
+    Memory,
 }
 
 impl HostMetrics {
     pub fn new() -> HostMetrics {
-        HostMetrics {}
+        let mut system = System::new();
+        // first sample, usage is measured from here on
+        system.refresh_cpu();
+        system.refresh_processes();
+        HostMetrics {
+            system,
+            disks: Disks::new_with_refreshed_list(),
+            networks: Networks::new(),
+            network_meter: NetworkMeter::new(),
+        }
     }
 
-    pub fn cpu_usage(&self) -> f32 {
-        let mut sys = System::new();
-        sys.refresh_cpu();
-        let cpu_usage = sys.global_cpu_info().cpu_usage();
-        cpu_usage
+    pub fn cpu_usage(&mut self) -> f32 {
+        self.system.refresh_cpu_usage();
+        self.system.global_cpu_info().cpu_usage()
     }
 
-    pub fn cpu_freq(&self) -> u64 {
-        let mut sys = System::new();
-        sys.refresh_cpu();
-        let cpu_freq = sys.global_cpu_info().frequency();
-        cpu_freq
+    /// Usage of every core in percent, in core order
+    pub fn cpu_core_usage(&mut self) -> Vec<f32> {
+        self.system.refresh_cpu_usage();
+        self.system
+            .cpus()
+            .iter()
+            .map(|cpu| cpu.cpu_usage())
+            .collect()
     }
 
-    pub fn memory_usage(&self) -> u64 {
-        let mut sys = System::new();
-        sys.refresh_memory();
-        let memory_usage = sys.used_memory();
-        memory_usage
+    pub fn cpu_freq(&mut self) -> u64 {
+        self.system.refresh_cpu_frequency();
+        self.system
+            .cpus()
+            .iter()
+            .map(|cpu| cpu.frequency())
+            .max()
+            .unwrap_or_default()
     }
 
-    pub fn swap_usage(&self) -> u64 {
-        let mut sys = System::new();
-        sys.refresh_memory();
-        let swap_usage = sys.used_swap();
-        swap_usage
+    pub fn memory_usage(&mut self) -> u64 {
+        self.system.refresh_memory();
+        self.system.used_memory()
     }
 
-    pub fn disk_info(&self) -> DiskInfo {
-        let sys = Disks::new_with_refreshed_list();
-        let disk_info = sys.iter().next().unwrap();
+    pub fn swap_usage(&mut self) -> u64 {
+        self.system.refresh_memory();
+        self.system.used_swap()
+    }
 
-        DiskInfo {
-            available_space: disk_info.available_space(),
-            total_space: disk_info.total_space(),
-            used_space: disk_info.total_space() - disk_info.available_space(),
-        }
+    /// Disk mounted at `/`, or the first disk when there is none
+    pub fn disk_info(&mut self) -> Option<DiskInfo> {
+        self.disks.refresh();
+        let disks = self.disks.list();
+        disks
+            .iter()
+            .find(|disk| disk.mount_point() == Path::new("/"))
+            .or(disks.first())
+            .map(to_disk_info)
+    }
+
+    /// All mounted disks
+    pub fn disks(&mut self) -> Vec<DiskInfo> {
+        self.disks.refresh_list();
+        self.disks.list().iter().map(to_disk_info).collect()
     }
 
-    pub fn memory_info(&self) -> MemoryInfo {
-        let mut sys = System::new();
-        sys.refresh_memory();
-        let total_memory = sys.total_memory();
-        let used_memory = sys.used_memory();
-        let total_swap = sys.total_swap();
-        let used_swap = sys.used_swap();
+    pub fn memory_info(&mut self) -> MemoryInfo {
+        self.system.refresh_memory();
+        let total_memory = self.system.total_memory();
+        let used_memory = self.system.used_memory();
+        let total_swap = self.system.total_swap();
+        let used_swap = self.system.used_swap();
 
         MemoryInfo {
             total_memory,
@@ -88,16 +138,50 @@ impl HostMetrics {
         }
     }
 
-    pub fn swap_info(&self) -> u64 {
-        let mut sys = System::new();
-        sys.refresh_memory();
-        let swap_info = sys.total_swap();
-        swap_info
+    pub fn swap_info(&mut self) -> u64 {
+        self.system.refresh_memory();
+        self.system.total_swap()
+    }
+
+    /// Processes using the most CPU or memory, highest first
+    pub fn top_processes(&mut self, count: usize, sort: ProcessSort) -> Vec<ProcessInfo> {
+        self.system.refresh_processes();
+        let mut processes: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            .map(|process| ProcessInfo {
+                pid: process.pid().as_u32(),
+                name: process.name().to_string(),
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+            })
+            .collect();
+
+        match sort {
+            ProcessSort::Cpu => processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage)),
+            ProcessSort::Memory => processes.sort_by_key(|process| Reverse(process.memory)),
+        };
+        processes.truncate(count);
+        processes
+    }
+
+    pub fn thermal_zones(&self) -> Vec<ThermalZone> {
+        thermal_zones_in(Path::new(THERMAL_CLASS_PATH))
+    }
+
+    /// Battery temperature in degrees Celsius, `None` when no battery reports it
+    pub fn battery_temperature(&self) -> Option<f32> {
+        battery_temperature_in(Path::new(POWER_SUPPLY_CLASS_PATH))
+    }
+
+    /// Battery charge in percent, `None` when there is no battery
+    pub fn battery_percentage(&self) -> Option<f32> {
+        battery_capacity_in(Path::new(POWER_SUPPLY_CLASS_PATH))
     }
 
     pub fn uptime(&self) -> u64 {
-        let uptime = System::uptime();
-        uptime
+        System::uptime()
     }
 
     //load average
@@ -111,20 +195,75 @@ impl HostMetrics {
         }
     }
 
-    //network usage
-    pub fn network_usage(&self) -> Vec<NetworkDataInfo> {
-        let mut networks = Networks::new_with_refreshed_list();
-        networks.refresh();
-        let mut collected_data = Vec::new();
-        for (interface_name, data) in networks.iter() {
-            let network_data = NetworkDataInfo {
-                interface_name: interface_name.clone(),
-                mac_address: data.mac_address(),
-                received: data.received(),
-                transmitted: data.transmitted(),
-            };
-            collected_data.push(network_data);
-        }
-        collected_data
+    /// Reads the interface counters into the network meter and returns the
+    /// interfaces that came up or went away since the previous refresh,
+    /// including the ones noticed by `network_usage` in between
+    pub fn refresh_networks(&mut self) -> NetworkChanges {
+        self.update_network_meter();
+        self.network_meter.take_changes()
+    }
+
+    /// Throughput and byte counts of every interface, the interface changes
+    /// are left for `refresh_networks`
+    pub fn network_usage(&mut self) -> Vec<NetworkUsage> {
+        self.update_network_meter();
+        self.network_meter.usage()
+    }
+
+    /// Starts counting the bytes since now, for one interface or all of them
+    pub fn reset_network_mark(&mut self, interface_name: Option<&str>) {
+        self.network_meter.reset_mark(interface_name);
+    }
+
+    fn update_network_meter(&mut self) {
+        self.networks.refresh_list();
+        let counters = self.networks.iter().map(|(interface_name, data)| {
+            (
+                interface_name.clone(),
+                data.mac_address().to_string(),
+                data.total_received(),
+                data.total_transmitted(),
+            )
+        });
+        self.network_meter.update(counters, Instant::now());
+    }
+}
+
+impl Default for HostMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_disk_info(disk: &Disk) -> DiskInfo {
+    DiskInfo {
+        name: disk.name().to_string_lossy().to_string(),
+        mount_point: disk.mount_point().to_string_lossy().to_string(),
+        file_system: disk.file_system().to_string_lossy().to_string(),
+        is_removable: disk.is_removable(),
+        available_space: disk.available_space(),
+        total_space: disk.total_space(),
+        used_space: disk.total_space().saturating_sub(disk.available_space()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_cpu_and_processes_across_calls() {
+        let mut host_metrics = HostMetrics::new();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        let cores = host_metrics.cpu_core_usage();
+        assert!(!cores.is_empty());
+        assert!(cores.iter().all(|usage| (0. ..=100.).contains(usage)));
+
+        let processes = host_metrics.top_processes(3, ProcessSort::Memory);
+        assert!(processes.len() <= 3);
+        assert!(processes
+            .windows(2)
+            .all(|pair| pair[0].memory >= pair[1].memory));
     }
 }
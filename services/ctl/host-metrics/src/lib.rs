@@ -1,2 +1,8 @@
+mod history;
 mod host_metrics;
-pub use host_metrics::HostMetrics;
+mod network;
+mod thermal;
+pub use history::{Metric, MetricsHistory, MetricsSample};
+pub use host_metrics::{DiskInfo, HostMetrics, ProcessInfo, ProcessSort};
+pub use network::{NetworkChanges, NetworkUsage, MIN_RATE_INTERVAL};
+pub use thermal::{ThermalZone, POWER_SUPPLY_CLASS_PATH, THERMAL_CLASS_PATH};
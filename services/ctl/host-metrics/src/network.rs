@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Counters read closer together than this keep the previous rate, a rate
+/// over a few milliseconds is mostly noise
+pub const MIN_RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkUsage {
+    pub interface_name: String,
+    pub mac_address: String,
+    /// Bytes per second
+    pub received_rate: u64,
+    pub transmitted_rate: u64,
+    /// Bytes since the interface came up
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    /// Bytes since the mark was last reset
+    pub received_since_mark: u64,
+    pub transmitted_since_mark: u64,
+}
+
+/// Interfaces that came up or went away since the previous update
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetworkChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl NetworkChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Adds the `later` changes, an interface that came up and went away in
+    /// between is left out
+    fn merge(&mut self, later: &NetworkChanges) {
+        for interface_name in &later.added {
+            if let Some(i) = self.removed.iter().position(|name| name == interface_name) {
+                self.removed.remove(i);
+            } else if !self.added.contains(interface_name) {
+                self.added.push(interface_name.clone());
+            }
+        }
+        for interface_name in &later.removed {
+            if let Some(i) = self.added.iter().position(|name| name == interface_name) {
+                self.added.remove(i);
+            } else if !self.removed.contains(interface_name) {
+                self.removed.push(interface_name.clone());
+            }
+        }
+        self.added.sort();
+        self.removed.sort();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InterfaceState {
+    mac_address: String,
+    total_received: u64,
+    total_transmitted: u64,
+    at: Instant,
+    received_rate: u64,
+    transmitted_rate: u64,
+    mark_received: u64,
+    mark_transmitted: u64,
+}
+
+/// # Network Meter
+///
+/// Turns the byte counters of the interfaces into rates by comparing them
+/// with the previous reading, and keeps a mark per interface to count the
+/// bytes since (for example the data used today). The interfaces that came
+/// up or went away are kept until taken, so that reading the rates does not
+/// lose them.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkMeter {
+    interfaces: HashMap<String, InterfaceState>,
+    pending: NetworkChanges,
+}
+
+impl NetworkMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the meter with the counters of every interface present at
+    /// `now` as `(interface_name, mac_address, total_received,
+    /// total_transmitted)`
+    pub fn update<I>(&mut self, counters: I, now: Instant) -> NetworkChanges
+    where
+        I: IntoIterator<Item = (String, String, u64, u64)>,
+    {
+        let mut changes = NetworkChanges::default();
+        let mut present = vec![];
+
+        for (interface_name, mac_address, total_received, total_transmitted) in counters {
+            present.push(interface_name.clone());
+            let state = match self.interfaces.get_mut(&interface_name) {
+                Some(state) => state,
+                None => {
+                    changes.added.push(interface_name.clone());
+                    self.interfaces.insert(
+                        interface_name,
+                        InterfaceState {
+                            mac_address,
+                            total_received,
+                            total_transmitted,
+                            at: now,
+                            received_rate: 0,
+                            transmitted_rate: 0,
+                            mark_received: total_received,
+                            mark_transmitted: total_transmitted,
+                        },
+                    );
+                    continue;
+                }
+            };
+
+            let elapsed = now.saturating_duration_since(state.at);
+            if elapsed < MIN_RATE_INTERVAL {
+                continue;
+            }
+            let elapsed = elapsed.as_secs_f64();
+            // counters restart from zero when the driver is reloaded
+            state.received_rate =
+                (total_received.saturating_sub(state.total_received) as f64 / elapsed) as u64;
+            state.transmitted_rate =
+                (total_transmitted.saturating_sub(state.total_transmitted) as f64 / elapsed) as u64;
+            if total_received < state.total_received || total_transmitted < state.total_transmitted
+            {
+                state.mark_received = 0;
+                state.mark_transmitted = 0;
+            }
+            state.mac_address = mac_address;
+            state.total_received = total_received;
+            state.total_transmitted = total_transmitted;
+            state.at = now;
+        }
+
+        self.interfaces.retain(|interface_name, _| {
+            let is_present = present.contains(interface_name);
+            if !is_present {
+                changes.removed.push(interface_name.clone());
+            }
+            is_present
+        });
+        changes.added.sort();
+        changes.removed.sort();
+        self.pending.merge(&changes);
+        changes
+    }
+
+    /// Interfaces that came up or went away since the changes were last taken
+    pub fn take_changes(&mut self) -> NetworkChanges {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Usage of every interface, sorted by name
+    pub fn usage(&self) -> Vec<NetworkUsage> {
+        let mut usage: Vec<NetworkUsage> = self
+            .interfaces
+            .iter()
+            .map(|(interface_name, state)| NetworkUsage {
+                interface_name: interface_name.clone(),
+                mac_address: state.mac_address.clone(),
+                received_rate: state.received_rate,
+                transmitted_rate: state.transmitted_rate,
+                total_received: state.total_received,
+                total_transmitted: state.total_transmitted,
+                received_since_mark: state.total_received.saturating_sub(state.mark_received),
+                transmitted_since_mark: state
+                    .total_transmitted
+                    .saturating_sub(state.mark_transmitted),
+            })
+            .collect();
+        usage.sort_by(|a, b| a.interface_name.cmp(&b.interface_name));
+        usage
+    }
+
+    /// Starts counting the bytes since now, for one interface or all of them
+    pub fn reset_mark(&mut self, interface_name: Option<&str>) {
+        for (name, state) in self.interfaces.iter_mut() {
+            if interface_name.is_none() || interface_name == Some(name.as_str()) {
+                state.mark_received = state.total_received;
+                state.mark_transmitted = state.total_transmitted;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(interfaces: &[(&str, u64, u64)]) -> Vec<(String, String, u64, u64)> {
+        interfaces
+            .iter()
+            .map(|(name, received, transmitted)| {
+                (name.to_string(), String::new(), *received, *transmitted)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tracks_rates_marks_and_interface_changes() {
+        let start = Instant::now();
+        let mut meter = NetworkMeter::new();
+
+        let changes = meter.update(counters(&[("wlan0", 1000, 500), ("lo", 0, 0)]), start);
+        assert_eq!(changes.added, vec!["lo", "wlan0"]);
+
+        // too soon for a rate
+        meter.update(
+            counters(&[("wlan0", 1100, 500), ("lo", 0, 0)]),
+            start + Duration::from_millis(100),
+        );
+        assert_eq!(meter.usage()[1].received_rate, 0);
+
+        let changes = meter.update(
+            counters(&[("wlan0", 5000, 2500)]),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(changes.removed, vec!["lo"]);
+        let usage = meter.usage();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].received_rate, 2000);
+        assert_eq!(usage[0].transmitted_rate, 1000);
+        assert_eq!(usage[0].received_since_mark, 4000);
+
+        meter.reset_mark(Some("wlan0"));
+        meter.update(
+            counters(&[("wlan0", 6000, 2500)]),
+            start + Duration::from_secs(4),
+        );
+        let usage = meter.usage();
+        assert_eq!(usage[0].received_rate, 500);
+        assert_eq!(usage[0].received_since_mark, 1000);
+        assert_eq!(usage[0].total_received, 6000);
+    }
+
+    #[test]
+    fn keeps_interface_changes_until_taken() {
+        let start = Instant::now();
+        let mut meter = NetworkMeter::new();
+
+        meter.update(counters(&[("wlan0", 0, 0), ("lo", 0, 0)]), start);
+        meter.update(counters(&[("wlan0", 0, 0)]), start + Duration::from_secs(2));
+        let changes = meter.take_changes();
+        assert_eq!(changes.added, vec!["wlan0"]);
+        assert!(changes.removed.is_empty());
+        assert!(meter.take_changes().is_empty());
+
+        // usb0 came and went between two takes
+        meter.update(
+            counters(&[("wlan0", 0, 0), ("usb0", 0, 0)]),
+            start + Duration::from_secs(4),
+        );
+        meter.update(counters(&[]), start + Duration::from_secs(6));
+        let changes = meter.take_changes();
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.removed, vec!["wlan0"]);
+    }
+}
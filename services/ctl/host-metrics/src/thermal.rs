@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Directory the kernel exposes thermal zones in
+pub const THERMAL_CLASS_PATH: &str = "/sys/class/thermal";
+
+/// Directory the kernel exposes power supplies in
+pub const POWER_SUPPLY_CLASS_PATH: &str = "/sys/class/power_supply";
+
+pub struct ThermalZone {
+    pub name: String,
+    pub path: PathBuf,
+    /// Degrees Celsius
+    pub temperature: f32,
+}
+
+/// Thermal zones in `dir` with a readable temperature, sorted by path
+pub fn thermal_zones_in(dir: &Path) -> Vec<ThermalZone> {
+    let mut zones: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().starts_with("thermal_zone"))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        Err(_) => return vec![],
+    };
+    zones.sort();
+
+    zones
+        .into_iter()
+        .filter_map(|path| {
+            // `temp` is in millidegrees
+            let temperature = read_value(&path.join("temp"))? / 1000.;
+            let name = fs::read_to_string(path.join("type"))
+                .map(|name| name.trim().to_string())
+                .unwrap_or_default();
+            Some(ThermalZone {
+                name,
+                path,
+                temperature,
+            })
+        })
+        .collect()
+}
+
+/// Temperature in degrees Celsius of the first battery in `dir` that reports one
+pub fn battery_temperature_in(dir: &Path) -> Option<f32> {
+    let mut supplies: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    supplies.sort();
+
+    supplies
+        .iter()
+        .filter(|path| is_battery(path))
+        // `temp` is in tenths of a degree
+        .find_map(|path| read_value(&path.join("temp")))
+        .map(|temperature| temperature / 10.)
+}
+
+/// Charge in percent of the first battery in `dir` that reports one
+pub fn battery_capacity_in(dir: &Path) -> Option<f32> {
+    let mut supplies: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    supplies.sort();
+
+    supplies
+        .iter()
+        .filter(|path| is_battery(path))
+        .find_map(|path| read_value(&path.join("capacity")))
+}
+
+fn is_battery(path: &Path) -> bool {
+    fs::read_to_string(path.join("type"))
+        .map(|supply_type| supply_type.trim() == "Battery")
+        .unwrap_or(false)
+}
+
+fn read_value(path: &Path) -> Option<f32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_fake_thermal_and_power_supply_trees() {
+        let dir = std::env::temp_dir().join(format!("mechanix-thermal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let thermal = dir.join("thermal");
+        for (zone, zone_type, temp) in [
+            ("thermal_zone1", "gpu-thermal", "45500"),
+            ("thermal_zone0", "cpu-thermal", "51000"),
+        ] {
+            fs::create_dir_all(thermal.join(zone)).unwrap();
+            fs::write(thermal.join(zone).join("type"), format!("{}\n", zone_type)).unwrap();
+            fs::write(thermal.join(zone).join("temp"), format!("{}\n", temp)).unwrap();
+        }
+        // cooling devices live next to the zones
+        fs::create_dir_all(thermal.join("cooling_device0")).unwrap();
+
+        let zones = thermal_zones_in(&thermal);
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].name, "cpu-thermal");
+        assert_eq!(zones[0].temperature, 51.);
+        assert_eq!(zones[1].temperature, 45.5);
+
+        let power_supply = dir.join("power_supply");
+        fs::create_dir_all(power_supply.join("AC")).unwrap();
+        fs::write(power_supply.join("AC").join("type"), "Mains\n").unwrap();
+        assert_eq!(battery_temperature_in(&power_supply), None);
+
+        fs::create_dir_all(power_supply.join("BAT0")).unwrap();
+        fs::write(power_supply.join("BAT0").join("type"), "Battery\n").unwrap();
+        fs::write(power_supply.join("BAT0").join("temp"), "312\n").unwrap();
+        assert_eq!(battery_temperature_in(&power_supply), Some(31.2));
+        assert_eq!(battery_capacity_in(&power_supply), None);
+        fs::write(power_supply.join("BAT0").join("capacity"), "87\n").unwrap();
+        assert_eq!(battery_capacity_in(&power_supply), Some(87.));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
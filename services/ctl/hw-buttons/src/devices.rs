@@ -0,0 +1,345 @@
+use anyhow::Result;
+use evdev::Device;
+use futures_util::StreamExt;
+use inotify::{EventStream, Inotify, WatchMask};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{sleep_until, Instant},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    hw_button::{ButtonEvent, HwButton},
+    keymap::KeyMap,
+};
+
+/// Directory the kernel exposes input devices in
+pub const INPUT_DEVICES_PATH: &str = "/dev/input";
+
+/// udev sets the permissions of a node after it appears, wait for it before
+/// opening the device
+const HOTPLUG_SETTLE: Duration = Duration::from_millis(500);
+
+/// Rescan interval when `/dev/input` cannot be watched
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ButtonDeviceStatus {
+    pub path: PathBuf,
+    pub name: String,
+    pub available: bool,
+    /// Actions of the key map the device has keys for
+    pub actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ButtonDevicesEvent {
+    Button(ButtonEvent),
+    /// A device was opened or went away, see `status`
+    Changed,
+}
+
+enum DeviceMessage {
+    Button(ButtonEvent),
+    Closed(PathBuf, String),
+}
+
+struct OpenDevice {
+    name: String,
+    actions: Vec<String>,
+    task: JoinHandle<()>,
+}
+
+/// # Button Devices
+///
+/// Listens on every input device with a key of the key map: the configured
+/// paths and, with `discover`, any `event*` node in `/dev/input` that has a
+/// mapped key. Devices missing at start or unplugged later are opened when
+/// they (re)appear.
+///
+/// `next` is cancel safe, a pending rescan is kept in `rescan_at` and not in
+/// the future, so it can be raced with other events.
+pub struct ButtonDevices {
+    keymap: KeyMap,
+    configured: Vec<PathBuf>,
+    discover: bool,
+    open: HashMap<PathBuf, OpenDevice>,
+    tx: mpsc::Sender<DeviceMessage>,
+    rx: mpsc::Receiver<DeviceMessage>,
+    hotplug: Option<EventStream<[u8; 1024]>>,
+    /// When to rescan next, after a hotplug event settled or periodically
+    /// without a watch
+    rescan_at: Option<Instant>,
+}
+
+impl ButtonDevices {
+    pub fn new(keymap: KeyMap, configured: Vec<PathBuf>, discover: bool) -> Self {
+        let hotplug = match watch_input_devices() {
+            Ok(hotplug) => Some(hotplug),
+            Err(e) => {
+                warn!(
+                    task = "button_devices",
+                    "cannot watch {}, rescanning every {:?} - {}",
+                    INPUT_DEVICES_PATH,
+                    RESCAN_INTERVAL,
+                    e
+                );
+                None
+            }
+        };
+        let (tx, rx) = mpsc::channel(32);
+        let rescan_at = hotplug.is_none().then(|| Instant::now() + RESCAN_INTERVAL);
+        let mut devices = ButtonDevices {
+            keymap,
+            configured,
+            discover,
+            open: HashMap::new(),
+            tx,
+            rx,
+            hotplug,
+            rescan_at,
+        };
+        devices.rescan();
+        devices
+    }
+
+    /// Opens the devices that are present but not open yet, returns whether
+    /// any was opened
+    pub fn rescan(&mut self) -> bool {
+        let mut candidates: Vec<PathBuf> = self
+            .configured
+            .iter()
+            .filter(|path| path.exists())
+            .cloned()
+            .collect();
+        if self.discover {
+            candidates.extend(event_nodes_in(Path::new(INPUT_DEVICES_PATH)));
+        }
+
+        let mut opened = false;
+        for path in candidates {
+            if self.open.contains_key(&path) {
+                continue;
+            }
+            // a configured path may be a by-path link to a discovered node
+            let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if self.open.keys().any(|open| {
+                fs::canonicalize(open)
+                    .map(|open| open == canonical)
+                    .unwrap_or(false)
+            }) {
+                continue;
+            }
+
+            let is_configured = self.configured.contains(&path);
+            match self.open_device(&path, is_configured) {
+                Ok(true) => opened = true,
+                Ok(false) => (),
+                Err(e) if is_configured => {
+                    warn!(task = "button_devices", "cannot open {:?} - {}", path, e)
+                }
+                Err(_) => (),
+            }
+        }
+        opened
+    }
+
+    fn open_device(&mut self, path: &Path, is_configured: bool) -> Result<bool> {
+        let device = Device::open(path)?;
+        let actions = self.keymap.actions_of(&device);
+        if actions.is_empty() && !is_configured {
+            return Ok(false);
+        }
+        let name = device.name().unwrap_or_default().to_string();
+        let mut button = HwButton::from_device(device, self.keymap.clone())?;
+
+        let tx = self.tx.clone();
+        let device_path = path.to_path_buf();
+        let task = tokio::spawn(async move {
+            loop {
+                match button.poll().await {
+                    Ok(event) => {
+                        if tx.send(DeviceMessage::Button(event)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx
+                            .send(DeviceMessage::Closed(device_path, e.to_string()))
+                            .await;
+                        break;
+                    }
+                }
+            }
+        });
+
+        info!(
+            task = "button_devices",
+            "listening on {:?} ({}) for {:?}", path, name, actions
+        );
+        self.open.insert(
+            path.to_path_buf(),
+            OpenDevice {
+                name,
+                actions,
+                task,
+            },
+        );
+        Ok(true)
+    }
+
+    /// Waits for the next button event or change of the devices
+    pub async fn next(&mut self) -> ButtonDevicesEvent {
+        loop {
+            let hotplug = self.hotplug.as_mut();
+            // whether the watch is still there
+            let hotplug = async {
+                match hotplug {
+                    Some(hotplug) => hotplug.next().await.is_some(),
+                    None => std::future::pending().await,
+                }
+            };
+            let rescan_at = self.rescan_at;
+            let rescan = async {
+                match rescan_at {
+                    Some(rescan_at) => sleep_until(rescan_at).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                message = self.rx.recv() => match message {
+                    Some(DeviceMessage::Button(event)) => return ButtonDevicesEvent::Button(event),
+                    Some(DeviceMessage::Closed(path, e)) => {
+                        warn!(task = "button_devices", "{:?} went away - {}", path, e);
+                        self.open.remove(&path);
+                        return ButtonDevicesEvent::Changed;
+                    }
+                    // the sender half lives in self
+                    None => unreachable!(),
+                },
+                is_watching = hotplug => {
+                    if !is_watching {
+                        warn!(
+                            task = "button_devices",
+                            "watch of {} ended, rescanning every {:?}",
+                            INPUT_DEVICES_PATH,
+                            RESCAN_INTERVAL
+                        );
+                        self.hotplug = None;
+                    }
+                    // the first event of a hotplug sets the deadline, the
+                    // rest of it lands before the rescan
+                    if self.rescan_at.is_none() {
+                        self.rescan_at = Some(Instant::now() + HOTPLUG_SETTLE);
+                    }
+                }
+                _ = rescan => {
+                    self.rescan_at = self
+                        .hotplug
+                        .is_none()
+                        .then(|| Instant::now() + RESCAN_INTERVAL);
+                    if self.rescan() {
+                        return ButtonDevicesEvent::Changed;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Configured devices and the discovered devices that are open, sorted
+    /// by path
+    pub fn status(&self) -> Vec<ButtonDeviceStatus> {
+        let mut status: Vec<ButtonDeviceStatus> = self
+            .open
+            .iter()
+            .map(|(path, device)| ButtonDeviceStatus {
+                path: path.clone(),
+                name: device.name.clone(),
+                available: true,
+                actions: device.actions.clone(),
+            })
+            .collect();
+        status.extend(
+            self.configured
+                .iter()
+                .filter(|path| !self.open.contains_key(*path))
+                .map(|path| ButtonDeviceStatus {
+                    path: path.clone(),
+                    name: String::new(),
+                    available: false,
+                    actions: vec![],
+                }),
+        );
+        status.sort_by(|a, b| a.path.cmp(&b.path));
+        status
+    }
+}
+
+impl Drop for ButtonDevices {
+    fn drop(&mut self) {
+        for device in self.open.values() {
+            device.task.abort();
+        }
+    }
+}
+
+fn watch_input_devices() -> Result<EventStream<[u8; 1024]>> {
+    let inotify = Inotify::init()?;
+    inotify.watches().add(
+        INPUT_DEVICES_PATH,
+        WatchMask::CREATE | WatchMask::ATTRIB | WatchMask::DELETE,
+    )?;
+    Ok(inotify.into_event_stream([0; 1024])?)
+}
+
+/// `event*` nodes in `dir`, sorted
+fn event_nodes_in(dir: &Path) -> Vec<PathBuf> {
+    let mut nodes: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().starts_with("event"))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        Err(e) => {
+            error!(task = "button_devices", "cannot read {:?} - {}", dir, e);
+            vec![]
+        }
+    };
+    nodes.sort();
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_event_nodes() {
+        let dir = std::env::temp_dir().join(format!("mechanix-input-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("by-path")).unwrap();
+        for node in ["event2", "event0", "mice", "mouse0"] {
+            fs::write(dir.join(node), "").unwrap();
+        }
+
+        assert_eq!(
+            event_nodes_in(&dir),
+            vec![dir.join("event0"), dir.join("event2")]
+        );
+        assert!(event_nodes_in(&dir.join("missing")).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
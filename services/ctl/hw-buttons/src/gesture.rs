@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use zbus::zvariant::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum Gesture {
+    ShortPress,
+    LongPress,
+    DoublePress,
+    /// Keys of a configured chord held together
+    Chord,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct GestureEvent {
+    pub gesture: Gesture,
+    /// Action of the key, or the actions of the chord as configured
+    pub actions: Vec<String>,
+}
+
+impl GestureEvent {
+    fn new(gesture: Gesture, actions: Vec<String>) -> Self {
+        GestureEvent { gesture, actions }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GestureSettings {
+    /// Held at least this long is a long press, fired while still held
+    pub long_press: Duration,
+    /// Second press within this window after a release is a double press,
+    /// short presses are reported once the window has passed
+    pub double_press: Duration,
+    /// Sets of actions that form a chord when held together
+    pub chords: Vec<Vec<String>>,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            long_press: Duration::from_millis(800),
+            double_press: Duration::from_millis(300),
+            chords: vec![vec!["power".to_string(), "volume_down".to_string()]],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HeldKey {
+    action: String,
+    pressed_at: Instant,
+    /// Already part of a long press, double press or chord
+    consumed: bool,
+}
+
+/// # Gesture Detector
+///
+/// Turns presses and releases of mapped keys into short, long and double
+/// presses and chords. Time only moves through the `now` passed in, the
+/// caller calls `tick` at `next_deadline` so pending gestures fire without
+/// another key event.
+#[derive(Debug)]
+pub struct GestureDetector {
+    settings: GestureSettings,
+    held: Vec<HeldKey>,
+    pending_short: Option<(String, Instant)>,
+}
+
+impl GestureDetector {
+    pub fn new(settings: GestureSettings) -> Self {
+        GestureDetector {
+            settings,
+            held: vec![],
+            pending_short: None,
+        }
+    }
+
+    pub fn key_down(&mut self, action: &str, now: Instant) -> Vec<GestureEvent> {
+        let mut events = vec![];
+        // auto repeat or a press missed its release
+        if self.held.iter().any(|key| key.action == action) {
+            return events;
+        }
+
+        let mut consumed = false;
+        match self.pending_short.take() {
+            Some((pending, released_at))
+                if pending == action
+                    && now.saturating_duration_since(released_at) < self.settings.double_press =>
+            {
+                events.push(GestureEvent::new(
+                    Gesture::DoublePress,
+                    vec![action.to_string()],
+                ));
+                consumed = true;
+            }
+            Some((pending, _)) => {
+                events.push(GestureEvent::new(Gesture::ShortPress, vec![pending]));
+            }
+            None => (),
+        };
+        self.held.push(HeldKey {
+            action: action.to_string(),
+            pressed_at: now,
+            consumed,
+        });
+
+        let chord = self.settings.chords.iter().find(|chord| {
+            chord.len() == self.held.len()
+                && chord
+                    .iter()
+                    .all(|action| self.held.iter().any(|key| &key.action == action))
+        });
+        if let Some(chord) = chord {
+            events.push(GestureEvent::new(Gesture::Chord, chord.clone()));
+            self.held.iter_mut().for_each(|key| key.consumed = true);
+        }
+
+        events
+    }
+
+    pub fn key_up(&mut self, action: &str, now: Instant) -> Vec<GestureEvent> {
+        let mut events = vec![];
+        let position = match self.held.iter().position(|key| key.action == action) {
+            Some(position) => position,
+            None => return events,
+        };
+        let key = self.held.remove(position);
+        if key.consumed {
+            return events;
+        }
+
+        if now.saturating_duration_since(key.pressed_at) >= self.settings.long_press {
+            events.push(GestureEvent::new(Gesture::LongPress, vec![key.action]));
+        } else if self.settings.double_press.is_zero() {
+            events.push(GestureEvent::new(Gesture::ShortPress, vec![key.action]));
+        } else {
+            self.pending_short = Some((key.action, now));
+        }
+        events
+    }
+
+    /// Fires the long presses and short presses that are due at `now`
+    pub fn tick(&mut self, now: Instant) -> Vec<GestureEvent> {
+        let mut events = vec![];
+        for key in self.held.iter_mut() {
+            if !key.consumed
+                && now.saturating_duration_since(key.pressed_at) >= self.settings.long_press
+            {
+                key.consumed = true;
+                events.push(GestureEvent::new(
+                    Gesture::LongPress,
+                    vec![key.action.clone()],
+                ));
+            }
+        }
+
+        let is_due = match &self.pending_short {
+            Some((_, released_at)) => {
+                now.saturating_duration_since(*released_at) >= self.settings.double_press
+            }
+            None => false,
+        };
+        if is_due {
+            if let Some((action, _)) = self.pending_short.take() {
+                events.push(GestureEvent::new(Gesture::ShortPress, vec![action]));
+            }
+        }
+        events
+    }
+
+    /// When `tick` has something to fire next, `None` when nothing is pending
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let long_press = self
+            .held
+            .iter()
+            .filter(|key| !key.consumed)
+            .map(|key| key.pressed_at + self.settings.long_press);
+        let short_press = self
+            .pending_short
+            .iter()
+            .map(|(_, released_at)| *released_at + self.settings.double_press);
+        long_press.chain(short_press).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gesture(gesture: Gesture, actions: &[&str]) -> GestureEvent {
+        GestureEvent::new(gesture, actions.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn detects_presses_and_chords() {
+        let ms = Duration::from_millis;
+        let start = Instant::now();
+        let mut detector = GestureDetector::new(GestureSettings::default());
+
+        // short press fires once the double press window has passed
+        assert!(detector.key_down("home", start).is_empty());
+        assert!(detector.key_up("home", start + ms(100)).is_empty());
+        assert_eq!(detector.next_deadline(), Some(start + ms(400)));
+        assert!(detector.tick(start + ms(200)).is_empty());
+        assert_eq!(
+            detector.tick(start + ms(400)),
+            vec![gesture(Gesture::ShortPress, &["home"])]
+        );
+
+        // double press
+        let start = start + ms(1000);
+        detector.key_down("home", start);
+        detector.key_up("home", start + ms(50));
+        assert_eq!(
+            detector.key_down("home", start + ms(200)),
+            vec![gesture(Gesture::DoublePress, &["home"])]
+        );
+        assert!(detector.key_up("home", start + ms(250)).is_empty());
+        assert!(detector.tick(start + ms(2000)).is_empty());
+
+        // long press fires while held, not again on release
+        let start = start + ms(3000);
+        detector.key_down("power", start);
+        assert_eq!(detector.next_deadline(), Some(start + ms(800)));
+        assert_eq!(
+            detector.tick(start + ms(800)),
+            vec![gesture(Gesture::LongPress, &["power"])]
+        );
+        assert!(detector.key_up("power", start + ms(900)).is_empty());
+        assert_eq!(detector.next_deadline(), None);
+
+        // chord, neither key reports a press of its own
+        let start = start + ms(2000);
+        assert!(detector.key_down("volume_down", start).is_empty());
+        assert_eq!(
+            detector.key_down("power", start + ms(50)),
+            vec![gesture(Gesture::Chord, &["power", "volume_down"])]
+        );
+        assert!(detector.tick(start + ms(1000)).is_empty());
+        assert!(detector.key_up("power", start + ms(1100)).is_empty());
+        assert!(detector.key_up("volume_down", start + ms(1200)).is_empty());
+        assert_eq!(detector.next_deadline(), None);
+    }
+}
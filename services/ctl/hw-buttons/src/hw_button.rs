@@ -1,36 +1,61 @@
-use evdev::{EventStream, EventType};
+use anyhow::Result;
+use evdev::{Device, EventStream, EventType};
 
-use crate::utils::{get_device_stream, Key, KeyEvent};
+use crate::{
+    keymap::KeyMap,
+    utils::{get_device_stream, Key, KeyEvent},
+};
+
+/// Key event of a mapped key with the action it is mapped to, `event` holds
+/// `Key::Unknown` for custom actions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ButtonEvent {
+    pub action: String,
+    pub event: KeyEvent,
+}
 
 pub struct HwButton {
     pub event_stream: EventStream,
+    keymap: KeyMap,
 }
 
 impl HwButton {
-    pub fn new(path: String) -> Self {
-        let event_stream = get_device_stream(path).unwrap();
-        Self { event_stream }
+    pub fn new(path: &str, keymap: KeyMap) -> Result<Self> {
+        let event_stream = get_device_stream(path)?;
+        Ok(Self {
+            event_stream,
+            keymap,
+        })
+    }
+
+    pub fn from_device(device: Device, keymap: KeyMap) -> Result<Self> {
+        let event_stream = device.into_event_stream()?;
+        Ok(Self {
+            event_stream,
+            keymap,
+        })
     }
 
-    pub async fn poll(&mut self) -> KeyEvent {
+    /// Waits for the next event of a mapped key, errors when the device
+    /// stream fails (for example when the device is unplugged)
+    pub async fn poll(&mut self) -> Result<ButtonEvent> {
         loop {
-            let event = self.event_stream.next_event().await.unwrap();
-            match event.event_type() {
-                EventType::KEY => {
-                    let key = event.code();
-                    let key: Key = evdev::Key(key).into();
-                    if key == Key::Unknown {
-                        continue;
-                    }
-                    return match event.value() {
-                        0 => KeyEvent::Released(key),
-                        1 => KeyEvent::Pressed(key),
-                        2 => KeyEvent::Pressing(key),
-                        _ => unreachable!(),
-                    };
-                }
-                _ => {}
+            let event = self.event_stream.next_event().await?;
+            if event.event_type() != EventType::KEY {
+                continue;
             }
+            let action = match self.keymap.action(event.code()) {
+                Some(action) => action.to_string(),
+                None => continue,
+            };
+            let key = Key::from_action(&action);
+            let event = match event.value() {
+                0 => KeyEvent::Released(key),
+                1 => KeyEvent::Pressed(key),
+                2 => KeyEvent::Pressing(key),
+                _ => continue,
+            };
+            return Ok(ButtonEvent { action, event });
         }
     }
 }
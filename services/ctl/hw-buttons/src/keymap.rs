@@ -0,0 +1,94 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+/// # Key Map
+///
+/// Maps evdev key codes to action names. Built-in actions (`power`, `home`,
+/// `volume_up`, `volume_down`, `camera`) also get a [`crate::Key`], any other
+/// name is a custom action that is only reported through gestures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    actions: HashMap<u16, String>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let actions = [
+            (evdev::Key::KEY_POWER, "power"),
+            (evdev::Key::KEY_FN_1, "home"),
+            (evdev::Key::KEY_VOLUMEUP, "volume_up"),
+            (evdev::Key::KEY_VOLUMEDOWN, "volume_down"),
+            (evdev::Key::KEY_CAMERA, "camera"),
+        ]
+        .into_iter()
+        .map(|(key, action)| (key.code(), action.to_string()))
+        .collect();
+        KeyMap { actions }
+    }
+}
+
+impl KeyMap {
+    /// Keys are evdev names (`KEY_VOLUMEUP`) or numeric codes, values the
+    /// action names
+    pub fn new(entries: &HashMap<String, String>) -> Result<Self> {
+        let mut actions = HashMap::new();
+        for (key, action) in entries {
+            let code = match (key.parse::<u16>(), key.parse::<evdev::Key>()) {
+                (Ok(code), _) => code,
+                (_, Ok(key)) => key.code(),
+                _ => bail!("unknown key {} for action {}", key, action),
+            };
+            actions.insert(code, action.clone());
+        }
+        Ok(KeyMap { actions })
+    }
+
+    pub fn action(&self, code: u16) -> Option<&str> {
+        self.actions.get(&code).map(|action| action.as_str())
+    }
+
+    /// Actions of the keys `device` has, sorted
+    pub fn actions_of(&self, device: &evdev::Device) -> Vec<String> {
+        let keys = match device.supported_keys() {
+            Some(keys) => keys,
+            None => return vec![],
+        };
+        let mut actions: Vec<String> = self
+            .actions
+            .iter()
+            .filter(|(code, _)| keys.contains(evdev::Key::new(**code)))
+            .map(|(_, action)| action.clone())
+            .collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    #[test]
+    fn maps_named_and_numeric_keys() {
+        let entries = HashMap::from([
+            ("KEY_VOLUMEDOWN".to_string(), "volume_down".to_string()),
+            ("183".to_string(), "assistant".to_string()),
+        ]);
+        let keymap = KeyMap::new(&entries).unwrap();
+        assert_eq!(
+            keymap.action(evdev::Key::KEY_VOLUMEDOWN.code()),
+            Some("volume_down")
+        );
+        assert_eq!(keymap.action(183), Some("assistant"));
+        assert_eq!(keymap.action(evdev::Key::KEY_POWER.code()), None);
+        assert_eq!(Key::from_action("assistant"), Key::Unknown);
+
+        let entries = HashMap::from([("KEY_NOPE".to_string(), "nope".to_string())]);
+        assert!(KeyMap::new(&entries).is_err());
+
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.action(evdev::Key::KEY_CAMERA.code()), Some("camera"));
+    }
+}
@@ -1,5 +1,14 @@
 mod hw_button;
-pub use hw_button::HwButton;
+pub use hw_button::{ButtonEvent, HwButton};
+
+mod devices;
+pub use devices::{ButtonDeviceStatus, ButtonDevices, ButtonDevicesEvent, INPUT_DEVICES_PATH};
+
+mod gesture;
+pub use gesture::{Gesture, GestureDetector, GestureEvent, GestureSettings};
+
+mod keymap;
+pub use keymap::KeyMap;
 
 mod utils;
 pub use utils::{Key, KeyEvent};
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use evdev::{Device, EventStream};
 use serde::{Deserialize, Serialize};
 use zbus::zvariant::Type;
@@ -8,6 +8,25 @@ pub enum Key {
     Power,
     Home,
     Unknown,
+    // after `Unknown` so that the index of the earlier variants on the bus
+    // stays the same for existing clients
+    VolumeUp,
+    VolumeDown,
+    Camera,
+}
+
+impl Key {
+    /// Key of a built-in action of the key map, custom actions are `Unknown`
+    pub fn from_action(action: &str) -> Self {
+        match action {
+            "power" => Key::Power,
+            "home" => Key::Home,
+            "volume_up" => Key::VolumeUp,
+            "volume_down" => Key::VolumeDown,
+            "camera" => Key::Camera,
+            _ => Key::Unknown,
+        }
+    }
 }
 
 impl From<evdev::Key> for Key {
@@ -15,6 +34,9 @@ impl From<evdev::Key> for Key {
         match value {
             evdev::Key::KEY_POWER => Key::Power,
             evdev::Key::KEY_FN_1 => Key::Home,
+            evdev::Key::KEY_VOLUMEUP => Key::VolumeUp,
+            evdev::Key::KEY_VOLUMEDOWN => Key::VolumeDown,
+            evdev::Key::KEY_CAMERA => Key::Camera,
             _ => Key::Unknown,
         }
     }
@@ -27,20 +49,23 @@ pub enum KeyEvent {
     Pressing(Key),
 }
 
-pub fn get_device_stream(path: String) -> Result<EventStream> {
-    let device_r = Device::open(path);
-
-    if let Err(e) = &device_r {
-        println!("Error opening device: {:?}", e);
-    }
-
-    let device = device_r.unwrap();
+pub fn get_device_stream(path: &str) -> Result<EventStream> {
+    let device = Device::open(path).with_context(|| format!("Error opening device {}", path))?;
+    let stream = device
+        .into_event_stream()
+        .with_context(|| format!("Error getting stream of {}", path))?;
+    Ok(stream)
+}
 
-    let stream_r = device.into_event_stream();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if let Err(e) = &stream_r {
-        println!("Error getting stream {:?}", e);
+    #[test]
+    fn keeps_the_bus_index_of_keys() {
+        // zvariant sends unit variants by their index
+        assert_eq!(Key::Power as u32, 0);
+        assert_eq!(Key::Home as u32, 1);
+        assert_eq!(Key::Unknown as u32, 2);
     }
-
-    Ok(stream_r.unwrap())
 }
@@ -1,2 +1,5 @@
+mod logind;
+
 mod power;
-pub use power::Power;
+pub use power::{AcAdapterInfo, BatteryInfo, Power, SleepMode};
+pub use upower::BatteryStatus;
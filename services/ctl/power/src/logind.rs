@@ -0,0 +1,26 @@
+use zbus::{proxy, Result};
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+pub trait Manager {
+    /// Suspend method
+    fn suspend(&self, interactive: bool) -> Result<()>;
+
+    /// Hibernate method
+    fn hibernate(&self, interactive: bool) -> Result<()>;
+
+    /// HybridSleep method
+    fn hybrid_sleep(&self, interactive: bool) -> Result<()>;
+
+    /// CanSuspend method
+    fn can_suspend(&self) -> Result<String>;
+
+    /// CanHibernate method
+    fn can_hibernate(&self) -> Result<String>;
+
+    /// CanHybridSleep method
+    fn can_hybrid_sleep(&self) -> Result<String>;
+}
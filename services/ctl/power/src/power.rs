@@ -4,60 +4,125 @@ use std::{
     path::Path,
 };
 
-use system_shutdown::{hibernate, logout, reboot, shutdown};
+use anyhow::Result;
+use system_shutdown::{logout, reboot, shutdown};
+use upower::{device::DeviceProxy, upower::UPowerProxy, BatteryStatus, DeviceType};
+use zbus::Connection;
 
-use battery::Manager;
-pub struct Power {
-    battery: battery::Battery,
-}
+use crate::logind::ManagerProxy;
+
+#[derive(Default)]
+pub struct Power {}
 
-#[derive(Debug)]
-pub struct BatteryInfo<'a> {
-    pub vendor: Option<&'a str>,
-    pub model: Option<&'a str>,
-    pub serial_number: Option<&'a str>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryInfo {
+    pub native_path: String,
+    pub vendor: String,
+    pub model: String,
+    pub serial_number: String,
     pub technology: String,
-    pub state: String,
+    pub state: BatteryStatus,
+    pub percentage: f64,
+    /// Seconds until empty while discharging, `None` without an estimate
+    pub time_to_empty: Option<u64>,
+    /// Seconds until full while charging, `None` without an estimate
+    pub time_to_full: Option<u64>,
+    /// Watts going out of or into the battery
+    pub energy_rate: f64,
+    /// Full capacity as a percentage of the design capacity
+    pub health: f64,
+    /// `None` when unknown or not reported by the UPower in use
+    pub cycle_count: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcAdapterInfo {
+    pub native_path: String,
+    pub online: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepMode {
+    /// To RAM
+    Suspend,
+    /// To disk
+    Hibernate,
+    /// To RAM and disk, resumes from RAM unless power was lost
+    HybridSleep,
 }
 
 impl Power {
     pub fn new() -> Self {
-        let manager = Manager::new().unwrap();
-        let battery = manager.batteries().unwrap().next().unwrap().unwrap();
-
-        Power { battery: battery }
+        Power {}
     }
 
-    pub fn info(&self) -> String {
-        let info = format!("Battery: {:?}", self.battery);
-        info
+    /// Batteries that power the system, empty on devices without one
+    pub async fn batteries(&self) -> Result<Vec<BatteryInfo>> {
+        let connection = Connection::system().await?;
+        let mut batteries = vec![];
+        for device in devices_of_type(&connection, DeviceType::Battery).await? {
+            // peripherals report their batteries as well
+            if !device.power_supply().await? || !device.is_present().await? {
+                continue;
+            }
+            let state =
+                BatteryStatus::try_from(device.state().await?).unwrap_or(BatteryStatus::Unknown);
+            batteries.push(BatteryInfo {
+                native_path: device.native_path().await?,
+                vendor: device.vendor().await?,
+                model: device.model().await?,
+                serial_number: device.serial().await?,
+                technology: technology_name(device.technology().await?).to_string(),
+                state,
+                percentage: device.percentage().await?,
+                time_to_empty: seconds(device.time_to_empty().await?),
+                time_to_full: seconds(device.time_to_full().await?),
+                energy_rate: device.energy_rate().await?,
+                health: device.capacity().await?,
+                // `ChargeCycles` is missing before UPower 0.99.14
+                cycle_count: device.charge_cycles().await.ok().and_then(cycle_count),
+            });
+        }
+        Ok(batteries)
     }
 
-    pub fn get_battery_status(&self) -> String {
-        let status = match self.battery.state() {
-            battery::State::Charging => "Charging",
-            battery::State::Discharging => "Discharging",
-            battery::State::Empty => "Empty",
-            battery::State::Full => "Full",
-            battery::State::Unknown => "Unknown",
-            _ => "Unknown",
-        };
+    pub async fn ac_adapters(&self) -> Result<Vec<AcAdapterInfo>> {
+        let connection = Connection::system().await?;
+        let mut ac_adapters = vec![];
+        for device in devices_of_type(&connection, DeviceType::LinePower).await? {
+            ac_adapters.push(AcAdapterInfo {
+                native_path: device.native_path().await?,
+                online: device.online().await?,
+            });
+        }
+        Ok(ac_adapters)
+    }
 
-        status.to_string()
+    pub async fn is_on_battery(&self) -> Result<bool> {
+        let connection = Connection::system().await?;
+        let upower = UPowerProxy::new(&connection).await?;
+        Ok(upower.on_battery().await?)
     }
 
-    pub fn get_battery_info(&self) -> BatteryInfo {
-        BatteryInfo {
-            vendor: self.battery.vendor(),
-            model: self.battery.model(),
-            serial_number: self.battery.serial_number(),
-            technology: self.battery.technology().to_string(),
-            state: self.battery.state().to_string(),
+    /// Charge of all the batteries combined, `None` without a battery
+    pub async fn get_battery_percentage(&self) -> Result<Option<f64>> {
+        let display_device = display_device().await?;
+        if !display_device.is_present().await? {
+            return Ok(None);
         }
+        Ok(Some(display_device.percentage().await?))
     }
 
-    pub fn get_battery_percentage(&self) -> f32 {
-        self.battery.state_of_charge().value * 100.0
+    /// State of all the batteries combined, `None` without a battery
+    pub async fn get_battery_status(&self) -> Result<Option<BatteryStatus>> {
+        let display_device = display_device().await?;
+        if !display_device.is_present().await? {
+            return Ok(None);
+        }
+        let state = display_device.state().await?;
+        Ok(Some(
+            BatteryStatus::try_from(state).unwrap_or(BatteryStatus::Unknown),
+        ))
     }
 
     // we need to get all available governors form  reading this file /sys/devices/system/cpu/cpu0/cpufreq/scaling_available_governors and list it
@@ -148,14 +213,119 @@ impl Power {
         }
     }
 
-    //suspend the system
-    pub fn suspend(&self) -> Result<(), std::io::Error> {
-        match hibernate() {
-            Ok(_) => Ok(()),
-            Err(_) => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to suspend",
-            )),
+    pub async fn can_sleep(&self, mode: SleepMode) -> Result<bool> {
+        let connection = Connection::system().await?;
+        let manager = ManagerProxy::new(&connection).await?;
+        let answer = match mode {
+            SleepMode::Suspend => manager.can_suspend().await?,
+            SleepMode::Hibernate => manager.can_hibernate().await?,
+            SleepMode::HybridSleep => manager.can_hybrid_sleep().await?,
+        };
+        // `challenge` needs authentication, `na` and `no` are not possible
+        Ok(answer == "yes")
+    }
+
+    //suspend the system to RAM
+    pub async fn suspend(&self) -> Result<()> {
+        self.sleep(SleepMode::Suspend).await
+    }
+
+    //hibernate the system to disk
+    pub async fn hibernate(&self) -> Result<()> {
+        self.sleep(SleepMode::Hibernate).await
+    }
+
+    //suspend the system to RAM and disk
+    pub async fn hybrid_sleep(&self) -> Result<()> {
+        self.sleep(SleepMode::HybridSleep).await
+    }
+
+    pub async fn sleep(&self, mode: SleepMode) -> Result<()> {
+        let connection = Connection::system().await?;
+        let manager = ManagerProxy::new(&connection).await?;
+        match mode {
+            SleepMode::Suspend => manager.suspend(false).await?,
+            SleepMode::Hibernate => manager.hibernate(false).await?,
+            SleepMode::HybridSleep => manager.hybrid_sleep(false).await?,
+        };
+        Ok(())
+    }
+}
+
+async fn devices_of_type(
+    connection: &Connection,
+    device_type: DeviceType,
+) -> Result<Vec<DeviceProxy<'static>>> {
+    let device_type: u32 = device_type.into();
+    let upower = UPowerProxy::new(connection).await?;
+    let mut devices = vec![];
+    for device_path in upower.enumerate_devices().await? {
+        let device = DeviceProxy::builder(connection)
+            .path(device_path)?
+            .build()
+            .await?;
+        if device.type_().await? == device_type {
+            devices.push(device);
         }
     }
+    Ok(devices)
+}
+
+/// Composite device UPower keeps for all the batteries
+async fn display_device() -> Result<DeviceProxy<'static>> {
+    let connection = Connection::system().await?;
+    let upower = UPowerProxy::new(&connection).await?;
+    let device_path = upower.get_display_device().await?;
+    let device = DeviceProxy::builder(&connection)
+        .path(device_path)?
+        .build()
+        .await?;
+    Ok(device)
+}
+
+/// UPower reports 0 when it has no estimate
+fn seconds(value: i64) -> Option<u64> {
+    if value > 0 {
+        Some(value as u64)
+    } else {
+        None
+    }
+}
+
+/// UPower reports -1, or 0 on some drivers, when the count is unknown
+fn cycle_count(value: i32) -> Option<u32> {
+    if value > 0 {
+        Some(value as u32)
+    } else {
+        None
+    }
+}
+
+fn technology_name(technology: u32) -> &'static str {
+    match technology {
+        1 => "Lithium ion",
+        2 => "Lithium polymer",
+        3 => "Lithium iron phosphate",
+        4 => "Lead acid",
+        5 => "Nickel cadmium",
+        6 => "Nickel metal hydride",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_unknown_upower_values_to_none() {
+        assert_eq!(seconds(0), None);
+        assert_eq!(seconds(-1), None);
+        assert_eq!(seconds(5400), Some(5400));
+        assert_eq!(cycle_count(-1), None);
+        assert_eq!(cycle_count(0), None);
+        assert_eq!(cycle_count(312), Some(312));
+        assert_eq!(technology_name(1), "Lithium ion");
+        assert_eq!(technology_name(42), "Unknown");
+    }
 }
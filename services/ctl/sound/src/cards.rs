@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Result};
+
+use libpulse_binding::callbacks::ListResult;
+use libpulse_binding::context::introspect::CardInfo;
+use libpulse_binding::context::Context;
+use libpulse_binding::def::PortAvailable;
+use libpulse_binding::direction::FlagSet as DirectionFlags;
+use libpulse_binding::mainloop::standard::Mainloop;
+use libpulse_binding::proplist::properties;
+
+use crate::sound::run;
+use crate::subscription::Direction;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardProfile {
+    /// For example `a2dp-sink` or `headset-head-unit` on Bluetooth cards
+    pub name: String,
+    pub description: String,
+    pub is_available: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardPort {
+    /// For example `analog-output-speaker` or `analog-output-headphones`
+    pub name: String,
+    pub description: String,
+    pub direction: Direction,
+    /// `None` when the card cannot tell, for example without jack detection
+    pub is_available: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardInformation {
+    pub name: String,
+    pub description: String,
+    pub active_profile: Option<String>,
+    pub profiles: Vec<CardProfile>,
+    pub ports: Vec<CardPort>,
+}
+
+pub fn is_port_available(available: PortAvailable) -> Option<bool> {
+    match available {
+        PortAvailable::Yes => Some(true),
+        PortAvailable::No => Some(false),
+        PortAvailable::Unknown => None,
+    }
+}
+
+impl From<&CardInfo<'_>> for CardInformation {
+    fn from(info: &CardInfo) -> Self {
+        let profiles = info
+            .profiles
+            .iter()
+            .map(|profile| CardProfile {
+                name: profile.name.as_deref().unwrap_or_default().to_string(),
+                description: profile
+                    .description
+                    .as_deref()
+                    .unwrap_or_default()
+                    .to_string(),
+                is_available: profile.available,
+            })
+            .collect();
+        let ports = info
+            .ports
+            .iter()
+            .map(|port| CardPort {
+                name: port.name.as_deref().unwrap_or_default().to_string(),
+                description: port.description.as_deref().unwrap_or_default().to_string(),
+                direction: if port.direction.contains(DirectionFlags::OUTPUT) {
+                    Direction::Output
+                } else {
+                    Direction::Input
+                },
+                is_available: is_port_available(port.available),
+            })
+            .collect();
+
+        CardInformation {
+            name: info.name.as_deref().unwrap_or_default().to_string(),
+            description: info
+                .proplist
+                .get_str(properties::DEVICE_DESCRIPTION)
+                .unwrap_or_default(),
+            active_profile: info
+                .active_profile
+                .as_ref()
+                .and_then(|profile| profile.name.as_deref().map(str::to_string)),
+            profiles,
+            ports,
+        }
+    }
+}
+
+pub fn get_cards(main_loop: &mut Mainloop, context: &Context) -> Result<Vec<CardInformation>> {
+    run(main_loop, |output| {
+        let mut cards = vec![];
+        context
+            .introspect()
+            .get_card_info_list(move |info| match info {
+                ListResult::Item(card) => cards.push(CardInformation::from(card)),
+                ListResult::End => {
+                    *output.lock().unwrap() = Some(Ok(std::mem::take(&mut cards)));
+                }
+                ListResult::Error => *output.lock().unwrap() = Some(Err(())),
+            });
+    })?
+    .map_err(|_| anyhow!(context.errno()))
+}
+
+pub fn set_card_profile(
+    main_loop: &mut Mainloop,
+    context: &Context,
+    card: &str,
+    profile: &str,
+) -> Result<()> {
+    run(main_loop, |output| {
+        context.introspect().set_card_profile_by_name(
+            card,
+            profile,
+            Some(Box::new(move |success| {
+                *output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+            })),
+        );
+    })?
+    .map_err(|_| anyhow!(context.errno()))
+}
+
+/// Switches the active port of an output or input, for example between the
+/// speaker and the headphone jack
+pub fn set_port(
+    main_loop: &mut Mainloop,
+    context: &Context,
+    direction: Direction,
+    device: &str,
+    port: &str,
+) -> Result<()> {
+    run(main_loop, |output| {
+        let callback = Box::new(move |success: bool| {
+            *output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+        });
+        match direction {
+            Direction::Output => {
+                context
+                    .introspect()
+                    .set_sink_port_by_name(device, port, Some(callback));
+            }
+            Direction::Input => {
+                context
+                    .introspect()
+                    .set_source_port_by_name(device, port, Some(callback));
+            }
+        }
+    })?
+    .map_err(|_| anyhow!(context.errno()))
+}
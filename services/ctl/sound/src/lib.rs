@@ -1,6 +1,20 @@
 mod sound;
 pub use sound::{Proplist, Sound};
 
+mod subscription;
+pub use subscription::{
+    subscribe, Direction, SoundDevice, SoundEvent, SoundEventKind, SoundPort, SoundSnapshot,
+};
+
+mod cards;
+pub use cards::{CardInformation, CardPort, CardProfile};
+
+mod theme;
+pub use theme::{SoundTheme, FALLBACK_THEME};
+
+mod streams;
+pub use streams::StreamInformation;
+
 mod input_device;
 
 mod output_device;
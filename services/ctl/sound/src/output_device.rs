@@ -175,13 +175,16 @@ pub fn get_connected_devices<'a>(
             context.introspect().get_source_info_list(move |info| {
                 println!("Inside get_source_output_info_list callback");
                 match info {
-                    libpulse_binding::callbacks::ListResult::Item(x) => {
+                    libpulse_binding::callbacks::ListResult::Item(x)
+                        if x.monitor_of_sink.is_none() =>
+                    {
                         let source_information = SourceInformation::from(x);
                         list_of_output_devices
                             .lock()
                             .unwrap()
                             .push(source_information);
                     }
+                    libpulse_binding::callbacks::ListResult::Item(_) => {}
                     libpulse_binding::callbacks::ListResult::End => {
                         *output.lock().unwrap() = Some(Ok::<_, ()>(()));
                     }
@@ -7,12 +7,15 @@ use libpulse_binding::volume::{ChannelVolumes, Volume};
 
 pub use libpulse_binding::proplist::Proplist;
 
+use crate::cards::{get_cards, set_card_profile, set_port};
 use crate::input_device::{
     get_connected_input_devices, get_input_volumes, run_input_command, SinkInformation,
 };
 use crate::output_device::{
     get_connected_devices, get_output_volumes, run_output_command, SourceInformation,
 };
+use crate::streams::{get_streams, move_stream, set_stream_mute, set_stream_volume};
+use crate::{CardInformation, Direction, StreamInformation};
 
 pub struct Sound {}
 
@@ -72,14 +75,15 @@ impl Sound {
         Ok(())
     }
 
-    pub async fn get_connected_output_device_list(&self) -> Result<Vec<SourceInformation>> {
+    // outputs are the sinks
+    pub async fn get_connected_output_device_list(&self) -> Result<Vec<SinkInformation>> {
         let mut main_loop = Mainloop::new()
             .ok_or_else(|| eprintln!("Failed to initialize PulseAudio main loop."))
             .unwrap();
 
         let mut context = connect(&mut main_loop).unwrap();
 
-        let device_list = get_connected_devices(&mut main_loop, &mut context);
+        let device_list = get_connected_input_devices(&mut main_loop, &mut context);
 
         Ok(device_list.unwrap())
     }
@@ -126,17 +130,143 @@ impl Sound {
         Ok(())
     }
 
-    pub async fn get_connected_input_device_list(&self) -> Result<Vec<SinkInformation>> {
+    // inputs are the sources, without the monitors of the sinks
+    pub async fn get_connected_input_device_list(&self) -> Result<Vec<SourceInformation>> {
         let mut main_loop = Mainloop::new()
             .ok_or_else(|| eprintln!("Failed to initialize PulseAudio main loop."))
             .unwrap();
 
         let mut context = connect(&mut main_loop).unwrap();
 
-        let device_list = get_connected_input_devices(&mut main_loop, &mut context);
+        let device_list = get_connected_devices(&mut main_loop, &mut context);
 
         Ok(device_list.unwrap())
     }
+
+    pub async fn get_default_output(&self) -> Result<String> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        let (default_output, _) = get_default_devices(&mut main_loop, &context)?;
+        default_output.ok_or_else(|| anyhow!("No default output"))
+    }
+
+    pub async fn get_default_input(&self) -> Result<String> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        let (_, default_input) = get_default_devices(&mut main_loop, &context)?;
+        default_input.ok_or_else(|| anyhow!("No default input"))
+    }
+
+    pub async fn set_default_output(&self, device: String) -> Result<()> {
+        let (mut main_loop, mut context) = init_pulseaudio()?;
+        run(&mut main_loop, |output| {
+            context.set_default_sink(&device, move |success| {
+                *output.lock().unwrap() = Some(success);
+            });
+        })?
+        .then_some(())
+        .ok_or_else(|| anyhow!("Failed to set default output {}", device))
+    }
+
+    pub async fn set_default_input(&self, device: String) -> Result<()> {
+        let (mut main_loop, mut context) = init_pulseaudio()?;
+        run(&mut main_loop, |output| {
+            context.set_default_source(&device, move |success| {
+                *output.lock().unwrap() = Some(success);
+            });
+        })?
+        .then_some(())
+        .ok_or_else(|| anyhow!("Failed to set default input {}", device))
+    }
+
+    pub async fn list_streams(&self) -> Result<Vec<StreamInformation>> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        get_streams(&mut main_loop, &context)
+    }
+
+    /// Sets the volume of the streams of an application, of any of its
+    /// processes when `process_id` is 0
+    pub async fn set_stream_volume(
+        &self,
+        application_name: String,
+        process_id: u32,
+        volume: f64,
+    ) -> Result<()> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        set_stream_volume(
+            &mut main_loop,
+            &context,
+            &application_name,
+            process_id,
+            volume,
+        )
+    }
+
+    pub async fn mute_stream(
+        &self,
+        application_name: String,
+        process_id: u32,
+        mute: bool,
+    ) -> Result<()> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        set_stream_mute(
+            &mut main_loop,
+            &context,
+            &application_name,
+            process_id,
+            mute,
+        )
+    }
+
+    pub async fn move_stream(
+        &self,
+        application_name: String,
+        process_id: u32,
+        device: String,
+    ) -> Result<()> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        move_stream(
+            &mut main_loop,
+            &context,
+            &application_name,
+            process_id,
+            &device,
+        )
+    }
+
+    /// Sound cards with their profiles and ports
+    pub async fn list_cards(&self) -> Result<Vec<CardInformation>> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        get_cards(&mut main_loop, &context)
+    }
+
+    pub async fn set_card_profile(&self, card: String, profile: String) -> Result<()> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        set_card_profile(&mut main_loop, &context, &card, &profile)
+    }
+
+    pub async fn set_output_port(&self, device: String, port: String) -> Result<()> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        set_port(&mut main_loop, &context, Direction::Output, &device, &port)
+    }
+
+    pub async fn set_input_port(&self, device: String, port: String) -> Result<()> {
+        let (mut main_loop, context) = init_pulseaudio()?;
+        set_port(&mut main_loop, &context, Direction::Input, &device, &port)
+    }
+}
+
+/// Names of the default output and input
+fn get_default_devices(
+    main_loop: &mut Mainloop,
+    context: &Context,
+) -> Result<(Option<String>, Option<String>)> {
+    run(main_loop, |output| {
+        context.introspect().get_server_info(move |info| {
+            *output.lock().unwrap() = Some((
+                info.default_sink_name.as_deref().map(str::to_string),
+                info.default_source_name.as_deref().map(str::to_string),
+            ));
+        });
+    })
 }
 
 /// Convert a [`Volume`] to a percentage as `f64`.
@@ -0,0 +1,226 @@
+use anyhow::{anyhow, Result};
+
+use libpulse_binding::callbacks::ListResult;
+use libpulse_binding::context::introspect::SinkInputInfo;
+use libpulse_binding::context::Context;
+use libpulse_binding::mainloop::standard::Mainloop;
+use libpulse_binding::proplist::properties;
+use libpulse_binding::volume::ChannelVolumes;
+
+use crate::sound::{map_volumes, run, volume_to_percentage};
+
+/// Playback stream of an application (a sink input)
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInformation {
+    pub index: u32,
+    pub application_name: String,
+    pub process_id: Option<u32>,
+    /// Name of the output the stream plays on
+    pub output_device: String,
+    /// Loudest channel in percent
+    pub volume: f64,
+    pub is_mute: bool,
+}
+
+impl StreamInformation {
+    /// Streams are picked by application name, and by process id unless it
+    /// is 0
+    pub fn is_match(&self, application_name: &str, process_id: u32) -> bool {
+        self.application_name == application_name
+            && (process_id == 0 || self.process_id == Some(process_id))
+    }
+}
+
+struct Stream {
+    information: StreamInformation,
+    channels: ChannelVolumes,
+}
+
+impl Stream {
+    fn from(info: &SinkInputInfo, outputs: &[(u32, String)]) -> Self {
+        let application_name = info
+            .proplist
+            .get_str(properties::APPLICATION_NAME)
+            .or_else(|| info.name.as_deref().map(str::to_string))
+            .unwrap_or_default();
+        let process_id = info
+            .proplist
+            .get_str(properties::APPLICATION_PROCESS_ID)
+            .and_then(|process_id| process_id.parse().ok());
+        let output_device = outputs
+            .iter()
+            .find(|(index, _)| *index == info.sink)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_default();
+
+        Stream {
+            information: StreamInformation {
+                index: info.index,
+                application_name,
+                process_id,
+                output_device,
+                volume: volume_to_percentage(info.volume.max()),
+                is_mute: info.mute,
+            },
+            channels: info.volume,
+        }
+    }
+}
+
+fn list_outputs(main_loop: &mut Mainloop, context: &Context) -> Result<Vec<(u32, String)>> {
+    run(main_loop, |output| {
+        let mut outputs = vec![];
+        context
+            .introspect()
+            .get_sink_info_list(move |info| match info {
+                ListResult::Item(sink) => outputs.push((
+                    sink.index,
+                    sink.name.as_deref().unwrap_or_default().to_string(),
+                )),
+                ListResult::End => {
+                    *output.lock().unwrap() = Some(Ok(std::mem::take(&mut outputs)));
+                }
+                ListResult::Error => *output.lock().unwrap() = Some(Err(())),
+            });
+    })?
+    .map_err(|_| anyhow!(context.errno()))
+}
+
+fn list_streams(main_loop: &mut Mainloop, context: &Context) -> Result<Vec<Stream>> {
+    let outputs = list_outputs(main_loop, context)?;
+    run(main_loop, |output| {
+        let mut streams = vec![];
+        context
+            .introspect()
+            .get_sink_input_info_list(move |info| match info {
+                ListResult::Item(stream) => streams.push(Stream::from(stream, &outputs)),
+                ListResult::End => {
+                    *output.lock().unwrap() = Some(Ok(std::mem::take(&mut streams)));
+                }
+                ListResult::Error => *output.lock().unwrap() = Some(Err(())),
+            });
+    })?
+    .map_err(|_| anyhow!(context.errno()))
+}
+
+/// Playback streams of all applications
+pub fn get_streams(main_loop: &mut Mainloop, context: &Context) -> Result<Vec<StreamInformation>> {
+    Ok(list_streams(main_loop, context)?
+        .into_iter()
+        .map(|stream| stream.information)
+        .collect())
+}
+
+/// Matching streams, an error when there are none
+fn matching_streams(
+    main_loop: &mut Mainloop,
+    context: &Context,
+    application_name: &str,
+    process_id: u32,
+) -> Result<Vec<Stream>> {
+    let streams: Vec<Stream> = list_streams(main_loop, context)?
+        .into_iter()
+        .filter(|stream| stream.information.is_match(application_name, process_id))
+        .collect();
+    if streams.is_empty() {
+        return Err(anyhow!(
+            "No stream of {} ({}) found",
+            application_name,
+            process_id
+        ));
+    }
+    Ok(streams)
+}
+
+pub fn set_stream_volume(
+    main_loop: &mut Mainloop,
+    context: &Context,
+    application_name: &str,
+    process_id: u32,
+    volume: f64,
+) -> Result<()> {
+    for mut stream in matching_streams(main_loop, context, application_name, process_id)? {
+        map_volumes(&mut stream.channels, |_| volume);
+        let index = stream.information.index;
+        let channels = stream.channels;
+        run(main_loop, move |output| {
+            context.introspect().set_sink_input_volume(
+                index,
+                &channels,
+                Some(Box::new(move |success| {
+                    *output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+                })),
+            );
+        })?
+        .map_err(|_| anyhow!(context.errno()))?;
+    }
+    Ok(())
+}
+
+pub fn set_stream_mute(
+    main_loop: &mut Mainloop,
+    context: &Context,
+    application_name: &str,
+    process_id: u32,
+    mute: bool,
+) -> Result<()> {
+    for stream in matching_streams(main_loop, context, application_name, process_id)? {
+        let index = stream.information.index;
+        run(main_loop, move |output| {
+            context.introspect().set_sink_input_mute(
+                index,
+                mute,
+                Some(Box::new(move |success| {
+                    *output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+                })),
+            );
+        })?
+        .map_err(|_| anyhow!(context.errno()))?;
+    }
+    Ok(())
+}
+
+/// Moves the matching streams to another output
+pub fn move_stream(
+    main_loop: &mut Mainloop,
+    context: &Context,
+    application_name: &str,
+    process_id: u32,
+    device: &str,
+) -> Result<()> {
+    for stream in matching_streams(main_loop, context, application_name, process_id)? {
+        let index = stream.information.index;
+        run(main_loop, move |output| {
+            context.introspect().move_sink_input_by_name(
+                index,
+                device,
+                Some(Box::new(move |success| {
+                    *output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+                })),
+            );
+        })?
+        .map_err(|_| anyhow!(context.errno()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_streams_by_application_and_process() {
+        let stream = StreamInformation {
+            index: 3,
+            application_name: "Music".to_string(),
+            process_id: Some(1200),
+            output_device: "speaker".to_string(),
+            volume: 80.,
+            is_mute: false,
+        };
+        assert!(stream.is_match("Music", 0));
+        assert!(stream.is_match("Music", 1200));
+        assert!(!stream.is_match("Music", 1201));
+        assert!(!stream.is_match("Camera", 0));
+    }
+}
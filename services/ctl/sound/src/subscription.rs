@@ -0,0 +1,438 @@
+use anyhow::{anyhow, Result};
+use std::{cell::Cell, rc::Rc, thread, time::Duration};
+use tokio::sync::mpsc;
+
+use libpulse_binding::callbacks::ListResult;
+use libpulse_binding::context::subscribe::InterestMaskSet;
+use libpulse_binding::context::{Context, State};
+use libpulse_binding::def::PortAvailable;
+use libpulse_binding::mainloop::standard::{IterateResult, Mainloop};
+use libpulse_binding::volume::ChannelVolumes;
+
+use crate::cards::is_port_available;
+use crate::sound::{init_pulseaudio, run, volume_to_percentage};
+
+/// Wait before connecting again when the sound server is gone
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Sink
+    Output,
+    /// Source, monitors of sinks are left out
+    Input,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEventKind {
+    VolumeChanged,
+    MuteChanged,
+    /// The device became the default of its direction
+    DefaultChanged,
+    Added,
+    Removed,
+    /// Something was plugged into the jack of `port`
+    PortPlugged,
+    PortUnplugged,
+    /// The device switched to `port`
+    ActivePortChanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundDevice {
+    pub name: String,
+    pub description: String,
+    /// Loudest channel in percent
+    pub volume: f64,
+    pub is_mute: bool,
+    pub active_port: Option<String>,
+    pub ports: Vec<SoundPort>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundPort {
+    pub name: String,
+    pub description: String,
+    /// `None` without jack detection
+    pub is_available: Option<bool>,
+}
+
+impl SoundDevice {
+    fn new(
+        name: Option<&str>,
+        description: Option<&str>,
+        volume: &ChannelVolumes,
+        is_mute: bool,
+        active_port: Option<&str>,
+        ports: Vec<SoundPort>,
+    ) -> Self {
+        SoundDevice {
+            name: name.unwrap_or_default().to_string(),
+            description: description.unwrap_or_default().to_string(),
+            volume: volume_to_percentage(volume.max()),
+            is_mute,
+            active_port: active_port.map(str::to_string),
+            ports,
+        }
+    }
+}
+
+impl SoundPort {
+    fn new(name: Option<&str>, description: Option<&str>, available: PortAvailable) -> Self {
+        SoundPort {
+            name: name.unwrap_or_default().to_string(),
+            description: description.unwrap_or_default().to_string(),
+            is_available: is_port_available(available),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundEvent {
+    pub kind: SoundEventKind,
+    pub direction: Direction,
+    pub device: SoundDevice,
+    /// The device is the default of its direction
+    pub is_default: bool,
+    /// Port of `PortPlugged`, `PortUnplugged` and `ActivePortChanged`
+    pub port: Option<String>,
+}
+
+/// Devices and defaults of the sound server at one point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SoundSnapshot {
+    pub outputs: Vec<SoundDevice>,
+    pub inputs: Vec<SoundDevice>,
+    pub default_output: Option<String>,
+    pub default_input: Option<String>,
+}
+
+impl SoundSnapshot {
+    /// Events that turn `previous` into `self`
+    pub fn changes_since(&self, previous: &SoundSnapshot) -> Vec<SoundEvent> {
+        let mut events = vec![];
+        for (direction, devices, previous_devices, default, previous_default) in [
+            (
+                Direction::Output,
+                &self.outputs,
+                &previous.outputs,
+                &self.default_output,
+                &previous.default_output,
+            ),
+            (
+                Direction::Input,
+                &self.inputs,
+                &previous.inputs,
+                &self.default_input,
+                &previous.default_input,
+            ),
+        ] {
+            let is_default = |device: &SoundDevice| default.as_deref() == Some(&device.name);
+            let mut push = |kind: SoundEventKind, device: &SoundDevice, port: Option<&String>| {
+                events.push(SoundEvent {
+                    kind,
+                    direction,
+                    device: device.clone(),
+                    is_default: is_default(device),
+                    port: port.cloned(),
+                })
+            };
+
+            for device in previous_devices {
+                if !devices.iter().any(|current| current.name == device.name) {
+                    push(SoundEventKind::Removed, device, None);
+                }
+            }
+            for device in devices {
+                match previous_devices
+                    .iter()
+                    .find(|previous| previous.name == device.name)
+                {
+                    Some(previous) => {
+                        if previous.volume != device.volume {
+                            push(SoundEventKind::VolumeChanged, device, None);
+                        }
+                        if previous.is_mute != device.is_mute {
+                            push(SoundEventKind::MuteChanged, device, None);
+                        }
+                        for port in device.ports.iter() {
+                            let was_available = previous
+                                .ports
+                                .iter()
+                                .find(|previous| previous.name == port.name)
+                                .and_then(|previous| previous.is_available);
+                            match (was_available, port.is_available) {
+                                (Some(false), Some(true)) => {
+                                    push(SoundEventKind::PortPlugged, device, Some(&port.name))
+                                }
+                                (Some(true), Some(false)) => {
+                                    push(SoundEventKind::PortUnplugged, device, Some(&port.name))
+                                }
+                                _ => (),
+                            }
+                        }
+                        if previous.active_port != device.active_port {
+                            push(
+                                SoundEventKind::ActivePortChanged,
+                                device,
+                                device.active_port.as_ref(),
+                            );
+                        }
+                    }
+                    None => push(SoundEventKind::Added, device, None),
+                }
+            }
+            if default != previous_default {
+                if let Some(device) = devices.iter().find(|device| is_default(device)) {
+                    push(SoundEventKind::DefaultChanged, device, None);
+                }
+            }
+        }
+        events
+    }
+}
+
+/// Listens to the sound server on a thread of its own, with one connection
+/// that subscribes to sink, source, server and card events and reconnects
+/// when the server restarts. The thread ends once the receiver is dropped.
+pub fn subscribe() -> mpsc::UnboundedReceiver<SoundEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    thread::spawn(move || {
+        // changes while disconnected are reported once connected again
+        let mut previous: Option<SoundSnapshot> = None;
+        while !tx.is_closed() {
+            if let Err(e) = listen(&tx, &mut previous) {
+                println!("Sound subscription stopped: {}", e);
+            }
+            thread::sleep(RECONNECT_DELAY);
+        }
+    });
+    rx
+}
+
+fn listen(
+    tx: &mpsc::UnboundedSender<SoundEvent>,
+    previous: &mut Option<SoundSnapshot>,
+) -> Result<()> {
+    let (mut main_loop, mut context) = init_pulseaudio()?;
+
+    // every event leads to a fresh snapshot, a burst of events (for example
+    // while a slider is dragged) is handled by one
+    let is_stale = Rc::new(Cell::new(true));
+    let is_stale_callback = is_stale.clone();
+    context.set_subscribe_callback(Some(Box::new(move |_facility, _operation, _index| {
+        is_stale_callback.set(true);
+    })));
+    context.subscribe(
+        InterestMaskSet::SINK
+            | InterestMaskSet::SOURCE
+            | InterestMaskSet::SERVER
+            | InterestMaskSet::CARD,
+        |_success| {},
+    );
+
+    loop {
+        if is_stale.replace(false) {
+            let snapshot = take_snapshot(&mut main_loop, &context)?;
+            // the first snapshot is the baseline
+            if let Some(previous) = previous.as_ref() {
+                for event in snapshot.changes_since(previous) {
+                    if tx.send(event).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            *previous = Some(snapshot);
+        }
+
+        match main_loop.iterate(true) {
+            IterateResult::Success(_) => (),
+            IterateResult::Quit(_) => return Ok(()),
+            IterateResult::Err(e) => return Err(anyhow!("Mainloop iteration error: {:?}", e)),
+        }
+        if context.get_state() != State::Ready {
+            return Err(anyhow!("Connection lost"));
+        }
+        if tx.is_closed() {
+            return Ok(());
+        }
+    }
+}
+
+fn take_snapshot(main_loop: &mut Mainloop, context: &Context) -> Result<SoundSnapshot> {
+    let (default_output, default_input) = run(main_loop, |output| {
+        context.introspect().get_server_info(move |info| {
+            *output.lock().unwrap() = Some((
+                info.default_sink_name.as_deref().map(str::to_string),
+                info.default_source_name.as_deref().map(str::to_string),
+            ));
+        });
+    })?;
+
+    let outputs = run(main_loop, |output| {
+        let mut devices = vec![];
+        context
+            .introspect()
+            .get_sink_info_list(move |info| match info {
+                ListResult::Item(sink) => devices.push(SoundDevice::new(
+                    sink.name.as_deref(),
+                    sink.description.as_deref(),
+                    &sink.volume,
+                    sink.mute,
+                    sink.active_port
+                        .as_ref()
+                        .and_then(|port| port.name.as_deref()),
+                    sink.ports
+                        .iter()
+                        .map(|port| {
+                            SoundPort::new(
+                                port.name.as_deref(),
+                                port.description.as_deref(),
+                                port.available,
+                            )
+                        })
+                        .collect(),
+                )),
+                ListResult::End => {
+                    *output.lock().unwrap() = Some(Ok(std::mem::take(&mut devices)));
+                }
+                ListResult::Error => *output.lock().unwrap() = Some(Err(())),
+            });
+    })?
+    .map_err(|_| anyhow!(context.errno()))?;
+
+    let inputs = run(main_loop, |output| {
+        let mut devices = vec![];
+        context
+            .introspect()
+            .get_source_info_list(move |info| match info {
+                ListResult::Item(source) if source.monitor_of_sink.is_none() => {
+                    devices.push(SoundDevice::new(
+                        source.name.as_deref(),
+                        source.description.as_deref(),
+                        &source.volume,
+                        source.mute,
+                        source
+                            .active_port
+                            .as_ref()
+                            .and_then(|port| port.name.as_deref()),
+                        source
+                            .ports
+                            .iter()
+                            .map(|port| {
+                                SoundPort::new(
+                                    port.name.as_deref(),
+                                    port.description.as_deref(),
+                                    port.available,
+                                )
+                            })
+                            .collect(),
+                    ))
+                }
+                ListResult::Item(_) => (),
+                ListResult::End => {
+                    *output.lock().unwrap() = Some(Ok(std::mem::take(&mut devices)));
+                }
+                ListResult::Error => *output.lock().unwrap() = Some(Err(())),
+            });
+    })?
+    .map_err(|_| anyhow!(context.errno()))?;
+
+    Ok(SoundSnapshot {
+        outputs,
+        inputs,
+        default_output,
+        default_input,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(name: &str, volume: f64, is_mute: bool) -> SoundDevice {
+        SoundDevice {
+            name: name.to_string(),
+            description: String::new(),
+            volume,
+            is_mute,
+            active_port: None,
+            ports: vec![],
+        }
+    }
+
+    fn port(name: &str, is_available: Option<bool>) -> SoundPort {
+        SoundPort {
+            name: name.to_string(),
+            description: String::new(),
+            is_available,
+        }
+    }
+
+    fn kinds(events: &[SoundEvent]) -> Vec<(SoundEventKind, Direction, &str, bool)> {
+        events
+            .iter()
+            .map(|e| (e.kind, e.direction, e.device.name.as_str(), e.is_default))
+            .collect()
+    }
+
+    #[test]
+    fn reports_changes_between_snapshots() {
+        let previous = SoundSnapshot {
+            outputs: vec![device("speaker", 50., false), device("hdmi", 80., false)],
+            inputs: vec![device("mic", 30., false)],
+            default_output: Some("speaker".to_string()),
+            default_input: Some("mic".to_string()),
+        };
+        assert!(previous.changes_since(&previous).is_empty());
+
+        let current = SoundSnapshot {
+            outputs: vec![device("speaker", 60., true), device("headset", 40., false)],
+            inputs: vec![device("mic", 30., true)],
+            default_output: Some("headset".to_string()),
+            default_input: Some("mic".to_string()),
+        };
+        use Direction::*;
+        use SoundEventKind::*;
+        assert_eq!(
+            kinds(&current.changes_since(&previous)),
+            vec![
+                (Removed, Output, "hdmi", false),
+                (VolumeChanged, Output, "speaker", false),
+                (MuteChanged, Output, "speaker", false),
+                (Added, Output, "headset", true),
+                (DefaultChanged, Output, "headset", true),
+                (MuteChanged, Input, "mic", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_jack_plugging_and_port_switches() {
+        let mut speaker = device("speaker", 50., false);
+        speaker.active_port = Some("analog-output-speaker".to_string());
+        speaker.ports = vec![
+            port("analog-output-speaker", None),
+            port("analog-output-headphones", Some(false)),
+        ];
+        let previous = SoundSnapshot {
+            outputs: vec![speaker.clone()],
+            default_output: Some("speaker".to_string()),
+            ..Default::default()
+        };
+
+        speaker.active_port = Some("analog-output-headphones".to_string());
+        speaker.ports[1].is_available = Some(true);
+        let current = SoundSnapshot {
+            outputs: vec![speaker],
+            default_output: Some("speaker".to_string()),
+            ..Default::default()
+        };
+        let events = current.changes_since(&previous);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, SoundEventKind::PortPlugged);
+        assert_eq!(events[0].port.as_deref(), Some("analog-output-headphones"));
+        assert_eq!(events[1].kind, SoundEventKind::ActivePortChanged);
+        assert!(events[1].is_default);
+    }
+}
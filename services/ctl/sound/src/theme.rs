@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Theme every other theme falls back to
+pub const FALLBACK_THEME: &str = "freedesktop";
+
+/// Output profile looked up in the theme directories
+const OUTPUT_PROFILE: &str = "stereo";
+
+/// In order of preference, `disabled` turns a sound off in a theme
+const EXTENSIONS: [&str; 4] = ["disabled", "oga", "ogg", "wav"];
+
+enum Lookup {
+    Found(PathBuf),
+    Disabled,
+    Missing,
+}
+
+#[derive(Debug, Default)]
+struct ThemeIndex {
+    inherits: Vec<String>,
+    /// Directories with their output profile
+    directories: Vec<(String, Option<String>)>,
+}
+
+/// # Sound Theme
+///
+/// Resolves sound names such as `message-new-instant` or `battery-low` to
+/// files, following the freedesktop sound theme spec: the theme and the
+/// themes it inherits from are searched in `$XDG_DATA_HOME/sounds` and
+/// `$XDG_DATA_DIRS/sounds`, then `freedesktop`, then the unthemed sounds.
+/// A name that is not found is retried without its last `-` component.
+/// Locales are not looked at.
+#[derive(Debug, Clone)]
+pub struct SoundTheme {
+    name: String,
+    base_dirs: Vec<PathBuf>,
+}
+
+impl SoundTheme {
+    pub fn new(name: &str) -> Self {
+        Self::with_base_dirs(name, default_base_dirs())
+    }
+
+    pub fn with_base_dirs(name: &str, base_dirs: Vec<PathBuf>) -> Self {
+        SoundTheme {
+            name: name.to_string(),
+            base_dirs,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// File of the sound, `None` when it is missing or disabled
+    pub fn lookup(&self, sound: &str) -> Option<PathBuf> {
+        let themes = self.themes();
+        for name in fallback_names(sound) {
+            for theme in themes.iter() {
+                match self.lookup_in_theme(theme, &name) {
+                    Lookup::Found(path) => return Some(path),
+                    Lookup::Disabled => return None,
+                    Lookup::Missing => (),
+                }
+            }
+        }
+
+        // unthemed sounds directly in the base directories
+        for name in fallback_names(sound) {
+            for base_dir in self.base_dirs.iter() {
+                match lookup_in_dir(base_dir, &name) {
+                    Lookup::Found(path) => return Some(path),
+                    Lookup::Disabled => return None,
+                    Lookup::Missing => (),
+                }
+            }
+        }
+        None
+    }
+
+    /// The theme and the themes it inherits from, breadth first
+    fn themes(&self) -> Vec<String> {
+        let mut themes = vec![self.name.clone()];
+        let mut next = 0;
+        while next < themes.len() {
+            for parent in self.index_of(&themes[next]).inherits {
+                if !themes.contains(&parent) {
+                    themes.push(parent);
+                }
+            }
+            next += 1;
+        }
+        if !themes.iter().any(|theme| theme == FALLBACK_THEME) {
+            themes.push(FALLBACK_THEME.to_string());
+        }
+        themes
+    }
+
+    /// First `index.theme` of the theme in the base directories
+    fn index_of(&self, theme: &str) -> ThemeIndex {
+        self.base_dirs
+            .iter()
+            .find_map(|base_dir| fs::read_to_string(base_dir.join(theme).join("index.theme")).ok())
+            .map(|index| parse_index(&index))
+            .unwrap_or_default()
+    }
+
+    fn lookup_in_theme(&self, theme: &str, name: &str) -> Lookup {
+        let index = self.index_of(theme);
+        for base_dir in self.base_dirs.iter() {
+            let theme_dir = base_dir.join(theme);
+            for (directory, profile) in index.directories.iter() {
+                if profile.as_deref().unwrap_or(OUTPUT_PROFILE) != OUTPUT_PROFILE {
+                    continue;
+                }
+                match lookup_in_dir(&theme_dir.join(directory), name) {
+                    Lookup::Missing => (),
+                    lookup => return lookup,
+                }
+            }
+        }
+        Lookup::Missing
+    }
+}
+
+fn lookup_in_dir(dir: &Path, name: &str) -> Lookup {
+    for extension in EXTENSIONS {
+        let path = dir.join(format!("{}.{}", name, extension));
+        if path.is_file() {
+            return match extension {
+                "disabled" => Lookup::Disabled,
+                _ => Lookup::Found(path),
+            };
+        }
+    }
+    Lookup::Missing
+}
+
+/// `a-b-c`, `a-b`, `a`
+fn fallback_names(sound: &str) -> Vec<String> {
+    let mut names = vec![sound.to_string()];
+    let mut name = sound;
+    while let Some((shorter, _)) = name.rsplit_once('-') {
+        names.push(shorter.to_string());
+        name = shorter;
+    }
+    names
+}
+
+fn parse_index(index: &str) -> ThemeIndex {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut section = String::new();
+    for line in index.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = name.to_string();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    let list = |key: &str| -> Vec<String> {
+        sections
+            .get("Sound Theme")
+            .and_then(|theme| theme.get(key))
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    ThemeIndex {
+        inherits: list("Inherits"),
+        directories: list("Directories")
+            .into_iter()
+            .map(|directory| {
+                let profile = sections
+                    .get(&directory)
+                    .and_then(|section| section.get("OutputProfile"))
+                    .cloned();
+                (directory, profile)
+            })
+            .collect(),
+    }
+}
+
+fn default_base_dirs() -> Vec<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or("/usr/local/share:/usr/share".to_string());
+
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(PathBuf::from))
+        .map(|dir| dir.join("sounds"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_names_through_inherited_themes() {
+        let base = env::temp_dir().join(format!("mechanix-sounds-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let files = [
+            (
+                "mechanix/index.theme",
+                "[Sound Theme]\nInherits=freedesktop\nDirectories=stereo,5.1\n\n\
+                 [stereo]\nOutputProfile=stereo\n\n[5.1]\nOutputProfile=5.1\n",
+            ),
+            ("mechanix/stereo/message.oga", ""),
+            ("mechanix/stereo/bell.disabled", ""),
+            ("mechanix/5.1/battery-low.oga", ""),
+            (
+                "freedesktop/index.theme",
+                "[Sound Theme]\nDirectories=stereo\n",
+            ),
+            ("freedesktop/stereo/battery-low.oga", ""),
+            ("freedesktop/stereo/bell.oga", ""),
+            ("unthemed.wav", ""),
+        ];
+        for (path, content) in files {
+            let path = base.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let theme = SoundTheme::with_base_dirs("mechanix", vec![base.clone()]);
+
+        assert_eq!(
+            theme.lookup("message-new-instant"),
+            Some(base.join("mechanix/stereo/message.oga"))
+        );
+        assert_eq!(
+            theme.lookup("battery-low"),
+            Some(base.join("freedesktop/stereo/battery-low.oga"))
+        );
+        assert_eq!(theme.lookup("bell"), None);
+        assert_eq!(theme.lookup("unthemed"), Some(base.join("unthemed.wav")));
+        assert_eq!(theme.lookup("missing"), None);
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -2,11 +2,23 @@ mod proxies;
 
 pub mod sound {
     use crate::proxies;
-    pub use mechanix_desktop_dbus_server::{SinkInformationResponse, SourceInformationResponse};
-    pub use proxies::sound_proxy::{NotificationStream, Sound};
+    pub use mechanix_desktop_dbus_server::{
+        CardPortResponse, CardProfileResponse, CardResponse, SinkInformationResponse,
+        SoundDeviceEvent, SourceInformationResponse, StreamInformationResponse,
+    };
+    pub use proxies::sound_proxy::{
+        ActivePortChangedStream, DefaultDeviceChangedStream, DeviceAddedStream,
+        DeviceRemovedStream, MuteChangedStream, NotificationStream, PortPluggedStream,
+        PortUnpluggedStream, Sound, VolumeChangedStream,
+    };
 }
 
 pub mod power {
     use crate::proxies;
     pub use proxies::power_proxy::Power;
 }
+
+pub mod feedback {
+    use crate::proxies;
+    pub use proxies::feedback_proxy::Feedback;
+}
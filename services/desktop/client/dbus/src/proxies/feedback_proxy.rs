@@ -0,0 +1,63 @@
+use zbus::{proxy, Connection, Result};
+
+#[proxy(
+    interface = "org.mechanix.services.Feedback",
+    default_service = "org.mechanix.services.Feedback",
+    default_path = "/org/mechanix/services/Feedback"
+)]
+trait FeedbackBusInterface {
+    async fn play_sound(&self, name: String, category: String) -> Result<()>;
+    async fn play_sound_file(&self, path: String, category: String) -> Result<()>;
+    async fn get_silent_mode(&self) -> Result<bool>;
+    async fn set_silent_mode(&self, silent: bool) -> Result<()>;
+    async fn get_category_volume(&self, category: String) -> Result<f64>;
+    async fn set_category_volume(&self, category: String, volume: f64) -> Result<()>;
+}
+
+/// Categories are `input-feedback`, `alerts`, `notifications` and `events`
+pub struct Feedback;
+
+impl Feedback {
+    /// Plays a sound of the sound theme, such as `desktop-screen-unlock`
+    pub async fn play_sound(name: String, category: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = FeedbackBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.play_sound(name, category).await?;
+        Ok(reply)
+    }
+
+    pub async fn play_sound_file(path: String, category: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = FeedbackBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.play_sound_file(path, category).await?;
+        Ok(reply)
+    }
+
+    pub async fn get_silent_mode() -> Result<bool> {
+        let connection = Connection::session().await?;
+        let proxy = FeedbackBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_silent_mode().await?;
+        Ok(reply)
+    }
+
+    pub async fn set_silent_mode(silent: bool) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = FeedbackBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_silent_mode(silent).await?;
+        Ok(reply)
+    }
+
+    pub async fn get_category_volume(category: String) -> Result<f64> {
+        let connection = Connection::session().await?;
+        let proxy = FeedbackBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_category_volume(category).await?;
+        Ok(reply)
+    }
+
+    pub async fn set_category_volume(category: String, volume: f64) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = FeedbackBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_category_volume(category, volume).await?;
+        Ok(reply)
+    }
+}
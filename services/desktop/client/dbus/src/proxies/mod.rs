@@ -1,2 +1,3 @@
+pub mod feedback_proxy;
 pub mod power_proxy;
 pub mod sound_proxy;
@@ -1,5 +1,6 @@
 use mechanix_desktop_dbus_server::{
-    SinkInformationResponse, SoundNotificationEvent, SourceInformationResponse,
+    CardResponse, SinkInformationResponse, SoundDeviceEvent, SoundNotificationEvent,
+    SourceInformationResponse, StreamInformationResponse,
 };
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -15,8 +16,52 @@ trait SoundBusInterface {
     async fn get_output_device_volume(&self, device: String) -> Result<f64>;
     async fn get_connected_input_devices(&self) -> Result<Vec<SinkInformationResponse>>;
     async fn get_connected_output_devices(&self) -> Result<Vec<SourceInformationResponse>>;
+    async fn get_default_output(&self) -> Result<String>;
+    async fn set_default_output(&self, device: String) -> Result<()>;
+    async fn get_default_input(&self) -> Result<String>;
+    async fn set_default_input(&self, device: String) -> Result<()>;
+    async fn list_streams(&self) -> Result<Vec<StreamInformationResponse>>;
+    async fn set_stream_volume(
+        &self,
+        application_name: String,
+        process_id: u32,
+        volume: f64,
+    ) -> Result<()>;
+    async fn mute_stream(
+        &self,
+        application_name: String,
+        process_id: u32,
+        mute: bool,
+    ) -> Result<()>;
+    async fn move_stream(
+        &self,
+        application_name: String,
+        process_id: u32,
+        device: String,
+    ) -> Result<()>;
+    async fn list_cards(&self) -> Result<Vec<CardResponse>>;
+    async fn set_card_profile(&self, card: String, profile: String) -> Result<()>;
+    async fn set_output_port(&self, device: String, port: String) -> Result<()>;
+    async fn set_input_port(&self, device: String, port: String) -> Result<()>;
+
     #[zbus(signal)]
     async fn notification(&self, event: SoundNotificationEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn volume_changed(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn mute_changed(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn default_device_changed(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn device_added(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn device_removed(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn port_plugged(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn port_unplugged(&self, event: SoundDeviceEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn active_port_changed(&self, event: SoundDeviceEvent) -> Result<()>;
 }
 
 pub struct Sound;
@@ -46,6 +91,63 @@ impl Sound {
         Ok(stream)
     }
 
+    pub async fn get_volume_changed_stream() -> Result<VolumeChangedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_volume_changed().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_mute_changed_stream() -> Result<MuteChangedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_mute_changed().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_default_device_changed_stream() -> Result<DefaultDeviceChangedStream<'static>>
+    {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_default_device_changed().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_device_added_stream() -> Result<DeviceAddedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_device_added().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_device_removed_stream() -> Result<DeviceRemovedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_device_removed().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_port_plugged_stream() -> Result<PortPluggedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_port_plugged().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_port_unplugged_stream() -> Result<PortUnpluggedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_port_unplugged().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_active_port_changed_stream() -> Result<ActivePortChangedStream<'static>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_active_port_changed().await?;
+        Ok(stream)
+    }
+
     pub async fn get_input_devices() -> Result<Vec<SinkInformationResponse>> {
         let connection = Connection::session().await?;
         let proxy = SoundBusInterfaceProxy::new(&connection).await?;
@@ -59,4 +161,103 @@ impl Sound {
         let reply = proxy.get_connected_output_devices().await?;
         Ok(reply)
     }
+
+    pub async fn get_default_output() -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_default_output().await?;
+        Ok(reply)
+    }
+
+    pub async fn set_default_output(device: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_default_output(device).await?;
+        Ok(reply)
+    }
+
+    pub async fn get_default_input() -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_default_input().await?;
+        Ok(reply)
+    }
+
+    pub async fn set_default_input(device: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_default_input(device).await?;
+        Ok(reply)
+    }
+
+    pub async fn list_streams() -> Result<Vec<StreamInformationResponse>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.list_streams().await?;
+        Ok(reply)
+    }
+
+    /// `process_id` 0 picks the streams of any process of the application
+    pub async fn set_stream_volume(
+        application_name: String,
+        process_id: u32,
+        volume: f64,
+    ) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy
+            .set_stream_volume(application_name, process_id, volume)
+            .await?;
+        Ok(reply)
+    }
+
+    pub async fn mute_stream(application_name: String, process_id: u32, mute: bool) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy
+            .mute_stream(application_name, process_id, mute)
+            .await?;
+        Ok(reply)
+    }
+
+    pub async fn move_stream(
+        application_name: String,
+        process_id: u32,
+        device: String,
+    ) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy
+            .move_stream(application_name, process_id, device)
+            .await?;
+        Ok(reply)
+    }
+
+    pub async fn list_cards() -> Result<Vec<CardResponse>> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.list_cards().await?;
+        Ok(reply)
+    }
+
+    pub async fn set_card_profile(card: String, profile: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_card_profile(card, profile).await?;
+        Ok(reply)
+    }
+
+    pub async fn set_output_port(device: String, port: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_output_port(device, port).await?;
+        Ok(reply)
+    }
+
+    pub async fn set_input_port(device: String, port: String) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = SoundBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.set_input_port(device, port).await?;
+        Ok(reply)
+    }
 }
@@ -0,0 +1,177 @@
+use anyhow::{anyhow, bail, Result};
+use command::spawn_command;
+use mechanix_sound_ctl::SoundTheme;
+use std::{path::Path, sync::Arc};
+use tokio::sync::Mutex;
+use zbus::{fdo::Error as ZbusError, interface};
+
+use crate::settings::sound_theme::SoundThemeSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCategory {
+    InputFeedback,
+    Alerts,
+    Notifications,
+    Events,
+}
+
+impl TryFrom<&str> for SoundCategory {
+    type Error = anyhow::Error;
+
+    fn try_from(category: &str) -> Result<Self> {
+        match category {
+            "input-feedback" => Ok(SoundCategory::InputFeedback),
+            "alerts" => Ok(SoundCategory::Alerts),
+            "notifications" => Ok(SoundCategory::Notifications),
+            "events" => Ok(SoundCategory::Events),
+            category => Err(anyhow!("Unknown sound category {}", category)),
+        }
+    }
+}
+
+/// # Sound Player
+///
+/// Plays short samples of the sound theme through PulseAudio with `paplay`,
+/// at the volume of their category and not at all in silent mode. Changes of
+/// the silent mode and the volumes last until the server restarts.
+#[derive(Debug, Clone)]
+pub struct SoundPlayer {
+    theme: SoundTheme,
+    settings: Arc<Mutex<SoundThemeSettings>>,
+}
+
+impl SoundPlayer {
+    pub fn new(settings: SoundThemeSettings) -> Self {
+        Self {
+            theme: SoundTheme::new(&settings.theme),
+            settings: Arc::new(Mutex::new(settings)),
+        }
+    }
+
+    /// Plays a sound of the theme, such as `message-new-instant`
+    pub async fn play(&self, name: &str, category: SoundCategory) -> Result<()> {
+        if self.is_silent().await {
+            return Ok(());
+        }
+        match self.theme.lookup(name) {
+            Some(path) => self.play_file(&path, category).await,
+            None => bail!("No sound {} in theme {}", name, self.theme.name()),
+        }
+    }
+
+    /// Plays a sound file, `path` must be absolute
+    pub async fn play_file(&self, path: &Path, category: SoundCategory) -> Result<()> {
+        if !path.is_absolute() {
+            bail!("Sound file path {:?} is not absolute", path);
+        }
+        if self.is_silent().await {
+            return Ok(());
+        }
+        let volume = self.get_volume(category).await;
+        if volume <= 0. {
+            return Ok(());
+        }
+
+        // `paplay` takes the volume from 0 to 65536 (100%)
+        let args = vec![
+            format!("--volume={}", (volume / 100. * 65536.) as u32),
+            "--property=media.role=event".to_string(),
+            // the path is not an option even when it looks like one
+            "--".to_string(),
+            path.to_string_lossy().to_string(),
+        ];
+        let mut child = spawn_command("paplay".to_string(), args)?;
+        // reap the player once the sample ended
+        tokio::task::spawn_blocking(move || child.wait());
+        Ok(())
+    }
+
+    pub async fn is_silent(&self) -> bool {
+        self.settings.lock().await.silent_mode
+    }
+
+    pub async fn set_silent(&self, silent: bool) {
+        self.settings.lock().await.silent_mode = silent;
+    }
+
+    pub async fn get_volume(&self, category: SoundCategory) -> f64 {
+        let settings = self.settings.lock().await;
+        let volumes = &settings.volumes;
+        match category {
+            SoundCategory::InputFeedback => volumes.input_feedback,
+            SoundCategory::Alerts => volumes.alerts,
+            SoundCategory::Notifications => volumes.notifications,
+            SoundCategory::Events => volumes.events,
+        }
+    }
+
+    pub async fn set_volume(&self, category: SoundCategory, volume: f64) -> Result<()> {
+        if !(0. ..=100.).contains(&volume) {
+            bail!("Volume {} is not between 0 and 100", volume);
+        }
+        let mut settings = self.settings.lock().await;
+        let volumes = &mut settings.volumes;
+        match category {
+            SoundCategory::InputFeedback => volumes.input_feedback = volume,
+            SoundCategory::Alerts => volumes.alerts = volume,
+            SoundCategory::Notifications => volumes.notifications = volume,
+            SoundCategory::Events => volumes.events = volume,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedbackBusInterface {
+    pub player: SoundPlayer,
+}
+
+// `category` is one of `input-feedback`, `alerts`, `notifications` or `events`
+#[interface(name = "org.mechanix.services.Feedback")]
+impl FeedbackBusInterface {
+    // name of the freedesktop sound naming spec, for example `device-added`
+    pub async fn play_sound(&self, name: String, category: String) -> Result<(), ZbusError> {
+        let category = parse_category(&category)?;
+        match self.player.play(&name, category).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!("Failed to play sound: {}", e))),
+        }
+    }
+
+    pub async fn play_sound_file(&self, path: String, category: String) -> Result<(), ZbusError> {
+        let category = parse_category(&category)?;
+        match self.player.play_file(Path::new(&path), category).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!("Failed to play sound: {}", e))),
+        }
+    }
+
+    pub async fn get_silent_mode(&self) -> bool {
+        self.player.is_silent().await
+    }
+
+    pub async fn set_silent_mode(&self, silent: bool) {
+        self.player.set_silent(silent).await
+    }
+
+    pub async fn get_category_volume(&self, category: String) -> Result<f64, ZbusError> {
+        let category = parse_category(&category)?;
+        Ok(self.player.get_volume(category).await)
+    }
+
+    pub async fn set_category_volume(
+        &self,
+        category: String,
+        volume: f64,
+    ) -> Result<(), ZbusError> {
+        let category = parse_category(&category)?;
+        match self.player.set_volume(category, volume).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::InvalidArgs(e.to_string())),
+        }
+    }
+}
+
+fn parse_category(category: &str) -> Result<SoundCategory, ZbusError> {
+    SoundCategory::try_from(category).map_err(|e| ZbusError::InvalidArgs(e.to_string()))
+}
@@ -2,12 +2,16 @@ mod sound_interface;
 pub use sound_interface::SoundBusInterface;
 
 pub use sound_interface::{
-    sound_event_notification_stream, SinkInformationResponse, SoundNotificationEvent,
-    SourceInformationResponse,
+    sound_event_notification_stream, CardPortResponse, CardProfileResponse, CardResponse,
+    SinkInformationResponse, SoundDeviceEvent, SoundNotificationEvent, SourceInformationResponse,
+    StreamInformationResponse,
 };
 
 mod power_interface;
 pub use power_interface::PowerBusInterface;
 
+mod feedback_interface;
+pub use feedback_interface::{FeedbackBusInterface, SoundCategory, SoundPlayer};
+
 mod notification_interface;
 pub use notification_interface::{NotificationBusInterface, Notifier};
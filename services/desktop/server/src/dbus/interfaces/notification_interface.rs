@@ -11,8 +11,12 @@ use zbus::{
     Connection,
 };
 
+use super::feedback_interface::{SoundCategory, SoundPlayer};
 use crate::settings::notifier::NotifierSettings;
 
+/// Played for notifications without `sound-file` or `sound-name`
+const DEFAULT_NOTIFICATION_SOUND: &str = "message-new-instant";
+
 #[derive(Debug, Clone)]
 pub enum Event {
     New(Notification),
@@ -191,12 +195,12 @@ impl NotificationBusInterface {
             "body",
             "icon-static",
             "persistence",
+            "sound",
             // TODO support these
             "actions",
             "action-icons",
             "body-markup",
             "body-hyperlinks",
-            "sound",
         ]
     }
     #[zbus(signal)]
@@ -218,14 +222,16 @@ pub struct Notifier {
     pub stack: Vec<Notification>,
     pub is_child_running: bool,
     pub configs: NotifierSettings,
+    pub player: SoundPlayer,
 }
 
 impl Notifier {
-    pub fn new(configs: NotifierSettings) -> Self {
+    pub fn new(configs: NotifierSettings, player: SoundPlayer) -> Self {
         Self {
             stack: vec![],
             is_child_running: false,
             configs,
+            player,
         }
     }
 
@@ -246,6 +252,10 @@ impl Notifier {
                                 n,
                                 self.configs.run_commands.notification.clone()
                             );
+                            if let Err(e) = play_notification_sound(&self.player, &n.hints).await {
+                                println!("Error while playing notification sound {:?}", e);
+                            }
+
                             let mut args: Vec<String> = vec![];
                             args.push(self.configs.run_commands.notification.clone());
                             args.push(format!("--app-name={:?}", n.app_name.clone()));
@@ -255,9 +265,9 @@ impl Notifier {
                             //Spawn notification shell component
                             let _ = spawn_notification(args.join(" "), event_tx.clone()).await;
                         }
-                        Event::Closed => {
-                            break;
-                        }
+                        // the notification component exited, keep serving the
+                        // next notifications
+                        Event::Closed => (),
                     }
                 };
             }
@@ -266,6 +276,32 @@ impl Notifier {
     }
 }
 
+/// Plays the `sound-file` or `sound-name` hint, nothing with `suppress-sound`
+async fn play_notification_sound(player: &SoundPlayer, hints: &[Hint]) -> Result<()> {
+    let mut sound_file = None;
+    let mut sound_name = None;
+    for hint in hints {
+        match hint {
+            Hint::SuppressSound(true) => return Ok(()),
+            Hint::SoundFile(path) => sound_file = Some(path),
+            Hint::SoundName(name) => sound_name = Some(name.as_str()),
+            _ => (),
+        }
+    }
+
+    match sound_file {
+        Some(path) => player.play_file(path, SoundCategory::Notifications).await,
+        None => {
+            player
+                .play(
+                    sound_name.unwrap_or(DEFAULT_NOTIFICATION_SOUND),
+                    SoundCategory::Notifications,
+                )
+                .await
+        }
+    }
+}
+
 async fn spawn_notification(run_command: String, event_tx: mpsc::Sender<Event>) -> Result<bool> {
     println!("spawn_notification run_command {:?}", run_command);
     let _ = tokio::spawn(async move {
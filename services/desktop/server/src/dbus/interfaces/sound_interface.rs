@@ -1,10 +1,7 @@
-use std::{collections::HashMap, sync::Arc, thread, time::Duration};
+use std::{collections::HashMap, sync::Arc, thread};
 
-use mechanix_sound_ctl::Sound;
-use tokio::{
-    sync::{mpsc, Mutex},
-    time,
-};
+use mechanix_sound_ctl::{CardInformation, Direction, Sound, SoundEvent, SoundEventKind};
+use tokio::sync::{mpsc, Mutex};
 use zbus::{
     fdo::Error as ZbusError,
     interface,
@@ -41,6 +38,121 @@ pub struct SoundNotificationEvent {
     pub volume_level: f64,
 }
 
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `StreamInformationResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct StreamInformationResponse {
+    pub application_name: String,
+    /// 0 when the stream does not report it
+    pub process_id: u32,
+    pub output_device: String,
+    pub volume_level: f64,
+    pub is_mute: bool,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `CardProfileResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct CardProfileResponse {
+    pub name: String,
+    pub description: String,
+    pub is_available: bool,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `CardPortResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct CardPortResponse {
+    pub name: String,
+    pub description: String,
+    /// `output` or `input`
+    pub direction: String,
+    /// `yes`, `no` or `unknown` without jack detection
+    pub availability: String,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `CardResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct CardResponse {
+    pub name: String,
+    pub description: String,
+    /// Empty when the card is off
+    pub active_profile: String,
+    pub profiles: Vec<CardProfileResponse>,
+    pub ports: Vec<CardPortResponse>,
+}
+
+impl From<CardInformation> for CardResponse {
+    fn from(card: CardInformation) -> Self {
+        CardResponse {
+            name: card.name,
+            description: card.description,
+            active_profile: card.active_profile.unwrap_or_default(),
+            profiles: card
+                .profiles
+                .into_iter()
+                .map(|profile| CardProfileResponse {
+                    name: profile.name,
+                    description: profile.description,
+                    is_available: profile.is_available,
+                })
+                .collect(),
+            ports: card
+                .ports
+                .into_iter()
+                .map(|port| CardPortResponse {
+                    name: port.name,
+                    description: port.description,
+                    direction: direction_name(port.direction).to_string(),
+                    availability: match port.is_available {
+                        Some(true) => "yes",
+                        Some(false) => "no",
+                        None => "unknown",
+                    }
+                    .to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Output => "output",
+        Direction::Input => "input",
+    }
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `SoundDeviceEvent` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct SoundDeviceEvent {
+    /// `output` or `input`
+    pub direction: String,
+    pub device: String,
+    pub description: String,
+    pub volume_level: f64,
+    pub is_mute: bool,
+    pub is_default: bool,
+    /// Port that was plugged, unplugged or switched to, empty otherwise
+    pub port: String,
+}
+
+impl From<&SoundEvent> for SoundDeviceEvent {
+    fn from(event: &SoundEvent) -> Self {
+        SoundDeviceEvent {
+            direction: direction_name(event.direction).to_string(),
+            device: event.device.name.clone(),
+            description: event.device.description.clone(),
+            volume_level: event.device.volume,
+            is_mute: event.device.is_mute,
+            is_default: event.is_default,
+            port: event.port.clone().unwrap_or_default(),
+        }
+    }
+}
+
 #[interface(name = "org.mechanix.services.Sound")]
 impl SoundBusInterface {
     pub async fn get_output_device_volume(
@@ -71,6 +183,64 @@ impl SoundBusInterface {
         event: SoundNotificationEvent,
     ) -> Result<(), zbus::Error>;
 
+    #[zbus(signal)]
+    async fn volume_changed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    #[zbus(signal)]
+    async fn mute_changed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    #[zbus(signal)]
+    async fn default_device_changed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    // hot-plug of outputs and inputs
+    #[zbus(signal)]
+    async fn device_added(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    #[zbus(signal)]
+    async fn device_removed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    // jack detection, for example headphones plugged in
+    #[zbus(signal)]
+    async fn port_plugged(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    #[zbus(signal)]
+    async fn port_unplugged(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
+    #[zbus(signal)]
+    async fn active_port_changed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: SoundDeviceEvent,
+    ) -> Result<(), zbus::Error>;
+
     pub async fn set_output_device_volume(
         &self,
         volume: f64,
@@ -252,6 +422,152 @@ impl SoundBusInterface {
             }
         }
     }
+
+    pub async fn get_default_output(&self) -> Result<String, ZbusError> {
+        let sound = Sound::new();
+        match sound.get_default_output().await {
+            Ok(device) => Ok(device),
+            Err(_) => Err(ZbusError::Failed(
+                "Failed to get default output".to_string(),
+            )),
+        }
+    }
+
+    pub async fn set_default_output(&self, device: String) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound.set_default_output(device).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ZbusError::Failed(
+                "Failed to set default output".to_string(),
+            )),
+        }
+    }
+
+    pub async fn get_default_input(&self) -> Result<String, ZbusError> {
+        let sound = Sound::new();
+        match sound.get_default_input().await {
+            Ok(device) => Ok(device),
+            Err(_) => Err(ZbusError::Failed("Failed to get default input".to_string())),
+        }
+    }
+
+    pub async fn set_default_input(&self, device: String) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound.set_default_input(device).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ZbusError::Failed("Failed to set default input".to_string())),
+        }
+    }
+
+    // playback streams of the applications
+    pub async fn list_streams(&self) -> Result<Vec<StreamInformationResponse>, ZbusError> {
+        let sound = Sound::new();
+        let streams = match sound.list_streams().await {
+            Ok(streams) => streams,
+            Err(_) => return Err(ZbusError::Failed("Failed to list streams".to_string())),
+        };
+        Ok(streams
+            .into_iter()
+            .map(|stream| StreamInformationResponse {
+                application_name: stream.application_name,
+                process_id: stream.process_id.unwrap_or_default(),
+                output_device: stream.output_device,
+                volume_level: stream.volume,
+                is_mute: stream.is_mute,
+            })
+            .collect())
+    }
+
+    // streams are picked by application name, and by process id unless it is 0
+    pub async fn set_stream_volume(
+        &self,
+        application_name: String,
+        process_id: u32,
+        volume: f64,
+    ) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound
+            .set_stream_volume(application_name, process_id, volume)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!(
+                "Failed to set stream volume: {}",
+                e
+            ))),
+        }
+    }
+
+    pub async fn mute_stream(
+        &self,
+        application_name: String,
+        process_id: u32,
+        mute: bool,
+    ) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound.mute_stream(application_name, process_id, mute).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!("Failed to mute stream: {}", e))),
+        }
+    }
+
+    pub async fn move_stream(
+        &self,
+        application_name: String,
+        process_id: u32,
+        device: String,
+    ) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound
+            .move_stream(application_name, process_id, device)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!("Failed to move stream: {}", e))),
+        }
+    }
+
+    pub async fn list_cards(&self) -> Result<Vec<CardResponse>, ZbusError> {
+        let sound = Sound::new();
+        match sound.list_cards().await {
+            Ok(cards) => Ok(cards.into_iter().map(CardResponse::from).collect()),
+            Err(_) => Err(ZbusError::Failed("Failed to list cards".to_string())),
+        }
+    }
+
+    // for example `a2dp-sink` or `headset-head-unit` of a Bluetooth headset
+    pub async fn set_card_profile(&self, card: String, profile: String) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound.set_card_profile(card, profile).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!(
+                "Failed to set card profile: {}",
+                e
+            ))),
+        }
+    }
+
+    pub async fn set_output_port(&self, device: String, port: String) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound.set_output_port(device, port).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!(
+                "Failed to set output port: {}",
+                e
+            ))),
+        }
+    }
+
+    pub async fn set_input_port(&self, device: String, port: String) -> Result<(), ZbusError> {
+        let sound = Sound::new();
+        match sound.set_input_port(device, port).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::Failed(format!(
+                "Failed to set input port: {}",
+                e
+            ))),
+        }
+    }
 }
 
 // events for all signals to be emitted notification
@@ -259,37 +575,51 @@ pub async fn sound_event_notification_stream(
     sound_bus: &SoundBusInterface,
     conn: &zbus::Connection,
 ) -> Result<(), ZbusError> {
-    let mut interval = time::interval(Duration::from_secs(1));
-    let mut previous_volume: Option<f64> = None;
-    let mut previous_mute_status: Option<bool> = None;
-
-    loop {
-        interval.tick().await;
-        let sound_manager = Sound::new();
-        let (current_volume, current_mute_status) =
-            sound_manager.get_output_device_volume(None).await.unwrap();
-        drop(sound_manager); // Release the lock
-
-        let ctxt = SignalContext::new(conn, "/org/mechanix/services/Sound")?;
-
-        // Check if the current volume or mute status has changed from the previous values
-        if previous_volume != Some(current_volume)
-            || previous_mute_status != Some(current_mute_status)
-        {
-            // If there's a change, emit the notification signal
+    let ctxt = SignalContext::new(conn, "/org/mechanix/services/Sound")?;
+    let mut events = mechanix_sound_ctl::subscribe();
+
+    while let Some(event) = events.recv().await {
+        let device_event = SoundDeviceEvent::from(&event);
+        match event.kind {
+            SoundEventKind::VolumeChanged => sound_bus.volume_changed(&ctxt, device_event).await?,
+            SoundEventKind::MuteChanged => sound_bus.mute_changed(&ctxt, device_event).await?,
+            SoundEventKind::DefaultChanged => {
+                sound_bus
+                    .default_device_changed(&ctxt, device_event)
+                    .await?
+            }
+            SoundEventKind::Added => sound_bus.device_added(&ctxt, device_event).await?,
+            SoundEventKind::Removed => sound_bus.device_removed(&ctxt, device_event).await?,
+            SoundEventKind::PortPlugged => sound_bus.port_plugged(&ctxt, device_event).await?,
+            SoundEventKind::PortUnplugged => sound_bus.port_unplugged(&ctxt, device_event).await?,
+            SoundEventKind::ActivePortChanged => {
+                sound_bus.active_port_changed(&ctxt, device_event).await?
+            }
+        };
+
+        // `notification` keeps following the volume of the default output
+        let is_default_output = event.is_default && event.direction == Direction::Output;
+        let is_volume_event = matches!(
+            event.kind,
+            SoundEventKind::VolumeChanged
+                | SoundEventKind::MuteChanged
+                | SoundEventKind::DefaultChanged
+                | SoundEventKind::Removed
+        );
+        if is_default_output && is_volume_event {
             sound_bus
                 .notification(
                     &ctxt,
                     SoundNotificationEvent {
-                        is_mute: current_mute_status,
-                        volume_level: current_volume,
+                        is_mute: event.device.is_mute,
+                        volume_level: event.device.volume,
                     },
                 )
                 .await?;
-
-            // Update the previous values to the current ones
-            previous_volume = Some(current_volume);
-            previous_mute_status = Some(current_mute_status);
         }
     }
+
+    Err(ZbusError::Failed(
+        "Sound event subscription ended".to_string(),
+    ))
 }
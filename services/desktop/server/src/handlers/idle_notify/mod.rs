@@ -12,6 +12,7 @@ use wayland_protocols_async::ext_idle_notify_v1::handler::{IdleNotifyEvent, Idle
 use crate::settings::idle_notify::IdleNotifySettings;
 #[derive(Debug, Clone, Copy)]
 pub enum NotifyEvents {
+    Dim,
     Display,
     Lock,
 }
@@ -19,6 +20,7 @@ pub enum NotifyEvents {
 impl ToString for NotifyEvents {
     fn to_string(&self) -> String {
         match self {
+            NotifyEvents::Dim => "Dim".to_string(),
             NotifyEvents::Display => "Display".to_string(),
             NotifyEvents::Lock => "Lock".to_string(),
         }
@@ -30,6 +32,7 @@ impl FromStr for NotifyEvents {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "Dim" => Ok(NotifyEvents::Dim),
             "Display" => Ok(NotifyEvents::Display),
             "Lock" => Ok(NotifyEvents::Lock),
             _ => Err(io::Error::from(io::ErrorKind::InvalidData)),
@@ -52,9 +55,17 @@ impl IdleNotifyHandlerClient {
         let (idle_notify_event_tx, mut idle_notify_event_rx) = mpsc::channel(128);
 
         let periods = self.configs.periods.clone();
+        let dim = self.configs.dim.clone();
 
         let mut subscribers = HashMap::new();
 
+        if let Some(dim_period) = periods.dim {
+            subscribers.insert(
+                NotifyEvents::Dim.to_string(),
+                Duration::from_secs(dim_period as u64),
+            );
+        }
+
         subscribers.insert(
             NotifyEvents::Display.to_string(),
             Duration::from_secs(periods.display_off as u64),
@@ -86,6 +97,10 @@ impl IdleNotifyHandlerClient {
                         IdleNotifyEvent::Idled { key } => {
                             if let Ok(notify_event) = NotifyEvents::from_str(&key) {
                                 match notify_event {
+                                    NotifyEvents::Dim => {
+                                        println!("Dimming display");
+                                        let _ = Display::dim(dim.brightness, dim.duration_ms).await;
+                                    }
                                     NotifyEvents::Display => {
                                         //Turn backlight off
                                         println!("Turning off display");
@@ -101,9 +116,9 @@ impl IdleNotifyHandlerClient {
                         IdleNotifyEvent::Resumed { key } => {
                             if let Ok(notify_event) = NotifyEvents::from_str(&key) {
                                 match notify_event {
-                                    NotifyEvents::Display => {
+                                    NotifyEvents::Dim | NotifyEvents::Display => {
                                         println!("Turning on display");
-                                        //Turn backlight on
+                                        //Restore the brightness set before dimming or turning off
                                         let _ = Display::set_backlight_on().await;
                                     }
                                     NotifyEvents::Lock => {
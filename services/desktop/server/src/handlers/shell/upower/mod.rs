@@ -3,18 +3,22 @@ use futures::StreamExt;
 use upower::{device::DeviceProxy, upower::UPowerProxy, DeviceType, WarningLevel};
 use zbus::{Connection, Proxy};
 
-pub struct UpowerHandler {}
+use crate::dbus::interfaces::{SoundCategory, SoundPlayer};
+
+pub struct UpowerHandler {
+    player: SoundPlayer,
+}
 impl UpowerHandler {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(player: SoundPlayer) -> Self {
+        Self { player }
     }
 
     pub async fn run(mut self) {
-        let _ = handle_upower_prop_update().await;
+        let _ = handle_upower_prop_update(&self.player).await;
     }
 }
 
-async fn handle_upower_prop_update() -> Result<bool> {
+async fn handle_upower_prop_update(player: &SoundPlayer) -> Result<bool> {
     let connection = Connection::system().await?;
     let upower_p = UPowerProxy::builder(&connection).build().await?;
     let devices = upower_p.enumerate_devices().await?;
@@ -39,6 +43,15 @@ async fn handle_upower_prop_update() -> Result<bool> {
         let warning_level = msg.get().await.unwrap();
         let warning = WarningLevel::from(warning_level);
         println!("warning {:?}", warning);
+
+        let sound = match warning {
+            WarningLevel::Low => "battery-low",
+            WarningLevel::Critical => "battery-caution",
+            _ => continue,
+        };
+        if let Err(e) = player.play(sound, SoundCategory::Alerts).await {
+            println!("Error while playing {} {:?}", sound, e);
+        }
     }
 
     Ok(true)
@@ -1,6 +1,7 @@
 mod dbus;
 pub use dbus::interfaces::{
-    SinkInformationResponse, SoundNotificationEvent, SourceInformationResponse,
+    CardPortResponse, CardProfileResponse, CardResponse, SinkInformationResponse, SoundDeviceEvent,
+    SoundNotificationEvent, SourceInformationResponse, StreamInformationResponse,
 };
 
 mod settings;
@@ -7,8 +7,8 @@ mod settings;
 
 use anyhow::Result;
 use dbus::interfaces::{
-    sound_event_notification_stream, NotificationBusInterface, Notifier, PowerBusInterface,
-    SoundBusInterface,
+    sound_event_notification_stream, FeedbackBusInterface, NotificationBusInterface, Notifier,
+    PowerBusInterface, SoundBusInterface, SoundPlayer,
 };
 use handlers::{
     session::SessionHandler,
@@ -57,19 +57,29 @@ async fn main() -> Result<()> {
 
     handles.push(sound_handle);
 
-    // let (event_tx, event_rx) = mpsc::channel(128);
-    // let notification_bus = NotificationBusInterface {
-    //     event_tx: event_tx.clone(),
-    // };
-    // let _notification_bus_connection = connection::Builder::session()?
-    //     .name("org.freedesktop.Notifications")?
-    //     .serve_at("/org/freedesktop/Notifications", notification_bus.clone())?
-    //     .build()
-    //     .await?;
+    let sound_player = SoundPlayer::new(settings.sound_theme.clone());
+    let feedback_bus = FeedbackBusInterface {
+        player: sound_player.clone(),
+    };
+    let _feedback_bus_connection = connection::Builder::session()?
+        .name("org.mechanix.services.Feedback")?
+        .serve_at("/org/mechanix/services/Feedback", feedback_bus)?
+        .build()
+        .await?;
+
+    let (event_tx, event_rx) = mpsc::channel(128);
+    let notification_bus = NotificationBusInterface {
+        event_tx: event_tx.clone(),
+    };
+    let _notification_bus_connection = connection::Builder::session()?
+        .name("org.freedesktop.Notifications")?
+        .serve_at("/org/freedesktop/Notifications", notification_bus.clone())?
+        .build()
+        .await?;
 
-    // let notifier = Notifier::new(settings.notifier.clone());
-    // let notifier_handle = tokio::spawn(async move { notifier.run(event_tx, event_rx).await });
-    // handles.push(notifier_handle);
+    let notifier = Notifier::new(settings.notifier.clone(), sound_player.clone());
+    let notifier_handle = tokio::spawn(async move { notifier.run(event_tx, event_rx).await });
+    handles.push(notifier_handle);
 
     let session_handler = SessionHandler::new(settings.clone());
     let session_handle = tokio::spawn(async move {
@@ -89,7 +99,7 @@ async fn main() -> Result<()> {
     // });
     // handles.push(security_handle);
 
-    let upower_handler = UpowerHandler::new();
+    let upower_handler = UpowerHandler::new(sound_player.clone());
     let upower_handle = tokio::spawn(async move {
         upower_handler.run().await;
     });
@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct Periods {
+    /// Dims the display before turning it off, not dimmed when not set
+    #[serde(default)]
+    pub dim: Option<f32>,
     pub display_off: f32,
     pub screen_lock: f32,
 }
@@ -9,13 +12,32 @@ pub struct Periods {
 impl Default for Periods {
     fn default() -> Self {
         Self {
+            dim: None,
             display_off: 10.,
             screen_lock: 30.,
         }
     }
 }
 
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct Dim {
+    /// Brightness in percent to dim to
+    pub brightness: u8,
+    pub duration_ms: u32,
+}
+
+impl Default for Dim {
+    fn default() -> Self {
+        Self {
+            brightness: 20,
+            duration_ms: 500,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, Serialize, Default)]
 pub struct IdleNotifySettings {
     pub periods: Periods,
+    #[serde(default)]
+    pub dim: Dim,
 }
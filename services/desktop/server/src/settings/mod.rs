@@ -7,11 +7,13 @@ pub mod idle_notify;
 pub mod lock_button;
 pub mod notifier;
 pub mod session;
+pub mod sound_theme;
 use anyhow::bail;
 use anyhow::Result;
 use notifier::NotifierSettings;
 use serde::{Deserialize, Serialize};
 use session::SessionSettings;
+use sound_theme::SoundThemeSettings;
 use std::{env, fs::File, path::PathBuf};
 use tracing::{debug, info};
 
@@ -22,6 +24,8 @@ pub struct DesktopServerSettings {
     pub lock_button: LockButtonSettings,
     pub home_button: HomeButtonSettings,
     pub notifier: NotifierSettings,
+    #[serde(default)]
+    pub sound_theme: SoundThemeSettings,
 }
 /// # Reads Settings path from arg
 ///
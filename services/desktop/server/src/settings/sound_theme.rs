@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Volume in percent of each category of sounds
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct CategoryVolumes {
+    /// Key clicks and touch feedback
+    pub input_feedback: f64,
+    /// Low battery and other warnings
+    pub alerts: f64,
+    pub notifications: f64,
+    /// Lock, unlock, plugging in the charger
+    pub events: f64,
+}
+
+impl Default for CategoryVolumes {
+    fn default() -> Self {
+        Self {
+            input_feedback: 40.,
+            alerts: 100.,
+            notifications: 80.,
+            events: 60.,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct SoundThemeSettings {
+    /// Name of the freedesktop sound theme
+    pub theme: String,
+    /// Plays no sounds at all
+    pub silent_mode: bool,
+    pub volumes: CategoryVolumes,
+}
+
+impl Default for SoundThemeSettings {
+    fn default() -> Self {
+        Self {
+            theme: "freedesktop".to_string(),
+            silent_mode: false,
+            volumes: CategoryVolumes::default(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use mechanix_store_client::store_proxy::StoreClient;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "mechanix-store",
+    about = "Export, import and restore the Mechanix store"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Writes every object of the store to a file, or stdout when omitted
+    Export {
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Imports an archive, a snapshot is taken before the import
+    Import {
+        file: PathBuf,
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        /// Deletes keys that are not in the archive
+        #[arg(long)]
+        replace: bool,
+    },
+    /// Takes a snapshot of the store
+    Snapshot,
+    /// Lists the ids of the snapshots
+    Snapshots,
+    /// Restores a snapshot
+    Restore {
+        id: String,
+        /// Deletes keys that are not in the snapshot
+        #[arg(long)]
+        replace: bool,
+    },
+}
+
+fn import_mode(replace: bool) -> &'static str {
+    match replace {
+        true => "replace",
+        false => "merge",
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Export { format, output } => {
+            let archive = StoreClient::export(&format).await?;
+            match output {
+                Some(output) => fs::write(output, archive)?,
+                None => println!("{}", archive),
+            }
+        }
+        Command::Import {
+            file,
+            format,
+            replace,
+        } => {
+            let archive = fs::read_to_string(file)?;
+            let id = StoreClient::import(&archive, &format, import_mode(replace)).await?;
+            println!("Imported, previous state saved as snapshot {}", id);
+        }
+        Command::Snapshot => {
+            println!("{}", StoreClient::snapshot().await?);
+        }
+        Command::Snapshots => {
+            for id in StoreClient::list_snapshots().await? {
+                println!("{}", id);
+            }
+        }
+        Command::Restore { id, replace } => {
+            StoreClient::restore_snapshot(&id, import_mode(replace)).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,9 @@
 mod proxies;
+mod typed_client;
 
-pub use mechanix_store_server::store::StoreEvent;
+pub use mechanix_store_server::{
+    history::{HistoryAction, HistoryEntry},
+    store::{GetBatchResponse, StoreEvent},
+};
 pub use proxies::store_proxy;
+pub use typed_client::{from_value, to_value, TypedStoreClient};
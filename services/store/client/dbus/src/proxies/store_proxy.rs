@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
-use mechanix_store_server::store::StoreEvent;
-use tracing::info;
+use futures_util::{Stream, StreamExt};
+use mechanix_store_server::{
+    history::HistoryEntry,
+    store::{GetBatchResponse, StoreEvent},
+};
 use zbus::{proxy, Connection, Result};
 
 #[proxy(
@@ -13,11 +16,32 @@ trait Store {
     async fn insert(&self, obj: &str, key_val: (&str, &str)) -> Result<()>;
     async fn insert_batch(&self, obj: &str, keys_vals: HashMap<&str, &str>) -> Result<()>;
     async fn get(&self, obj: &str, key: &str) -> Result<String>;
-    async fn get_batch(&self, obj: &str, keys: Vec<&str>) -> Result<HashMap<String, String>>;
+    async fn get_batch(&self, obj: &str, keys: Vec<&str>) -> Result<GetBatchResponse>;
     async fn update(&self, obj: &str, key_val: (&str, &str)) -> Result<()>;
     async fn update_batch(&self, obj: &str, keys_vals: HashMap<&str, &str>) -> Result<()>;
     async fn delete(&self, obj: &str, key: &str) -> Result<String>;
     async fn delete_batch(&self, obj: &str, keys: Vec<&str>) -> Result<()>;
+    async fn transaction(
+        &self,
+        insert: HashMap<&str, HashMap<&str, &str>>,
+        update: HashMap<&str, HashMap<&str, &str>>,
+        delete: HashMap<&str, Vec<&str>>,
+    ) -> Result<()>;
+    async fn get_default(&self, obj: &str, key: &str) -> Result<String>;
+    async fn get_layer(&self, obj: &str, key: &str) -> Result<String>;
+    async fn reset(&self, obj: &str, key: &str) -> Result<String>;
+    async fn get_namespace(&self) -> Result<String>;
+    async fn history(&self, obj: &str, key: &str) -> Result<Vec<HistoryEntry>>;
+    async fn revert(&self, obj: &str, key: &str, revision: u64) -> Result<()>;
+    async fn export(&self, format: &str) -> Result<String>;
+    async fn import(&self, archive: &str, format: &str, mode: &str) -> Result<String>;
+    async fn snapshot(&self) -> Result<String>;
+    async fn list_snapshots(&self) -> Result<Vec<String>>;
+    async fn restore_snapshot(&self, id: &str, mode: &str) -> Result<()>;
+    async fn watch(&self, obj: &str, prefix: &str) -> Result<u32>;
+    async fn unwatch(&self, id: u32) -> Result<bool>;
+    #[zbus(signal)]
+    async fn watch_event(&self, id: u32, obj: String, event: StoreEvent) -> Result<()>;
     #[zbus(signal)]
     async fn notify(&self, event: StoreEvent) -> Result<()>;
 }
@@ -28,15 +52,15 @@ impl StoreClient {
     pub async fn insert(obj: &str, key_val: (&str, &str)) -> Result<()> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
-        let reply = proxy.insert(obj, key_val).await?;
-        Ok(reply)
+        proxy.insert(obj, key_val).await?;
+        Ok(())
     }
 
     pub async fn insert_batch(obj: &str, keys_vals: HashMap<&str, &str>) -> Result<()> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
-        let reply = proxy.insert_batch(obj, keys_vals).await?;
-        Ok(reply)
+        proxy.insert_batch(obj, keys_vals).await?;
+        Ok(())
     }
 
     pub async fn get(obj: &str, key: &str) -> Result<String> {
@@ -46,7 +70,7 @@ impl StoreClient {
         Ok(reply)
     }
 
-    pub async fn get_batch(obj: &str, keys: Vec<&str>) -> Result<HashMap<String, String>> {
+    pub async fn get_batch(obj: &str, keys: Vec<&str>) -> Result<GetBatchResponse> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
         let reply = proxy.get_batch(obj, keys).await?;
@@ -56,15 +80,15 @@ impl StoreClient {
     pub async fn update(obj: &str, key_val: (&str, &str)) -> Result<()> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
-        let reply = proxy.update(obj, key_val).await?;
-        Ok(reply)
+        proxy.update(obj, key_val).await?;
+        Ok(())
     }
 
     pub async fn update_batch(obj: &str, keys_vals: HashMap<&str, &str>) -> Result<()> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
-        let reply = proxy.update_batch(obj, keys_vals).await?;
-        Ok(reply)
+        proxy.update_batch(obj, keys_vals).await?;
+        Ok(())
     }
 
     pub async fn delete(obj: &str, key: &str) -> Result<String> {
@@ -77,14 +101,126 @@ impl StoreClient {
     pub async fn delete_batch(obj: &str, keys: Vec<&str>) -> Result<()> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
-        let reply = proxy.delete_batch(obj, keys).await?;
+        proxy.delete_batch(obj, keys).await?;
+        Ok(())
+    }
+
+    pub async fn get_default(obj: &str, key: &str) -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.get_default(obj, key).await?;
         Ok(reply)
     }
 
+    /// Returns `user`, `system`, `vendor` or `schema`
+    pub async fn get_layer(obj: &str, key: &str) -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.get_layer(obj, key).await?;
+        Ok(reply)
+    }
+
+    pub async fn reset(obj: &str, key: &str) -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.reset(obj, key).await?;
+        Ok(reply)
+    }
+
+    /// Returns the prefix of the keys of `apps` that only this app can
+    /// read and write
+    pub async fn get_namespace() -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.get_namespace().await?;
+        Ok(reply)
+    }
+
+    /// Changes to the key with the bus name that made them, oldest first
+    pub async fn history(obj: &str, key: &str) -> Result<Vec<HistoryEntry>> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.history(obj, key).await?;
+        Ok(reply)
+    }
+
+    /// Undoes the change recorded as `revision` in the history of the key
+    pub async fn revert(obj: &str, key: &str, revision: u64) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        proxy.revert(obj, key, revision).await?;
+        Ok(())
+    }
+
+    pub async fn transaction(
+        insert: HashMap<&str, HashMap<&str, &str>>,
+        update: HashMap<&str, HashMap<&str, &str>>,
+        delete: HashMap<&str, Vec<&str>>,
+    ) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        proxy.transaction(insert, update, delete).await?;
+        Ok(())
+    }
+
+    pub async fn export(format: &str) -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.export(format).await?;
+        Ok(reply)
+    }
+
+    /// Returns the id of the snapshot taken before the import
+    pub async fn import(archive: &str, format: &str, mode: &str) -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.import(archive, format, mode).await?;
+        Ok(reply)
+    }
+
+    pub async fn snapshot() -> Result<String> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.snapshot().await?;
+        Ok(reply)
+    }
+
+    pub async fn list_snapshots() -> Result<Vec<String>> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let reply = proxy.list_snapshots().await?;
+        Ok(reply)
+    }
+
+    pub async fn restore_snapshot(id: &str, mode: &str) -> Result<()> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        proxy.restore_snapshot(id, mode).await?;
+        Ok(())
+    }
+
     pub async fn get_notify() -> Result<NotifyStream<'static>> {
         let connection = Connection::session().await?;
         let proxy = StoreProxy::new(&connection).await?;
         let stream = proxy.receive_notify().await?;
         Ok(stream)
     }
+
+    /// Watches the keys of `obj` starting with `prefix`, the watch is removed
+    /// by the server once the returned stream is dropped
+    pub async fn watch(obj: &str, prefix: &str) -> Result<impl Stream<Item = StoreEvent> + Unpin> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let stream = proxy.receive_watch_event().await?;
+        let id = proxy.watch(obj, prefix).await?;
+        let stream = stream.filter_map(move |signal| async move {
+            let args = signal.args().ok()?;
+            if args.id == id {
+                Some(args.event)
+            } else {
+                None
+            }
+        });
+        Ok(Box::pin(stream))
+    }
 }
@@ -0,0 +1,218 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use anyhow::Result;
+use futures_util::{stream, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{
+    runtime::Handle,
+    sync::broadcast::{self, error::RecvError},
+    task::AbortHandle,
+};
+use zbus::Connection;
+
+use crate::{store_proxy::StoreProxy, StoreEvent};
+
+/// Parses a stored value, values are JSON except plain strings which are
+/// stored unquoted (`dark` rather than `"dark"`) like the rest of the store
+pub fn from_value<T: DeserializeOwned>(value: &str) -> Result<T> {
+    match serde_json::from_str(value) {
+        Ok(value) => Ok(value),
+        Err(_) => Ok(serde_json::from_value(serde_json::Value::String(
+            value.to_string(),
+        ))?),
+    }
+}
+
+/// Serializes a value the way `from_value` reads it back
+pub fn to_value<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(value) => Ok(value),
+        value => Ok(value.to_string()),
+    }
+}
+
+/// # Typed Store Client
+///
+/// Client for one `StoreObj` that reads and writes serde types, reads go
+/// through a local cache that a watch on the whole object keeps current.
+/// The watch is removed once the last clone of the client is dropped.
+#[derive(Clone)]
+pub struct TypedStoreClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    obj: String,
+    proxy: StoreProxy<'static>,
+    cache: Mutex<HashMap<String, String>>,
+    changes: broadcast::Sender<(String, String)>,
+    watch_id: u32,
+    /// Set right after the task is spawned, which needs the `Arc` first
+    sync_task: OnceLock<AbortHandle>,
+}
+
+impl Inner {
+    /// Updates the cache from an event, deleted keys are read again since
+    /// they fall back to the system, vendor or schema default
+    async fn apply(&self, event: StoreEvent) {
+        match event {
+            StoreEvent::Insert { keys_vals } | StoreEvent::Update { keys_vals } => {
+                for (key, value) in keys_vals {
+                    self.cache_value(&key, &value);
+                }
+            }
+            StoreEvent::Delete { keys_vals } => {
+                for key in keys_vals.keys() {
+                    self.cache.lock().unwrap().remove(key);
+                    if let Ok(value) = self.proxy.get(&self.obj, key).await {
+                        self.cache_value(key, &value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn cache_value(&self, key: &str, value: &str) {
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        let _ = self.changes.send((key.to_string(), value.to_string()));
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Some(sync_task) = self.sync_task.get() {
+            sync_task.abort();
+        }
+        // the server keeps the watch until it is removed
+        if let Ok(runtime) = Handle::try_current() {
+            let proxy = self.proxy.clone();
+            let watch_id = self.watch_id;
+            runtime.spawn(async move {
+                let _ = proxy.unwatch(watch_id).await;
+            });
+        }
+    }
+}
+
+impl TypedStoreClient {
+    pub async fn new(obj: &str) -> Result<Self> {
+        let connection = Connection::session().await?;
+        let proxy = StoreProxy::new(&connection).await?;
+        let (changes, _) = broadcast::channel(64);
+
+        let mut events = proxy.receive_watch_event().await?;
+        let watch_id = proxy.watch(obj, "").await?;
+        let inner = Arc::new(Inner {
+            obj: obj.to_string(),
+            proxy,
+            cache: Mutex::new(HashMap::new()),
+            changes,
+            watch_id,
+            sync_task: OnceLock::new(),
+        });
+
+        // holds the client weakly so dropping it ends the task
+        let client = Arc::downgrade(&inner);
+        let sync_task = tokio::spawn(async move {
+            while let Some(signal) = events.next().await {
+                let args = match signal.args() {
+                    Ok(args) if args.id == watch_id => args,
+                    _ => continue,
+                };
+                match client.upgrade() {
+                    Some(client) => client.apply(args.event).await,
+                    None => break,
+                }
+            }
+        });
+        let _ = inner.sync_task.set(sync_task.abort_handle());
+
+        Ok(Self { inner })
+    }
+
+    pub async fn get_raw(&self, key: &str) -> Result<String> {
+        if let Some(value) = self.inner.cache.lock().unwrap().get(key) {
+            return Ok(value.clone());
+        }
+        let value = self.inner.proxy.get(&self.inner.obj, key).await?;
+        // a watch event during the call is newer than the value read
+        let value = self
+            .inner
+            .cache
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert(value)
+            .clone();
+        Ok(value)
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        from_value(&self.get_raw(key).await?)
+    }
+
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let value = to_value(value)?;
+        self.inner
+            .proxy
+            .insert(&self.inner.obj, (key, &value))
+            .await?;
+        // the watch event caches the new value, until then the key is read
+        // from the store rather than returning the old value
+        self.inner.cache.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    /// Yields the current value of the key and then every change to it,
+    /// values that do not parse as `T` are skipped
+    pub async fn watch<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<impl Stream<Item = T> + Unpin> {
+        let changes = stream::unfold(self.inner.changes.subscribe(), |mut changes| async move {
+            loop {
+                match changes.recv().await {
+                    Ok(change) => return Some((change, changes)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        let current = self.get::<T>(key).await.ok();
+
+        let key = key.to_string();
+        let changes = changes.filter_map(move |(changed_key, value)| {
+            let value = match changed_key == key {
+                true => from_value::<T>(&value).ok(),
+                false => None,
+            };
+            async move { value }
+        });
+        Ok(Box::pin(stream::iter(current).chain(changes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip_unquoted_strings() {
+        assert_eq!(to_value(&"dark").unwrap(), "dark");
+        assert_eq!(to_value(&10).unwrap(), "10");
+        assert_eq!(to_value(&vec![1, 2]).unwrap(), "[1,2]");
+
+        assert_eq!(from_value::<String>("dark").unwrap(), "dark");
+        assert_eq!(from_value::<String>("10").unwrap(), "10");
+        assert_eq!(from_value::<i32>("10").unwrap(), 10);
+        assert!(from_value::<bool>("true").unwrap());
+        assert_eq!(from_value::<Vec<i32>>("[1,2]").unwrap(), vec![1, 2]);
+        assert!(from_value::<i32>("dark").is_err());
+    }
+}
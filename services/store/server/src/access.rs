@@ -0,0 +1,276 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use policykit::{
+    authority::AuthorityProxy,
+    types::{Subject, CHECK_AUTHORIZATION_ALLOW_USER_INTERACTION},
+};
+use tracing::info;
+use zbus::{
+    fdo::DBusProxy, message::Header, names::BusName, proxy::CacheProperties, zvariant::Value,
+    Connection,
+};
+
+use crate::store::StoreObj;
+
+/// Polkit action checked for writes the policy does not grant
+pub const POLKIT_WRITE_ACTION: &str = "org.mechanix.store.write";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Process that sent a D-Bus message, identified by its executable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    pub uid: u32,
+    pub pid: u32,
+    /// Start time of the process in clock ticks after boot, tells it apart
+    /// from a later process that reused the pid
+    pub start_time: u64,
+    pub exe: PathBuf,
+}
+
+impl Caller {
+    /// Prefix of the keys of the `apps` object that only the caller can
+    /// read and write, the full path of the executable so that a copy of a
+    /// binary elsewhere does not share its namespace
+    pub fn namespace(&self) -> String {
+        format!("{}/", self.exe.display())
+    }
+}
+
+/// Whether the key of `obj` lies in the namespace of some caller, the
+/// namespaces are absolute paths while the keys of the schema are not
+pub fn is_namespaced(obj: StoreObj, key: &str) -> bool {
+    obj == StoreObj::Apps && key.starts_with('/')
+}
+
+/// Looks up the uid and pid of the sender from the bus and its executable
+/// from `/proc`. The sender is looked up again afterwards, it is still
+/// connected with the same pid only when the process did not exit meanwhile
+/// and its pid was not reused by the process read from `/proc`.
+pub async fn get_caller(hdr: &Header<'_>, conn: &Connection) -> Result<Caller> {
+    let sender = match hdr.sender() {
+        Some(sender) => sender.to_owned(),
+        None => bail!("Message has no sender"),
+    };
+    let dbus_proxy = DBusProxy::builder(conn)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    let credentials = dbus_proxy
+        .get_connection_credentials(BusName::Unique(sender.clone()))
+        .await?;
+    let (uid, pid) = match (credentials.unix_user_id(), credentials.process_id()) {
+        (Some(uid), Some(pid)) => (uid, pid),
+        _ => bail!("Bus did not report the uid and pid of the sender"),
+    };
+    let start_time = read_start_time(pid)?;
+    let exe = fs::read_link(format!("/proc/{}/exe", pid))?;
+
+    let credentials = dbus_proxy
+        .get_connection_credentials(BusName::Unique(sender))
+        .await?;
+    if credentials.process_id() != Some(pid) || read_start_time(pid)? != start_time {
+        bail!("Sender exited while it was identified");
+    }
+    Ok(Caller {
+        uid,
+        pid,
+        start_time,
+        exe,
+    })
+}
+
+/// Start time of the process, field 22 of `/proc/<pid>/stat`
+fn read_start_time(pid: u32) -> Result<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))?;
+    // the executable name in field 2 may contain spaces and parentheses
+    let start_time = stat
+        .rsplit_once(')')
+        .and_then(|(_, fields)| fields.split_whitespace().nth(19))
+        .and_then(|start_time| start_time.parse().ok());
+    match start_time {
+        Some(start_time) => Ok(start_time),
+        None => bail!("Cannot read the start time of process {}", pid),
+    }
+}
+
+/// # Access Policy
+///
+/// Key prefixes of each object that an executable may write, `""` grants the
+/// whole object, read from `policy.yml`:
+///
+/// ```yaml
+/// /usr/bin/mechanix-settings-panel:
+///   settings: ["settings_panel."]
+/// ```
+///
+/// Every caller may read and write its own namespace in `apps` and read the
+/// other objects, everything else needs a grant or the polkit action
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    grants: HashMap<PathBuf, HashMap<StoreObj, Vec<String>>>,
+}
+
+impl AccessPolicy {
+    pub fn new(grants: HashMap<PathBuf, HashMap<StoreObj, Vec<String>>>) -> Self {
+        Self { grants }
+    }
+
+    /// Reads the policy from `path`, a missing file grants nothing
+    pub fn read(path: &Path) -> Result<Self> {
+        info!(task = "read_policy", "policy file location - {:?}", path);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => bail!("Cannot read the policy.yml in the path - {}", e),
+        };
+
+        let policy: HashMap<PathBuf, HashMap<String, Vec<String>>> =
+            match serde_yaml::from_reader(file) {
+                Ok(policy) => policy,
+                Err(e) => bail!("Error parsing the policy.yml - {}", e),
+            };
+
+        let mut grants = HashMap::new();
+        for (exe, objs) in policy {
+            let mut obj_grants = HashMap::new();
+            for (obj, prefixes) in objs {
+                obj_grants.insert(obj.parse::<StoreObj>()?, prefixes);
+            }
+            grants.insert(exe, obj_grants);
+        }
+
+        Ok(Self::new(grants))
+    }
+
+    pub fn allows(&self, caller: &Caller, access: Access, obj: StoreObj, key: &str) -> bool {
+        if obj == StoreObj::Apps && key.starts_with(&caller.namespace()) {
+            return true;
+        }
+        if access == Access::Read && obj != StoreObj::Apps {
+            return true;
+        }
+        self.grants
+            .get(&caller.exe)
+            .and_then(|objs| objs.get(&obj))
+            .is_some_and(|prefixes| prefixes.iter().any(|prefix| key.starts_with(prefix)))
+    }
+}
+
+/// Asks polkit whether the caller may perform `POLKIT_WRITE_ACTION`, the
+/// user may be prompted to authenticate. The store is on the session bus,
+/// which polkit cannot resolve `system-bus-name` subjects on, so the process
+/// is passed with its start time to rule out a reused pid.
+pub async fn check_polkit(caller: &Caller) -> Result<bool> {
+    let connection = Connection::system().await?;
+    let authority = AuthorityProxy::new(&connection).await?;
+    let subject = Subject {
+        subject_kind: "unix-process",
+        subject_details: HashMap::from([
+            ("pid", Value::from(caller.pid)),
+            ("start-time", Value::from(caller.start_time)),
+            ("uid", Value::from(caller.uid as i32)),
+        ]),
+    };
+    let result = authority
+        .check_authorization(
+            subject,
+            POLKIT_WRITE_ACTION,
+            HashMap::new(),
+            CHECK_AUTHORIZATION_ALLOW_USER_INTERACTION,
+            "",
+        )
+        .await?;
+    Ok(result.is_authorized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+    use crate::store::tests::temporary_store;
+
+    #[test]
+    fn policy_grants_namespace_and_prefixes() {
+        let settings_panel = Caller {
+            uid: 1000,
+            pid: 42,
+            start_time: 1,
+            exe: PathBuf::from("/usr/bin/mechanix-settings-panel"),
+        };
+        let other = Caller {
+            uid: 1000,
+            pid: 43,
+            start_time: 1,
+            exe: PathBuf::from("/usr/bin/some-app"),
+        };
+        let policy = AccessPolicy::new(HashMap::from([(
+            settings_panel.exe.clone(),
+            HashMap::from([(StoreObj::Settings, vec!["settings_panel.".to_string()])]),
+        )]));
+
+        assert!(policy.allows(
+            &settings_panel,
+            Access::Write,
+            StoreObj::Settings,
+            "settings_panel.brightness_step"
+        ));
+        assert!(!policy.allows(
+            &settings_panel,
+            Access::Write,
+            StoreObj::Settings,
+            "launcher.x"
+        ));
+        assert!(!policy.allows(&settings_panel, Access::Write, StoreObj::Settings, ""));
+        assert!(!policy.allows(&other, Access::Write, StoreObj::Theme, "mode"));
+        assert!(policy.allows(&other, Access::Read, StoreObj::Theme, "mode"));
+
+        assert!(policy.allows(
+            &other,
+            Access::Write,
+            StoreObj::Apps,
+            "/usr/bin/some-app/state"
+        ));
+        assert!(!policy.allows(
+            &other,
+            Access::Read,
+            StoreObj::Apps,
+            "/usr/bin/mechanix-settings-panel/state"
+        ));
+    }
+
+    #[test]
+    fn namespaced_keys_pass_the_shipped_schema() {
+        let schema = Schema::from_reader(include_str!("../schema.yml.example").as_bytes()).unwrap();
+        let mut store = temporary_store().with_schema(schema);
+        let caller = Caller {
+            uid: 1000,
+            pid: 42,
+            start_time: 1,
+            exe: PathBuf::from("/usr/bin/some-app"),
+        };
+        let key = format!("{}foo", caller.namespace());
+
+        assert!(AccessPolicy::default().allows(&caller, Access::Write, StoreObj::Apps, &key));
+        store
+            .insert(StoreObj::Apps, (key.as_str(), "bar"), "test")
+            .unwrap();
+        assert_eq!(store.get(StoreObj::Apps, &key).unwrap(), "bar");
+        assert!(store
+            .insert(StoreObj::Apps, ("unknown", "bar"), "test")
+            .is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::store::{now, Store, StoreEvent, StoreObj, StoreTransaction};
+
+/// Bumped whenever the layout of `StoreArchive` changes, archives of a newer
+/// version are rejected on import
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// # Store Archive
+///
+/// Contents of every `StoreObj` tree, used to move the store between devices
+/// and for snapshots
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoreArchive {
+    pub version: u32,
+    pub created_at: u64,
+    pub objs: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Json,
+    Yaml,
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "json" => Ok(ArchiveFormat::Json),
+            "yaml" | "yml" => Ok(ArchiveFormat::Yaml),
+            _ => bail!("Unknown archive format {}", value),
+        }
+    }
+}
+
+/// `Merge` keeps keys that are not in the archive, `Replace` deletes them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    Merge,
+    Replace,
+}
+
+impl FromStr for ImportMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "merge" => Ok(ImportMode::Merge),
+            "replace" => Ok(ImportMode::Replace),
+            _ => bail!("Unknown import mode {}", value),
+        }
+    }
+}
+
+impl StoreArchive {
+    pub fn to_string(&self, format: ArchiveFormat) -> Result<String> {
+        let archive = match format {
+            ArchiveFormat::Json => serde_json::to_string_pretty(self)?,
+            ArchiveFormat::Yaml => serde_yaml::to_string(self)?,
+        };
+        Ok(archive)
+    }
+
+    pub fn from_str(archive: &str, format: ArchiveFormat) -> Result<Self> {
+        let archive: StoreArchive = match format {
+            ArchiveFormat::Json => serde_json::from_str(archive)?,
+            ArchiveFormat::Yaml => serde_yaml::from_str(archive)?,
+        };
+        if archive.version > ARCHIVE_VERSION {
+            bail!(
+                "Archive version {} is newer than the supported version {}",
+                archive.version,
+                ARCHIVE_VERSION
+            );
+        }
+        Ok(archive)
+    }
+}
+
+impl Store {
+    pub fn export(&self) -> Result<StoreArchive> {
+        let mut objs = BTreeMap::new();
+        for obj in StoreObj::ALL {
+            objs.insert(obj.name().to_string(), self.entries(obj)?);
+        }
+
+        Ok(StoreArchive {
+            version: ARCHIVE_VERSION,
+            created_at: now(),
+            objs,
+        })
+    }
+
+    /// Restores the archive in a single transaction and returns the events
+    /// for every key that was inserted, updated or deleted
+    pub fn import(
+        &self,
+        archive: &StoreArchive,
+        mode: ImportMode,
+        changed_by: &str,
+    ) -> Result<Vec<(StoreObj, StoreEvent)>> {
+        let mut imported = HashMap::new();
+        for (obj, keys_vals) in archive.objs.iter() {
+            imported.insert(obj.parse::<StoreObj>()?, keys_vals);
+        }
+
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        let mut events = vec![];
+        for obj in StoreObj::ALL {
+            let current = self.entries(obj)?;
+            let empty = BTreeMap::new();
+            let keys_vals = imported.get(&obj).copied().unwrap_or(&empty);
+
+            let mut inserted = HashMap::new();
+            let mut updated = HashMap::new();
+            for (key, val) in keys_vals {
+                match current.get(key) {
+                    None => {
+                        inserted.insert(key.clone(), val.clone());
+                    }
+                    Some(current_val) if current_val != val => {
+                        updated.insert(key.clone(), val.clone());
+                    }
+                    Some(_) => (),
+                }
+            }
+
+            let mut deleted = HashMap::new();
+            if mode == ImportMode::Replace {
+                for key in current.keys() {
+                    if !keys_vals.contains_key(key) {
+                        deleted.insert(key.clone(), "".to_string());
+                    }
+                }
+            }
+
+            if !inserted.is_empty() {
+                transaction.insert.insert(obj, inserted.clone());
+                events.push((
+                    obj,
+                    StoreEvent::Insert {
+                        keys_vals: inserted,
+                    },
+                ));
+            }
+            if !updated.is_empty() {
+                transaction.update.insert(obj, updated.clone());
+                events.push((obj, StoreEvent::Update { keys_vals: updated }));
+            }
+            if !deleted.is_empty() {
+                transaction
+                    .delete
+                    .insert(obj, deleted.keys().cloned().collect());
+                events.push((obj, StoreEvent::Delete { keys_vals: deleted }));
+            }
+        }
+
+        self.commit(&transaction)?;
+        Ok(events)
+    }
+}
+
+/// # Snapshots
+///
+/// Exports kept next to the database, named by their creation time and a
+/// sequence number so snapshots taken within the same second are kept apart.
+/// Only the latest `keep` snapshots are kept.
+#[derive(Debug, Clone)]
+pub struct Snapshots {
+    pub dir: PathBuf,
+    pub keep: usize,
+}
+
+impl Snapshots {
+    pub fn new(dir: PathBuf, keep: usize) -> Self {
+        Self { dir, keep }
+    }
+
+    pub fn create(&self, store: &Store) -> Result<String> {
+        let archive = store.export()?;
+        let contents = archive.to_string(ArchiveFormat::Json)?;
+        fs::create_dir_all(&self.dir)?;
+
+        let mut sequence = 0;
+        let id = loop {
+            // padded so the ids sort in the order they were created
+            let id = format!("{}-{:03}", archive.created_at, sequence);
+            let file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(self.dir.join(format!("{}.json", id)));
+            match file {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    break id;
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => sequence += 1,
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        self.prune()?;
+        Ok(id)
+    }
+
+    /// Removes the oldest snapshots beyond `keep`
+    pub fn prune(&self) -> Result<()> {
+        let ids = self.list()?;
+        // the snapshot just taken is always kept
+        let excess = ids.len().saturating_sub(self.keep.max(1));
+        for id in ids.iter().take(excess) {
+            fs::remove_file(self.dir.join(format!("{}.json", id)))?;
+        }
+        Ok(())
+    }
+
+    pub fn list(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut ids = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    pub fn read(&self, id: &str) -> Result<StoreArchive> {
+        if id.contains('/') || id.contains("..") {
+            bail!("Invalid snapshot id {}", id);
+        }
+        let path = self.dir.join(format!("{}.json", id));
+        if !path.exists() {
+            bail!("Snapshot {} not found", id);
+        }
+        StoreArchive::from_str(&fs::read_to_string(path)?, ArchiveFormat::Json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::temporary_store;
+
+    #[test]
+    fn import_replace_restores_export() {
+        let mut store = temporary_store();
+        store
+            .insert(StoreObj::Settings, ("k1", "v1"), "test")
+            .unwrap();
+        store
+            .insert(StoreObj::Theme, ("mode", "dark"), "test")
+            .unwrap();
+        let archive = store.export().unwrap();
+
+        store
+            .insert(StoreObj::Settings, ("k1", "v2"), "test")
+            .unwrap();
+        store
+            .insert(StoreObj::Apps, ("pinned", "[]"), "test")
+            .unwrap();
+
+        let json = archive.to_string(ArchiveFormat::Json).unwrap();
+        let archive = StoreArchive::from_str(&json, ArchiveFormat::Json).unwrap();
+        let events = store.import(&archive, ImportMode::Replace, "test").unwrap();
+
+        assert_eq!(store.export().unwrap().objs, archive.objs);
+        assert!(events.contains(&(
+            StoreObj::Settings,
+            StoreEvent::Update {
+                keys_vals: HashMap::from([("k1".to_string(), "v1".to_string())])
+            }
+        )));
+        assert!(events.contains(&(
+            StoreObj::Apps,
+            StoreEvent::Delete {
+                keys_vals: HashMap::from([("pinned".to_string(), "".to_string())])
+            }
+        )));
+    }
+
+    #[test]
+    fn snapshots_in_the_same_second_are_kept_apart() {
+        let store = temporary_store();
+        let dir = std::env::temp_dir().join(format!(
+            "mechanix-store-snapshots-{}-{}",
+            std::process::id(),
+            now()
+        ));
+        let snapshots = Snapshots::new(dir.clone(), 2);
+
+        let first = snapshots.create(&store).unwrap();
+        let second = snapshots.create(&store).unwrap();
+        let third = snapshots.create(&store).unwrap();
+
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        assert_eq!(snapshots.list().unwrap(), vec![second, third]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
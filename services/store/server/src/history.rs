@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sled::{IVec, Tree};
+use zbus::zvariant::Type;
+
+use crate::store::{now, Store, StoreEvent, StoreObj, StoreTransaction};
+
+/// Tree holding the journal of every `StoreObj`, keyed by
+/// `<obj>\0<key>\0<revision>` so the entries of a key are adjacent and ordered
+pub const HISTORY_TREE: &str = "history";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+pub enum HistoryAction {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// One write to a key, `old` is empty for `Insert` and `new` for `Delete`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct HistoryEntry {
+    pub revision: u64,
+    pub timestamp: u64,
+    /// Bus name of the client that made the change
+    pub changed_by: String,
+    pub action: HistoryAction,
+    pub old: String,
+    pub new: String,
+}
+
+impl HistoryEntry {
+    /// Entry for a write that replaced `old` with `new`, `None` when nothing
+    /// changed
+    pub(crate) fn new(
+        revision: u64,
+        changed_by: &str,
+        old: Option<IVec>,
+        new: Option<&str>,
+    ) -> Option<Self> {
+        let old = old.map(|old| String::from_utf8_lossy(&old).to_string());
+        let action = match (&old, new) {
+            (None, None) => return None,
+            (Some(old), Some(new)) if old == new => return None,
+            (None, Some(_)) => HistoryAction::Insert,
+            (Some(_), Some(_)) => HistoryAction::Update,
+            (Some(_), None) => HistoryAction::Delete,
+        };
+        Some(Self {
+            revision,
+            timestamp: now(),
+            changed_by: changed_by.to_string(),
+            action,
+            old: old.unwrap_or_default(),
+            new: new.unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// Bounds of the journal, enforced by `Store::compact_history`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryLimits {
+    pub max_age_secs: u64,
+    pub max_entries_per_key: usize,
+}
+
+fn key_prefix(obj: StoreObj, key: &str) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(obj.name().len() + key.len() + 2);
+    prefix.extend_from_slice(obj.name().as_bytes());
+    prefix.push(0);
+    prefix.extend_from_slice(key.as_bytes());
+    prefix.push(0);
+    prefix
+}
+
+pub(crate) fn history_key(obj: StoreObj, key: &str, revision: u64) -> Vec<u8> {
+    let mut history_key = key_prefix(obj, key);
+    history_key.extend_from_slice(&revision.to_be_bytes());
+    history_key
+}
+
+impl Store {
+    pub(crate) fn history_tree(&self) -> Result<Tree> {
+        Ok(self.db().open_tree(HISTORY_TREE)?)
+    }
+
+    /// Changes to the key, oldest first
+    pub fn history(&self, obj: StoreObj, key: &str) -> Result<Vec<HistoryEntry>> {
+        let mut entries = vec![];
+        for entry in self.history_tree()?.scan_prefix(key_prefix(obj, key)) {
+            let (_, val) = entry?;
+            entries.push(serde_json::from_slice(&val)?);
+        }
+        Ok(entries)
+    }
+
+    /// Undoes the change recorded as `revision` by writing back the value the
+    /// key had before it, the revert is journaled as a change of its own
+    pub fn revert(
+        &self,
+        obj: StoreObj,
+        key: &str,
+        revision: u64,
+        changed_by: &str,
+    ) -> Result<StoreEvent> {
+        let entry: HistoryEntry = match self.history_tree()?.get(history_key(obj, key, revision))? {
+            Some(val) => serde_json::from_slice(&val)?,
+            None => bail!("Revision {} of {}.{} not found", revision, obj.name(), key),
+        };
+
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        let keys_vals = HashMap::from([(key.to_string(), entry.old.clone())]);
+        let event = match entry.action {
+            HistoryAction::Insert => {
+                transaction.delete.insert(obj, vec![key.to_string()]);
+                StoreEvent::Delete {
+                    keys_vals: HashMap::from([(key.to_string(), "".to_string())]),
+                }
+            }
+            HistoryAction::Update | HistoryAction::Delete => {
+                transaction.insert.insert(obj, keys_vals.clone());
+                StoreEvent::Update { keys_vals }
+            }
+        };
+
+        self.commit(&transaction)?;
+        Ok(event)
+    }
+
+    /// Drops entries older than `max_age_secs` and all but the newest
+    /// `max_entries_per_key` of every key, returns how many were dropped
+    pub fn compact_history(&self, limits: HistoryLimits) -> Result<usize> {
+        let tree = self.history_tree()?;
+        let cutoff = now().saturating_sub(limits.max_age_secs);
+
+        let mut keys: HashMap<Vec<u8>, Vec<(IVec, u64)>> = HashMap::new();
+        for entry in tree.iter() {
+            let (history_key, val) = entry?;
+            let entry: HistoryEntry = serde_json::from_slice(&val)?;
+            let prefix = history_key[..history_key.len().saturating_sub(8)].to_vec();
+            keys.entry(prefix)
+                .or_default()
+                .push((history_key, entry.timestamp));
+        }
+
+        let mut dropped = 0;
+        for (_, entries) in keys {
+            let excess = entries.len().saturating_sub(limits.max_entries_per_key);
+            for (i, (history_key, timestamp)) in entries.into_iter().enumerate() {
+                if i < excess || timestamp < cutoff {
+                    tree.remove(history_key)?;
+                    dropped += 1;
+                }
+            }
+        }
+        Ok(dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::temporary_store;
+
+    #[test]
+    fn journal_records_and_reverts_changes() {
+        let mut store = temporary_store();
+        store
+            .insert(StoreObj::Theme, ("mode", "dark"), ":1.1")
+            .unwrap();
+        store
+            .update(StoreObj::Theme, ("mode", "light"), ":1.2")
+            .unwrap();
+        store
+            .insert(StoreObj::Theme, ("mode", "light"), ":1.3")
+            .unwrap();
+
+        let history = store.history(StoreObj::Theme, "mode").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, HistoryAction::Insert);
+        assert_eq!(history[1].action, HistoryAction::Update);
+        assert_eq!(history[1].changed_by, ":1.2");
+        assert_eq!(history[1].old, "dark");
+
+        store
+            .revert(StoreObj::Theme, "mode", history[1].revision, ":1.4")
+            .unwrap();
+        assert_eq!(store.get(StoreObj::Theme, "mode").unwrap(), "dark");
+        store
+            .revert(StoreObj::Theme, "mode", history[0].revision, ":1.4")
+            .unwrap();
+        assert!(store.get(StoreObj::Theme, "mode").is_err());
+        assert_eq!(store.history(StoreObj::Theme, "mode").unwrap().len(), 4);
+
+        let limits = HistoryLimits {
+            max_age_secs: 3600,
+            max_entries_per_key: 1,
+        };
+        assert_eq!(store.compact_history(limits).unwrap(), 3);
+        let history = store.history(StoreObj::Theme, "mode").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, HistoryAction::Delete);
+    }
+}
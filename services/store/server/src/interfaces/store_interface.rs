@@ -1,11 +1,26 @@
-use std::collections::HashMap;
-use zbus::{fdo::Error as ZbusError, interface, zvariant::Type, SignalContext};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use zbus::{fdo::Error as ZbusError, interface, message::Header, Connection, SignalContext};
 
-use crate::store::{Store, StoreEvent, StoreObj};
+use crate::watcher::Watchers;
+use mechanix_store_server::access::{check_polkit, get_caller, Access, AccessPolicy};
+use mechanix_store_server::archive::{ArchiveFormat, ImportMode, Snapshots, StoreArchive};
+use mechanix_store_server::history::HistoryEntry;
+use mechanix_store_server::schema::SchemaError;
+use mechanix_store_server::store::{
+    to_owned_map, GetBatchResponse, Store, StoreEvent, StoreObj, StoreTransaction,
+};
 
 #[derive(Clone)]
 pub struct StoreInterface {
     pub store: Store,
+    pub watchers: Arc<Mutex<Watchers>>,
+    pub snapshots: Snapshots,
+    /// `None` when access control is disabled in settings.yml
+    pub policy: Option<Arc<AccessPolicy>>,
 }
 
 #[interface(name = "org.mechanix.store")]
@@ -14,17 +29,24 @@ impl StoreInterface {
         &mut self,
         obj: &str,
         key_val: (&str, &str),
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
         #[zbus(signal_context)] ctxt: SignalContext<'_>,
     ) -> Result<bool, ZbusError> {
-        let res = self.store.insert(StoreObj::from(obj), key_val);
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Write, &[(obj, key_val.0)])
+            .await?;
+
+        let res = self.store.insert(obj, key_val, &sender(&hdr));
         if let Err(e) = &res {
             println!("Error while inserting in store {:?}", e);
-            return Err(create_err("Failed to insert"));
+            return Err(to_err(e, "Failed to insert"));
         }
 
         let mut keys_vals = HashMap::new();
         keys_vals.insert(key_val.0.to_string(), key_val.1.to_string());
-        let _ = self.notify(&ctxt, StoreEvent::Insert { keys_vals }).await;
+        self.emit(&ctxt, obj, StoreEvent::Insert { keys_vals })
+            .await;
         Ok(true)
     }
 
@@ -32,34 +54,47 @@ impl StoreInterface {
         &mut self,
         obj: &str,
         keys_vals: HashMap<&str, &str>,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
         #[zbus(signal_context)] ctxt: SignalContext<'_>,
     ) -> Result<bool, ZbusError> {
-        let res = self.store.insert_batch(StoreObj::from(obj), &keys_vals);
+        let obj = parse_obj(obj)?;
+        let keys: Vec<(StoreObj, &str)> = keys_vals.keys().map(|key| (obj, *key)).collect();
+        self.authorize(&hdr, conn, Access::Write, &keys).await?;
+
+        let res = self.store.insert_batch(obj, &keys_vals, &sender(&hdr));
         if let Err(e) = &res {
             println!("Error while batch inserting in store {:?}", e);
-            return Err(create_err("Failed to insert batch"));
-        }
-
-        let _ = self
-            .notify(
-                &ctxt,
-                StoreEvent::Insert {
-                    keys_vals: keys_vals
-                        .iter()
-                        .map(|(k, v)| (k.to_string(), v.to_string()))
-                        .collect(),
-                },
-            )
-            .await;
+            return Err(to_err(e, "Failed to insert batch"));
+        }
+
+        self.emit(
+            &ctxt,
+            obj,
+            StoreEvent::Insert {
+                keys_vals: to_owned_map(&keys_vals),
+            },
+        )
+        .await;
 
         Ok(true)
     }
 
-    pub async fn get(&self, obj: &str, key: &str) -> Result<String, ZbusError> {
-        let res = self.store.get(StoreObj::from(obj), key);
+    pub async fn get(
+        &self,
+        obj: &str,
+        key: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<String, ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Read, &[(obj, key)])
+            .await?;
+
+        let res = self.store.get(obj, key);
         if let Err(e) = &res {
             println!("Error while getting in store {:?}", e);
-            return Err(create_err("Failed to get"));
+            return Err(to_err(e, "Failed to get"));
         }
 
         Ok(res.unwrap())
@@ -68,26 +103,44 @@ impl StoreInterface {
         &self,
         obj: &str,
         keys: Vec<String>,
-    ) -> Result<HashMap<String, String>, ZbusError> {
-        let key_vals = self.store.get_batch(StoreObj::from(obj), keys).unwrap();
-        Ok(key_vals)
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<GetBatchResponse, ZbusError> {
+        let obj = parse_obj(obj)?;
+        let obj_keys: Vec<(StoreObj, &str)> = keys.iter().map(|key| (obj, key.as_str())).collect();
+        self.authorize(&hdr, conn, Access::Read, &obj_keys).await?;
+
+        let res = self.store.get_batch(obj, &keys);
+        if let Err(e) = &res {
+            println!("Error while batch getting in store {:?}", e);
+            return Err(to_err(e, "Failed to get batch"));
+        }
+
+        Ok(res.unwrap())
     }
 
     pub async fn update(
         &mut self,
         obj: &str,
         key_val: (&str, &str),
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
         #[zbus(signal_context)] ctxt: SignalContext<'_>,
     ) -> Result<(), ZbusError> {
-        let res = self.store.update(StoreObj::from(obj), key_val);
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Write, &[(obj, key_val.0)])
+            .await?;
+
+        let res = self.store.update(obj, key_val, &sender(&hdr));
         if let Err(e) = &res {
             println!("Error while updating in store {:?}", e);
-            return Err(create_err("Failed to update"));
+            return Err(to_err(e, "Failed to update"));
         }
 
         let mut keys_vals = HashMap::new();
         keys_vals.insert(key_val.0.to_string(), key_val.1.to_string());
-        let _ = self.notify(&ctxt, StoreEvent::Update { keys_vals }).await;
+        self.emit(&ctxt, obj, StoreEvent::Update { keys_vals })
+            .await;
 
         Ok(())
     }
@@ -96,25 +149,28 @@ impl StoreInterface {
         &mut self,
         obj: &str,
         keys_vals: HashMap<&str, &str>,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
         #[zbus(signal_context)] ctxt: SignalContext<'_>,
     ) -> Result<(), ZbusError> {
-        let res = self.store.update_batch(StoreObj::from(obj), &keys_vals);
+        let obj = parse_obj(obj)?;
+        let keys: Vec<(StoreObj, &str)> = keys_vals.keys().map(|key| (obj, *key)).collect();
+        self.authorize(&hdr, conn, Access::Write, &keys).await?;
+
+        let res = self.store.update_batch(obj, &keys_vals, &sender(&hdr));
         if let Err(e) = &res {
             println!("Error while batch updating in store {:?}", e);
-            return Err(create_err("Failed to update batch"));
-        }
-
-        let _ = self
-            .notify(
-                &ctxt,
-                StoreEvent::Update {
-                    keys_vals: keys_vals
-                        .iter()
-                        .map(|(k, v)| (k.to_string(), v.to_string()))
-                        .collect(),
-                },
-            )
-            .await;
+            return Err(to_err(e, "Failed to update batch"));
+        }
+
+        self.emit(
+            &ctxt,
+            obj,
+            StoreEvent::Update {
+                keys_vals: to_owned_map(&keys_vals),
+            },
+        )
+        .await;
 
         Ok(())
     }
@@ -123,17 +179,24 @@ impl StoreInterface {
         &self,
         obj: &str,
         key: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
         #[zbus(signal_context)] ctxt: SignalContext<'_>,
     ) -> Result<String, ZbusError> {
-        let res = self.store.delete(StoreObj::from(obj), key);
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Write, &[(obj, key)])
+            .await?;
+
+        let res = self.store.delete(obj, key, &sender(&hdr));
         if let Err(e) = &res {
             println!("Error while deleting in store {:?}", e);
-            return Err(create_err("Failed to delete"));
+            return Err(to_err(e, "Failed to delete"));
         }
 
         let mut keys_vals = HashMap::new();
         keys_vals.insert(key.to_string(), "".to_string());
-        let _ = self.notify(&ctxt, StoreEvent::Delete { keys_vals }).await;
+        self.emit(&ctxt, obj, StoreEvent::Delete { keys_vals })
+            .await;
 
         Ok(res.unwrap())
     }
@@ -142,33 +205,493 @@ impl StoreInterface {
         &mut self,
         obj: &str,
         keys: Vec<&str>,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
         #[zbus(signal_context)] ctxt: SignalContext<'_>,
     ) -> Result<(), ZbusError> {
-        let res = self.store.delete_batch(StoreObj::from(obj), &keys);
+        let obj = parse_obj(obj)?;
+        let obj_keys: Vec<(StoreObj, &str)> = keys.iter().map(|key| (obj, *key)).collect();
+        self.authorize(&hdr, conn, Access::Write, &obj_keys).await?;
+
+        let res = self.store.delete_batch(obj, &keys, &sender(&hdr));
         if let Err(e) = &res {
             println!("Error while batch deleting in store {:?}", e);
-            return Err(create_err("Failed to delete batch"));
-        }
-
-        let _ = self
-            .notify(
-                &ctxt,
-                StoreEvent::Update {
-                    keys_vals: keys
-                        .iter()
-                        .map(|k| (k.to_string(), "".to_string()))
-                        .collect(),
-                },
-            )
+            return Err(to_err(e, "Failed to delete batch"));
+        }
+
+        self.emit(
+            &ctxt,
+            obj,
+            StoreEvent::Delete {
+                keys_vals: keys
+                    .iter()
+                    .map(|k| (k.to_string(), "".to_string()))
+                    .collect(),
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    /// Inserts, updates and deletes keys across several objects atomically
+    pub async fn transaction(
+        &mut self,
+        insert: HashMap<&str, HashMap<&str, &str>>,
+        update: HashMap<&str, HashMap<&str, &str>>,
+        delete: HashMap<&str, Vec<&str>>,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<(), ZbusError> {
+        let mut transaction = StoreTransaction::changed_by(&sender(&hdr));
+        for (obj, keys_vals) in insert.iter() {
+            transaction
+                .insert
+                .entry(parse_obj(obj)?)
+                .or_default()
+                .extend(to_owned_map(keys_vals));
+        }
+        for (obj, keys_vals) in update.iter() {
+            transaction
+                .update
+                .entry(parse_obj(obj)?)
+                .or_default()
+                .extend(to_owned_map(keys_vals));
+        }
+        for (obj, keys) in delete.iter() {
+            transaction
+                .delete
+                .entry(parse_obj(obj)?)
+                .or_default()
+                .extend(keys.iter().map(|k| k.to_string()));
+        }
+
+        let mut keys: Vec<(StoreObj, &str)> = vec![];
+        for (obj, keys_vals) in transaction.insert.iter().chain(transaction.update.iter()) {
+            keys.extend(keys_vals.keys().map(|key| (*obj, key.as_str())));
+        }
+        for (obj, obj_keys) in transaction.delete.iter() {
+            keys.extend(obj_keys.iter().map(|key| (*obj, key.as_str())));
+        }
+        self.authorize(&hdr, conn, Access::Write, &keys).await?;
+
+        let res = self.store.commit(&transaction);
+        if let Err(e) = &res {
+            println!("Error while committing transaction in store {:?}", e);
+            return Err(to_err(e, "Failed to commit transaction"));
+        }
+
+        for (obj, keys_vals) in transaction.insert {
+            self.emit(&ctxt, obj, StoreEvent::Insert { keys_vals })
+                .await;
+        }
+        for (obj, keys_vals) in transaction.update {
+            self.emit(&ctxt, obj, StoreEvent::Update { keys_vals })
+                .await;
+        }
+        for (obj, keys) in transaction.delete {
+            let keys_vals = keys.into_iter().map(|k| (k, "".to_string())).collect();
+            self.emit(&ctxt, obj, StoreEvent::Delete { keys_vals })
+                .await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_default(
+        &self,
+        obj: &str,
+        key: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<String, ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Read, &[(obj, key)])
+            .await?;
+
+        let res = self.store.get_default(obj, key);
+        if let Err(e) = &res {
+            println!("Error while getting default in store {:?}", e);
+            return Err(to_err(e, "No default for key"));
+        }
+
+        Ok(res.unwrap())
+    }
+
+    /// Returns the layer the value of the key comes from, one of `user`,
+    /// `system`, `vendor` or `schema`
+    pub async fn get_layer(
+        &self,
+        obj: &str,
+        key: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<String, ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Read, &[(obj, key)])
+            .await?;
+
+        let res = self.store.resolve(obj, key);
+        if let Err(e) = &res {
+            println!("Error while resolving in store {:?}", e);
+            return Err(to_err(e, "Failed to get layer"));
+        }
+
+        match res.unwrap() {
+            Some((_, layer)) => Ok(layer.to_string()),
+            None => Err(create_err("Key not found")),
+        }
+    }
+
+    /// Drops the user value of the key and returns the system, vendor or
+    /// schema default it falls back to
+    pub async fn reset(
+        &mut self,
+        obj: &str,
+        key: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<String, ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Write, &[(obj, key)])
+            .await?;
+
+        let res = self.store.reset(obj, key, &sender(&hdr));
+        if let Err(e) = &res {
+            println!("Error while resetting in store {:?}", e);
+            return Err(to_err(e, "Failed to reset"));
+        }
+
+        let default = res.unwrap();
+        let mut keys_vals = HashMap::new();
+        keys_vals.insert(key.to_string(), default.clone());
+        self.emit(&ctxt, obj, StoreEvent::Update { keys_vals })
             .await;
 
+        Ok(default)
+    }
+
+    /// Changes to the key with the bus name that made them, oldest first
+    pub async fn history(
+        &self,
+        obj: &str,
+        key: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<Vec<HistoryEntry>, ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Read, &[(obj, key)])
+            .await?;
+
+        let res = self.store.history(obj, key);
+        if let Err(e) = &res {
+            println!("Error while getting history in store {:?}", e);
+            return Err(create_err("Failed to get history"));
+        }
+
+        Ok(res.unwrap())
+    }
+
+    /// Undoes the change recorded as `revision` in the history of the key
+    pub async fn revert(
+        &mut self,
+        obj: &str,
+        key: &str,
+        revision: u64,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<(), ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Write, &[(obj, key)])
+            .await?;
+
+        let res = self.store.revert(obj, key, revision, &sender(&hdr));
+        if let Err(e) = &res {
+            println!("Error while reverting in store {:?}", e);
+            return Err(to_err(e, "Failed to revert"));
+        }
+
+        self.emit(&ctxt, obj, res.unwrap()).await;
         Ok(())
     }
 
+    /// Returns the prefix of the keys of `apps` private to the caller
+    pub async fn get_namespace(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<String, ZbusError> {
+        match get_caller(&hdr, conn).await {
+            Ok(caller) => Ok(caller.namespace()),
+            Err(e) => {
+                println!("Error while identifying caller {:?}", e);
+                Err(create_err("Failed to identify caller"))
+            }
+        }
+    }
+
+    /// Serializes every object to a versioned `json` or `yaml` archive
+    pub async fn export(
+        &self,
+        format: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<String, ZbusError> {
+        let format = parse_arg::<ArchiveFormat>(format)?;
+        self.authorize(&hdr, conn, Access::Read, &whole_store())
+            .await?;
+
+        let res = self
+            .store
+            .export()
+            .and_then(|archive| archive.to_string(format));
+        if let Err(e) = &res {
+            println!("Error while exporting store {:?}", e);
+            return Err(create_err("Failed to export"));
+        }
+
+        Ok(res.unwrap())
+    }
+
+    /// Restores an archive, `mode` is `merge` or `replace`. A snapshot of the
+    /// current values is taken first and its id returned for rolling back
+    pub async fn import(
+        &mut self,
+        archive: &str,
+        format: &str,
+        mode: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<String, ZbusError> {
+        let format = parse_arg::<ArchiveFormat>(format)?;
+        let mode = parse_arg::<ImportMode>(mode)?;
+        self.authorize(&hdr, conn, Access::Write, &whole_store())
+            .await?;
+        let archive = match StoreArchive::from_str(archive, format) {
+            Ok(archive) => archive,
+            Err(e) => return Err(ZbusError::InvalidArgs(e.to_string())),
+        };
+
+        let snapshot_id = match self.snapshots.create(&self.store) {
+            Ok(id) => id,
+            Err(e) => {
+                println!("Error while creating snapshot {:?}", e);
+                return Err(create_err("Failed to create snapshot before import"));
+            }
+        };
+
+        self.restore(&archive, mode, &hdr, &ctxt).await?;
+        Ok(snapshot_id)
+    }
+
+    pub async fn snapshot(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<String, ZbusError> {
+        self.authorize(&hdr, conn, Access::Write, &whole_store())
+            .await?;
+
+        let res = self.snapshots.create(&self.store);
+        if let Err(e) = &res {
+            println!("Error while creating snapshot {:?}", e);
+            return Err(create_err("Failed to create snapshot"));
+        }
+
+        Ok(res.unwrap())
+    }
+
+    pub async fn list_snapshots(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<Vec<String>, ZbusError> {
+        self.authorize(&hdr, conn, Access::Read, &whole_store())
+            .await?;
+
+        let res = self.snapshots.list();
+        if let Err(e) = &res {
+            println!("Error while listing snapshots {:?}", e);
+            return Err(create_err("Failed to list snapshots"));
+        }
+
+        Ok(res.unwrap())
+    }
+
+    pub async fn restore_snapshot(
+        &mut self,
+        id: &str,
+        mode: &str,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> Result<(), ZbusError> {
+        let mode = parse_arg::<ImportMode>(mode)?;
+        self.authorize(&hdr, conn, Access::Write, &whole_store())
+            .await?;
+        let archive = match self.snapshots.read(id) {
+            Ok(archive) => archive,
+            Err(e) => return Err(ZbusError::InvalidArgs(e.to_string())),
+        };
+
+        self.restore(&archive, mode, &hdr, &ctxt).await
+    }
+
+    /// Registers a watch on the keys of `obj` starting with `prefix`, matching
+    /// changes are sent to the caller only through `WatchEvent`
+    pub async fn watch(
+        &self,
+        obj: &str,
+        prefix: String,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<u32, ZbusError> {
+        let obj = parse_obj(obj)?;
+        self.authorize(&hdr, conn, Access::Read, &[(obj, &prefix)])
+            .await?;
+
+        let owner = match hdr.sender() {
+            Some(sender) => sender.to_owned().into(),
+            None => return Err(create_err("Failed to get sender")),
+        };
+
+        let id = self.watchers.lock().unwrap().add(owner, obj, prefix);
+        Ok(id)
+    }
+
+    pub async fn unwatch(
+        &self,
+        id: u32,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> Result<bool, ZbusError> {
+        let owner = match hdr.sender() {
+            Some(sender) => sender.to_string(),
+            None => return Err(create_err("Failed to get sender")),
+        };
+
+        Ok(self.watchers.lock().unwrap().remove(&owner, id))
+    }
+
+    #[zbus(signal)]
+    pub async fn watch_event(
+        &self,
+        ctxt: &SignalContext<'_>,
+        id: u32,
+        obj: &str,
+        event: StoreEvent,
+    ) -> Result<(), zbus::Error>;
+
     #[zbus(signal)]
     async fn notify(&self, ctxt: &SignalContext<'_>, event: StoreEvent) -> Result<(), zbus::Error>;
 }
 
+impl StoreInterface {
+    async fn restore(
+        &self,
+        archive: &StoreArchive,
+        mode: ImportMode,
+        hdr: &Header<'_>,
+        ctxt: &SignalContext<'_>,
+    ) -> Result<(), ZbusError> {
+        let res = self.store.import(archive, mode, &sender(hdr));
+        if let Err(e) = &res {
+            println!("Error while importing in store {:?}", e);
+            return Err(to_err(e, "Failed to import"));
+        }
+
+        for (obj, event) in res.unwrap() {
+            self.emit(ctxt, obj, event).await;
+        }
+
+        Ok(())
+    }
+
+    /// Broadcasts the event through `Notify`, except for `apps` whose keys
+    /// are private to their app and only sent to authorized watchers
+    async fn emit(&self, ctxt: &SignalContext<'_>, obj: StoreObj, event: StoreEvent) {
+        if obj != StoreObj::Apps {
+            let _ = self.notify(ctxt, event).await;
+        }
+    }
+
+    /// Allows the call if the policy grants every key to the caller, writes
+    /// that are not granted fall back to the polkit action
+    async fn authorize(
+        &self,
+        hdr: &Header<'_>,
+        conn: &Connection,
+        access: Access,
+        keys: &[(StoreObj, &str)],
+    ) -> Result<(), ZbusError> {
+        let policy = match &self.policy {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+
+        let caller = match get_caller(hdr, conn).await {
+            Ok(caller) => caller,
+            Err(e) => {
+                println!("Error while identifying caller {:?}", e);
+                return Err(ZbusError::AccessDenied("Failed to identify caller".into()));
+            }
+        };
+
+        if keys
+            .iter()
+            .all(|(obj, key)| policy.allows(&caller, access, *obj, key))
+        {
+            return Ok(());
+        }
+
+        if access == Access::Write {
+            match check_polkit(&caller).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => (),
+                Err(e) => println!("Error while checking polkit authorization {:?}", e),
+            }
+        }
+
+        Err(ZbusError::AccessDenied(format!(
+            "{:?} is not allowed to access these keys",
+            caller.exe
+        )))
+    }
+}
+
+/// Unique bus name of the caller, recorded in the history of the keys it changes
+fn sender(hdr: &Header<'_>) -> String {
+    hdr.sender()
+        .map(|sender| sender.to_string())
+        .unwrap_or_default()
+}
+
+/// Keys standing for every object, only granted by a `""` prefix
+fn whole_store() -> Vec<(StoreObj, &'static str)> {
+    StoreObj::ALL.iter().map(|obj| (*obj, "")).collect()
+}
+
 fn create_err<S: Into<String>>(msg: S) -> ZbusError {
-    return ZbusError::Failed(msg.into());
+    ZbusError::Failed(msg.into())
+}
+
+/// Schema violations are reported with their message so clients can tell
+/// what was wrong with the value
+fn to_err<S: Into<String>>(e: &anyhow::Error, msg: S) -> ZbusError {
+    match e.downcast_ref::<SchemaError>() {
+        Some(e) => ZbusError::InvalidArgs(e.to_string()),
+        None => create_err(msg),
+    }
+}
+
+fn parse_obj(obj: &str) -> Result<StoreObj, ZbusError> {
+    parse_arg::<StoreObj>(obj)
+}
+
+fn parse_arg<T: FromStr<Err = anyhow::Error>>(arg: &str) -> Result<T, ZbusError> {
+    arg.parse::<T>()
+        .map_err(|e| ZbusError::InvalidArgs(e.to_string()))
 }
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, fmt, fs::File, path::Path};
+
+use anyhow::{bail, Result};
+use tracing::info;
+
+use crate::store::StoreObj;
+
+/// Where the value returned for a key comes from, from highest to lowest
+/// priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    User,
+    System,
+    Vendor,
+    Schema,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Layer::User => write!(f, "user"),
+            Layer::System => write!(f, "system"),
+            Layer::Vendor => write!(f, "vendor"),
+            Layer::Schema => write!(f, "schema"),
+        }
+    }
+}
+
+/// # Defaults Layer
+///
+/// Read-only values of the system (`/etc`) or vendor (`/usr/share`) layer,
+/// read from a `defaults.yml` of `<obj>: { <key>: <value> }`
+#[derive(Debug, Clone, Default)]
+pub struct DefaultsLayer {
+    objs: HashMap<StoreObj, HashMap<String, String>>,
+}
+
+impl DefaultsLayer {
+    pub fn new(objs: HashMap<StoreObj, HashMap<String, String>>) -> Self {
+        Self { objs }
+    }
+
+    /// Reads the layer from `path`, a missing file is an empty layer
+    pub fn read(path: &Path) -> Result<Self> {
+        info!(
+            task = "read_defaults",
+            "defaults file location - {:?}", path
+        );
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => bail!("Cannot read the defaults in {:?} - {}", path, e),
+        };
+
+        let objs: HashMap<String, Option<HashMap<String, String>>> =
+            match serde_yaml::from_reader(file) {
+                Ok(objs) => objs,
+                Err(e) => bail!("Error parsing the defaults in {:?} - {}", path, e),
+            };
+
+        let mut layer = HashMap::new();
+        for (obj, keys_vals) in objs {
+            layer.insert(obj.parse::<StoreObj>()?, keys_vals.unwrap_or_default());
+        }
+
+        Ok(Self::new(layer))
+    }
+
+    pub fn get(&self, obj: StoreObj, key: &str) -> Option<&String> {
+        self.objs.get(&obj)?.get(key)
+    }
+}
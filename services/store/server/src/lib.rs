@@ -1 +1,7 @@
+pub mod access;
+pub mod archive;
+pub mod history;
+pub mod layers;
+pub mod schema;
+pub mod settings;
 pub mod store;
@@ -1,22 +1,119 @@
-use std::future;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::Result;
 mod interfaces;
-mod store;
+mod watcher;
 use interfaces::store_interface::StoreInterface;
-use store::Store;
+use mechanix_store_server::{
+    access::AccessPolicy,
+    archive::{ImportMode, Snapshots},
+    layers::DefaultsLayer,
+    schema::{read_schema_yml, Schema},
+    settings::{read_settings_yml, StoreServerSettings},
+    store::{Store, StoreOpenError, StoreOpenErrorCodes},
+};
+use watcher::{watch_event_forwarder, Watchers};
 use zbus::connection;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let store = Store::new();
-    let store_bus = StoreInterface { store };
-    let _store_bus_connection = connection::Builder::session()?
+    let settings = match read_settings_yml() {
+        Ok(settings) => settings,
+        Err(e) => {
+            println!("error while reading settings.yml {:?}", e);
+            StoreServerSettings::default()
+        }
+    };
+    let schema = match read_schema_yml() {
+        Ok(schema) => schema,
+        Err(e) => {
+            println!("error while reading schema.yml {:?}", e);
+            Schema::default()
+        }
+    };
+    let system = read_defaults_layer(&settings.system_defaults_path);
+    let vendor = read_defaults_layer(&settings.vendor_defaults_path);
+
+    let snapshots = Snapshots::new(settings.snapshots_path()?, settings.max_snapshots);
+    let store = open_store(&settings, &snapshots)?
+        .with_schema(schema)
+        .with_layers(system, vendor);
+    let policy = match settings.enforce_access {
+        true => Some(Arc::new(AccessPolicy::read(&settings.access_policy_path)?)),
+        false => {
+            println!("access control is disabled");
+            None
+        }
+    };
+    let history_limits = settings.history_limits();
+    let compact_store = store.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = compact_store.compact_history(history_limits) {
+                println!("error while compacting history {:?}", e);
+            }
+        }
+    });
+
+    let watchers = Arc::new(Mutex::new(Watchers::default()));
+    let store_bus = StoreInterface {
+        store: store.clone(),
+        watchers: watchers.clone(),
+        snapshots,
+        policy,
+    };
+    let store_bus_connection = connection::Builder::session()?
         .name("org.mechanix.store")?
         .serve_at("/org/mechanix/store", store_bus.clone())?
         .build()
         .await?;
-    let future = future::pending();
-    let () = future.await;
+
+    watch_event_forwarder(store, watchers, store_bus_connection).await?;
     Ok(())
 }
+
+fn read_defaults_layer(path: &std::path::Path) -> DefaultsLayer {
+    match DefaultsLayer::read(path) {
+        Ok(layer) => layer,
+        Err(e) => {
+            println!("error while reading defaults {:?}", e);
+            DefaultsLayer::default()
+        }
+    }
+}
+
+/// Opens the user database, a corrupted database is moved aside and the
+/// latest snapshot restored when `recover_corrupted` is set
+fn open_store(settings: &StoreServerSettings, snapshots: &Snapshots) -> Result<Store> {
+    let db_path = settings.db_path()?;
+    let e = match Store::open(&db_path) {
+        Ok(store) => return Ok(store),
+        Err(e) => e,
+    };
+
+    let corrupted = e
+        .downcast_ref::<StoreOpenError>()
+        .is_some_and(|e| e.code == StoreOpenErrorCodes::Corrupted);
+    if !corrupted || !settings.recover_corrupted {
+        return Err(e);
+    }
+
+    println!("{}", e);
+    let (store, moved_to) = Store::recover(&db_path)?;
+    println!("corrupted store database moved to {:?}", moved_to);
+
+    if let Some(id) = snapshots.list()?.last() {
+        store.import(
+            &snapshots.read(id)?,
+            ImportMode::Replace,
+            "mechanix_store_server",
+        )?;
+        println!("store restored from snapshot {}", id);
+    }
+    Ok(store)
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, fmt, fs::File, io::Read, path::PathBuf};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::access::is_namespaced;
+use crate::store::StoreObj;
+
+/// Type of the values a key accepts, values are always stored as strings
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeyType {
+    Bool,
+    Int { min: Option<i64>, max: Option<i64> },
+    Enum { values: Vec<String> },
+    String { max_len: Option<usize> },
+    Json,
+}
+
+impl KeyType {
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            KeyType::Bool => match value {
+                "true" | "false" => Ok(()),
+                _ => Err(format!("expected true or false, found {:?}", value)),
+            },
+            KeyType::Int { min, max } => {
+                let value = match value.parse::<i64>() {
+                    Ok(value) => value,
+                    Err(_) => return Err(format!("expected an integer, found {:?}", value)),
+                };
+                if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+                    return Err(format!(
+                        "{} is out of range {}..={}",
+                        value,
+                        min.map(|m| m.to_string()).unwrap_or_default(),
+                        max.map(|m| m.to_string()).unwrap_or_default()
+                    ));
+                }
+                Ok(())
+            }
+            KeyType::Enum { values } => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(format!("expected one of {:?}, found {:?}", values, value))
+                }
+            }
+            KeyType::String { max_len } => match max_len {
+                Some(max_len) if value.chars().count() > *max_len => {
+                    Err(format!("longer than {} characters", max_len))
+                }
+                _ => Ok(()),
+            },
+            KeyType::Json => match serde_json::from_str::<serde_json::Value>(value) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("invalid JSON - {}", e)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeySchema {
+    #[serde(flatten)]
+    pub key_type: KeyType,
+    pub default: Option<String>,
+    pub component: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ObjSchema {
+    /// Rejects keys that are not declared in `keys`
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub keys: HashMap<String, KeySchema>,
+}
+
+/// Raised for writes that do not match the schema, returned to D-Bus clients
+/// as `InvalidArgs` with the message
+#[derive(Debug)]
+pub struct SchemaError {
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// # Schema
+///
+/// Declares the type, default and owning component of the keys of each
+/// `StoreObj`, read from `schema.yml`
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    objs: HashMap<StoreObj, ObjSchema>,
+}
+
+impl Schema {
+    pub fn new(objs: HashMap<StoreObj, ObjSchema>) -> Result<Self> {
+        for (obj, obj_schema) in objs.iter() {
+            for (key, key_schema) in obj_schema.keys.iter() {
+                if let Some(default) = &key_schema.default {
+                    if let Err(e) = key_schema.key_type.validate(default) {
+                        bail!("Invalid default for {}.{}: {}", obj.name(), key, e);
+                    }
+                }
+            }
+        }
+        Ok(Self { objs })
+    }
+
+    /// Parses the YAML of a `schema.yml`
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let objs: HashMap<String, ObjSchema> = match serde_yaml::from_reader(reader) {
+            Ok(objs) => objs,
+            Err(e) => bail!("Error parsing the schema.yml - {}", e),
+        };
+
+        let mut schema = HashMap::new();
+        for (obj, obj_schema) in objs {
+            schema.insert(obj.parse::<StoreObj>()?, obj_schema);
+        }
+
+        Self::new(schema)
+    }
+
+    pub fn get(&self, obj: StoreObj, key: &str) -> Option<&KeySchema> {
+        self.objs.get(&obj)?.keys.get(key)
+    }
+
+    pub fn default_value(&self, obj: StoreObj, key: &str) -> Option<String> {
+        self.get(obj, key)?.default.clone()
+    }
+
+    pub fn validate(&self, obj: StoreObj, key: &str, value: &str) -> Result<(), SchemaError> {
+        let obj_schema = match self.objs.get(&obj) {
+            Some(obj_schema) => obj_schema,
+            None => return Ok(()),
+        };
+
+        match obj_schema.keys.get(key) {
+            Some(key_schema) => key_schema
+                .key_type
+                .validate(value)
+                .map_err(|e| SchemaError {
+                    message: format!("Invalid value for {}.{}: {}", obj.name(), key, e),
+                }),
+            // apps declare their own keys, they are not in the schema
+            None if obj_schema.strict && !is_namespaced(obj, key) => Err(SchemaError {
+                message: format!("Unknown key {} in {}", key, obj.name()),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// # Reads Schema YML
+///
+/// Reads the schema from `MECHANIX_STORE_SCHEMA_PATH`, falls back to an
+/// empty schema (all writes accepted) when the file does not exist
+pub fn read_schema_yml() -> Result<Schema> {
+    let file_path = PathBuf::from(
+        std::env::var("MECHANIX_STORE_SCHEMA_PATH")
+            .unwrap_or(String::from("/usr/share/mechanix/store/schema.yml")),
+    );
+
+    info!(
+        task = "read_schema",
+        "schema file location - {:?}", file_path
+    );
+
+    if !file_path.exists() {
+        return Ok(Schema::default());
+    }
+
+    let file = match File::open(&file_path) {
+        Ok(file) => file,
+        Err(e) => bail!("Cannot read the schema.yml in the path - {}", e),
+    };
+
+    Schema::from_reader(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_values_against_key_type() {
+        let schema: HashMap<String, ObjSchema> = serde_yaml::from_str(
+            r#"
+theme:
+  strict: true
+  keys:
+    accent:
+      type: enum
+      values: [blue, red]
+      default: blue
+      component: settings-app
+    font_size:
+      type: int
+      min: 8
+      max: 32
+      component: settings-app
+"#,
+        )
+        .unwrap();
+        let schema = Schema::new(
+            schema
+                .into_iter()
+                .map(|(obj, s)| (obj.parse().unwrap(), s))
+                .collect(),
+        )
+        .unwrap();
+
+        assert!(schema.validate(StoreObj::Theme, "accent", "red").is_ok());
+        assert!(schema.validate(StoreObj::Theme, "accent", "green").is_err());
+        assert!(schema.validate(StoreObj::Theme, "font_size", "33").is_err());
+        assert!(schema.validate(StoreObj::Theme, "font_size", "x").is_err());
+        assert!(schema.validate(StoreObj::Theme, "unknown", "1").is_err());
+        assert!(schema.validate(StoreObj::Settings, "unknown", "1").is_ok());
+        assert_eq!(
+            schema.default_value(StoreObj::Theme, "accent"),
+            Some("blue".to_string())
+        );
+    }
+}
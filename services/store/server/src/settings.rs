@@ -0,0 +1,132 @@
+use std::{env, fs::File, path::PathBuf};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::history::HistoryLimits;
+
+/// # Store Server Settings
+///
+/// Locations of the per-user database and of the read-only layers below it,
+/// every field is optional so an empty or missing settings.yml keeps the defaults
+#[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
+pub struct StoreServerSettings {
+    /// Per-user sled database, `~/.config/mechanix/store/db` when not set
+    pub db_path: Option<PathBuf>,
+    /// Snapshots taken before imports, next to the database when not set
+    pub snapshots_path: Option<PathBuf>,
+    /// Number of snapshots kept, older ones are removed
+    pub max_snapshots: usize,
+    /// Defaults maintained by the admin of the device
+    pub system_defaults_path: PathBuf,
+    /// Defaults shipped with the image
+    pub vendor_defaults_path: PathBuf,
+    /// Executables and the keys they may write, see `AccessPolicy`
+    pub access_policy_path: PathBuf,
+    /// Checks every call against the access policy, only disable for development
+    pub enforce_access: bool,
+    /// Changes older than this are dropped from the history
+    pub history_max_age_days: u64,
+    /// Number of changes kept in the history of each key
+    pub history_max_entries_per_key: usize,
+    /// Moves a corrupted database aside and restores the latest snapshot
+    /// instead of refusing to start
+    pub recover_corrupted: bool,
+}
+
+impl Default for StoreServerSettings {
+    fn default() -> Self {
+        Self {
+            db_path: None,
+            snapshots_path: None,
+            max_snapshots: 10,
+            system_defaults_path: PathBuf::from("/etc/mechanix/store/defaults.yml"),
+            vendor_defaults_path: PathBuf::from("/usr/share/mechanix/store/defaults.yml"),
+            access_policy_path: PathBuf::from("/etc/mechanix/store/policy.yml"),
+            enforce_access: true,
+            history_max_age_days: 30,
+            history_max_entries_per_key: 50,
+            recover_corrupted: true,
+        }
+    }
+}
+
+impl StoreServerSettings {
+    pub fn db_path(&self) -> Result<PathBuf> {
+        if let Some(db_path) = &self.db_path {
+            return Ok(db_path.clone());
+        }
+        match dirs::home_dir() {
+            Some(home_dir) => Ok(home_dir.join(".config/mechanix/store/db")),
+            None => bail!("Cannot find the home directory, set db_path in settings.yml"),
+        }
+    }
+
+    pub fn history_limits(&self) -> HistoryLimits {
+        HistoryLimits {
+            max_age_secs: self.history_max_age_days * 24 * 60 * 60,
+            max_entries_per_key: self.history_max_entries_per_key,
+        }
+    }
+
+    pub fn snapshots_path(&self) -> Result<PathBuf> {
+        if let Some(snapshots_path) = &self.snapshots_path {
+            return Ok(snapshots_path.clone());
+        }
+        let db_path = self.db_path()?;
+        match db_path.parent() {
+            Some(parent) => Ok(parent.join("snapshots")),
+            None => bail!("Cannot place snapshots next to {:?}", db_path),
+        }
+    }
+}
+
+/// # Reads Settings path from arg
+///
+/// Reads the `-s` or `--settings` argument for the path
+pub fn read_settings_path_from_args() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 2 && (args[1] == "-s" || args[1] == "--settings") {
+        debug!("Using settings path from argument - {}", args[2]);
+        return Some(args[2].clone());
+    }
+    None
+}
+
+/// # Reads Settings YML
+///
+/// Reads the `settings.yml` and parses it to StoreServerSettings, falls back
+/// to the defaults when the file does not exist
+pub fn read_settings_yml() -> Result<StoreServerSettings> {
+    let mut file_path = PathBuf::from(
+        std::env::var("MECHANIX_STORE_SERVER_SETTINGS_PATH")
+            .unwrap_or(String::from("/etc/mechanix/store/settings.yml")),
+    );
+
+    if let Some(file_path_in_args) = read_settings_path_from_args() {
+        file_path = PathBuf::from(file_path_in_args);
+    }
+
+    info!(
+        task = "read_settings",
+        "settings file location - {:?}", file_path
+    );
+
+    if !file_path.exists() {
+        return Ok(StoreServerSettings::default());
+    }
+
+    let file = match File::open(&file_path) {
+        Ok(file) => file,
+        Err(e) => bail!("Cannot read the settings.yml in the path - {}", e),
+    };
+
+    let settings: StoreServerSettings = match serde_yaml::from_reader(file) {
+        Ok(settings) => settings,
+        Err(e) => bail!("Error parsing the settings.yml - {}", e),
+    };
+
+    Ok(settings)
+}
@@ -1,25 +1,53 @@
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
-use sled::{open, Batch, Config, Db, Subscriber, Tree};
+use sled::{
+    transaction::{abort, TransactionError},
+    Config, Db, IVec, Transactional, Tree,
+};
 use tokio::sync::mpsc;
-use zbus::zvariant::{self, DeserializeDict, OwnedValue, SerializeDict, Type};
+use zbus::zvariant::Type;
 
-#[derive(Debug, Clone)]
+use crate::history::{history_key, HistoryEntry};
+use crate::layers::{DefaultsLayer, Layer};
+use crate::schema::Schema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StoreObj {
     Settings,
     Theme,
     Apps,
 }
 
-impl From<&str> for StoreObj {
-    fn from(value: &str) -> Self {
+impl FromStr for StoreObj {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
         match value {
-            "settings" => StoreObj::Settings,
-            "theme" => StoreObj::Theme,
-            "apps" => StoreObj::Apps,
-            _ => StoreObj::Settings,
+            "settings" => Ok(StoreObj::Settings),
+            "theme" => Ok(StoreObj::Theme),
+            "apps" => Ok(StoreObj::Apps),
+            _ => bail!("Unknown object {}", value),
+        }
+    }
+}
+
+impl StoreObj {
+    pub const ALL: [StoreObj; 3] = [StoreObj::Settings, StoreObj::Theme, StoreObj::Apps];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoreObj::Settings => "settings",
+            StoreObj::Theme => "theme",
+            StoreObj::Apps => "apps",
         }
     }
 }
@@ -41,18 +69,175 @@ pub enum StoreEvent {
     Delete { keys_vals: HashMap<String, String> },
 }
 
+/// Values found by `Store::get_batch`, keys that are not in the tree are
+/// reported in `missing` instead of failing the whole read
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type, Default)]
+pub struct GetBatchResponse {
+    pub keys_vals: HashMap<String, String>,
+    pub missing: Vec<String>,
+}
+
+/// Writes to one or more trees that are committed atomically by
+/// `Store::commit`, `update` fails the whole transaction if a key is missing
+/// from every layer
+#[derive(Debug, Clone, Default)]
+pub struct StoreTransaction {
+    pub insert: HashMap<StoreObj, HashMap<String, String>>,
+    pub update: HashMap<StoreObj, HashMap<String, String>>,
+    pub delete: HashMap<StoreObj, Vec<String>>,
+    /// Recorded in the history of every key the transaction changes
+    pub changed_by: String,
+}
+
+impl StoreTransaction {
+    pub fn changed_by(changed_by: &str) -> Self {
+        Self {
+            changed_by: changed_by.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn objs(&self) -> Vec<StoreObj> {
+        let mut objs: Vec<StoreObj> = vec![];
+        for obj in self
+            .insert
+            .keys()
+            .chain(self.update.keys())
+            .chain(self.delete.keys())
+        {
+            if !objs.contains(obj) {
+                objs.push(*obj);
+            }
+        }
+        objs
+    }
+
+    fn len(&self) -> usize {
+        self.insert
+            .values()
+            .chain(self.update.values())
+            .map(|keys_vals| keys_vals.len())
+            .chain(self.delete.values().map(|keys| keys.len()))
+            .sum()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StoreOpenErrorCodes {
+    #[default]
+    OpenError,
+    Locked,
+    Corrupted,
+}
+
+impl fmt::Display for StoreOpenErrorCodes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreOpenErrorCodes::OpenError => write!(f, "OpenError"),
+            StoreOpenErrorCodes::Locked => write!(f, "Locked"),
+            StoreOpenErrorCodes::Corrupted => write!(f, "Corrupted"),
+        }
+    }
+}
+
+/// Raised by `Store::open` when the database cannot be used, `Corrupted`
+/// databases can be moved aside with `Store::recover`
+#[derive(Debug)]
+pub struct StoreOpenError {
+    pub code: StoreOpenErrorCodes,
+    pub message: String,
+}
+
+impl fmt::Display for StoreOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(code: {}, message: {})", self.code, self.message)
+    }
+}
+
+impl std::error::Error for StoreOpenError {}
+
+impl StoreOpenError {
+    fn new(path: &Path, e: sled::Error) -> Self {
+        match e {
+            sled::Error::Io(e) if e.to_string().contains("could not acquire lock") => Self {
+                code: StoreOpenErrorCodes::Locked,
+                message: format!(
+                    "Store database {:?} is locked, is another mechanix_store_server running?",
+                    path
+                ),
+            },
+            sled::Error::Corruption { .. } => Self {
+                code: StoreOpenErrorCodes::Corrupted,
+                message: format!("Store database {:?} is corrupted - {}", path, e),
+            },
+            e => Self {
+                code: StoreOpenErrorCodes::OpenError,
+                message: format!("Cannot open the store database {:?} - {}", path, e),
+            },
+        }
+    }
+}
+
+/// # Store
+///
+/// Per-user sled database layered over the read-only system and vendor
+/// defaults and the schema defaults, writes only ever go to the user layer
 #[derive(Debug, Clone)]
 pub struct Store {
     db: Db,
+    schema: Arc<Schema>,
+    system: Arc<DefaultsLayer>,
+    vendor: Arc<DefaultsLayer>,
 }
 
 impl Store {
-    pub fn new() -> Self {
-        let home_dir = dirs::home_dir().unwrap();
-        let path = home_dir.join(".config/mechanix/store/db");
-        let config = Config::new().path(path);
-        let db = config.open().unwrap();
-        Self { db }
+    fn from_db(db: Db) -> Self {
+        Self {
+            db,
+            schema: Arc::new(Schema::default()),
+            system: Arc::new(DefaultsLayer::default()),
+            vendor: Arc::new(DefaultsLayer::default()),
+        }
+    }
+
+    /// Opens the user database at `path`, fails with a `StoreOpenError`
+    /// instead of panicking when it is locked or corrupted
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match Config::new().path(path).open() {
+            Ok(db) => Ok(Self::from_db(db)),
+            Err(e) => bail!(StoreOpenError::new(path, e)),
+        }
+    }
+
+    /// Moves the database at `path` aside and opens an empty one in its
+    /// place, returns the store and where the old database was moved to
+    pub fn recover(path: &Path) -> Result<(Self, PathBuf)> {
+        let mut moved_to = path.as_os_str().to_owned();
+        moved_to.push(format!(".corrupted-{}", now()));
+        let moved_to = PathBuf::from(moved_to);
+
+        if path.exists() {
+            fs::rename(path, &moved_to)?;
+        }
+        Ok((Self::open(path)?, moved_to))
+    }
+
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = Arc::new(schema);
+        self
+    }
+
+    pub fn with_layers(mut self, system: DefaultsLayer, vendor: DefaultsLayer) -> Self {
+        self.system = Arc::new(system);
+        self.vendor = Arc::new(vendor);
+        self
+    }
+
+    pub(crate) fn db(&self) -> &Db {
+        &self.db
     }
 
     fn get_tree(&self, obj: StoreObj) -> Result<Tree> {
@@ -60,78 +245,220 @@ impl Store {
         Ok(tree)
     }
 
-    pub fn insert(&mut self, obj: StoreObj, key_val: (&str, &str)) -> Result<()> {
-        let tree = self.get_tree(obj)?;
-        let _ = tree.insert(key_val.0, key_val.1)?;
-        Ok(())
+    pub fn insert(&mut self, obj: StoreObj, key_val: (&str, &str), changed_by: &str) -> Result<()> {
+        self.insert_batch(obj, &HashMap::from([key_val]), changed_by)
+    }
+
+    pub fn insert_batch(
+        &mut self,
+        obj: StoreObj,
+        keys_vals: &HashMap<&str, &str>,
+        changed_by: &str,
+    ) -> Result<()> {
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        transaction.insert.insert(obj, to_owned_map(keys_vals));
+        self.commit(&transaction)
+    }
+
+    /// Gets the value of the key from the first layer that has it
+    pub fn get(&self, obj: StoreObj, key: &str) -> Result<String> {
+        match self.resolve(obj, key)? {
+            Some((val, _)) => Ok(val),
+            None => bail!("Key not found"),
+        }
     }
 
-    pub fn insert_batch(&mut self, obj: StoreObj, keys_vals: &HashMap<&str, &str>) -> Result<()> {
+    /// Gets the value of the key and the layer it was found in
+    pub fn resolve(&self, obj: StoreObj, key: &str) -> Result<Option<(String, Layer)>> {
         let tree = self.get_tree(obj)?;
-        let mut batch = Batch::default();
-        for (&key, &val) in keys_vals.into_iter() {
-            batch.insert(key, val);
+        if let Some(val) = tree.get(key)? {
+            let val = std::str::from_utf8(&val)?.to_string();
+            return Ok(Some((val, Layer::User)));
         }
-        tree.apply_batch(batch)?;
-        Ok(())
+        Ok(self.resolve_default(obj, key))
     }
 
-    pub fn get(&self, obj: StoreObj, key: &str) -> Result<String> {
-        let tree = self.get_tree(obj.clone())?;
-        let res = tree.get(key)?;
-        if res.is_none() {
-            bail!("Key not found");
+    /// Resolves the key through the system, vendor and schema layers
+    fn resolve_default(&self, obj: StoreObj, key: &str) -> Option<(String, Layer)> {
+        if let Some(val) = self.system.get(obj, key) {
+            return Some((val.clone(), Layer::System));
         }
-        let val = std::str::from_utf8(&res.unwrap())?.to_string();
-        Ok(val)
+        if let Some(val) = self.vendor.get(obj, key) {
+            return Some((val.clone(), Layer::Vendor));
+        }
+        self.schema
+            .default_value(obj, key)
+            .map(|val| (val, Layer::Schema))
     }
 
-    pub fn get_batch<S: Into<String>>(&self, obj: StoreObj, keys: Vec<S>) -> Result<HashMap<S, S>> {
-        Ok(HashMap::new())
+    /// Gets the value the key falls back to when the user has not set it
+    pub fn get_default(&self, obj: StoreObj, key: &str) -> Result<String> {
+        match self.resolve_default(obj, key) {
+            Some((default, _)) => Ok(default),
+            None => bail!("Key not found"),
+        }
     }
 
-    pub fn update(&mut self, obj: StoreObj, key_val: (&str, &str)) -> Result<()> {
+    /// Drops the user value of the key and returns the default it falls back to
+    pub fn reset(&mut self, obj: StoreObj, key: &str, changed_by: &str) -> Result<String> {
+        let default = self.get_default(obj, key)?;
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        transaction.delete.insert(obj, vec![key.to_string()]);
+        self.commit(&transaction)?;
+        Ok(default)
+    }
+
+    pub fn get_batch<S: AsRef<str>>(&self, obj: StoreObj, keys: &[S]) -> Result<GetBatchResponse> {
         let tree = self.get_tree(obj)?;
-        let res = tree.insert(key_val.0, key_val.1)?;
-        if res.is_none() {
-            bail!("Response was none");
-        };
-        Ok(())
+        let mut response = GetBatchResponse::default();
+        for key in keys {
+            let key = key.as_ref();
+            match tree.get(key)? {
+                Some(val) => {
+                    let val = std::str::from_utf8(&val)?.to_string();
+                    response.keys_vals.insert(key.to_string(), val);
+                }
+                None => match self.resolve_default(obj, key) {
+                    Some((default, _)) => {
+                        response.keys_vals.insert(key.to_string(), default);
+                    }
+                    None => response.missing.push(key.to_string()),
+                },
+            }
+        }
+        Ok(response)
+    }
+
+    pub fn update(&mut self, obj: StoreObj, key_val: (&str, &str), changed_by: &str) -> Result<()> {
+        self.update_batch(obj, &HashMap::from([key_val]), changed_by)
     }
 
-    pub fn update_batch(&self, obj: StoreObj, keys_vals: &HashMap<&str, &str>) -> Result<()> {
+    pub fn update_batch(
+        &self,
+        obj: StoreObj,
+        keys_vals: &HashMap<&str, &str>,
+        changed_by: &str,
+    ) -> Result<()> {
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        transaction.update.insert(obj, to_owned_map(keys_vals));
+        self.commit(&transaction)
+    }
+
+    /// All keys and values of the user layer of the tree
+    pub fn entries(&self, obj: StoreObj) -> Result<BTreeMap<String, String>> {
         let tree = self.get_tree(obj)?;
-        let mut batch = Batch::default();
-        for (key, val) in keys_vals.into_iter() {
-            batch.insert(*key, *val);
+        let mut entries = BTreeMap::new();
+        for entry in tree.iter() {
+            let (key, val) = entry?;
+            entries.insert(
+                std::str::from_utf8(&key)?.to_string(),
+                std::str::from_utf8(&val)?.to_string(),
+            );
         }
-        tree.apply_batch(batch)?;
-        Ok(())
+        Ok(entries)
     }
 
-    pub fn delete(&self, obj: StoreObj, key: &str) -> Result<String> {
+    pub fn delete(&self, obj: StoreObj, key: &str, changed_by: &str) -> Result<String> {
         let tree = self.get_tree(obj)?;
-        let res = tree.remove(key)?;
+        let res = tree.get(key)?;
         if res.is_none() {
             bail!("Key not found");
         }
         let val = std::str::from_utf8(&res.unwrap())?.to_string();
 
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        transaction.delete.insert(obj, vec![key.to_string()]);
+        self.commit(&transaction)?;
         Ok(val)
     }
 
-    pub fn delete_batch(&self, obj: StoreObj, keys: &Vec<&str>) -> Result<()> {
-        let tree = self.get_tree(obj)?;
-        let mut batch = Batch::default();
-        for &key in keys {
-            let _ = batch.remove(key);
+    pub fn delete_batch(&self, obj: StoreObj, keys: &[&str], changed_by: &str) -> Result<()> {
+        let mut transaction = StoreTransaction::changed_by(changed_by);
+        transaction
+            .delete
+            .insert(obj, keys.iter().map(|k| k.to_string()).collect());
+        self.commit(&transaction)
+    }
+
+    /// Applies all writes of the transaction or none of them, even when they
+    /// span several trees, and journals every change in the history tree
+    pub fn commit(&self, transaction: &StoreTransaction) -> Result<()> {
+        for (obj, keys_vals) in transaction.insert.iter().chain(transaction.update.iter()) {
+            for (key, val) in keys_vals {
+                self.schema.validate(*obj, key, val)?;
+            }
+        }
+
+        let objs = transaction.objs();
+        let mut trees = objs
+            .iter()
+            .map(|obj| self.get_tree(*obj))
+            .collect::<Result<Vec<Tree>>>()?;
+        trees.push(self.history_tree()?);
+        let revisions = (0..transaction.len())
+            .map(|_| self.db.generate_id())
+            .collect::<sled::Result<Vec<u64>>>()?;
+
+        let res = trees.as_slice().transaction(|tx_trees| {
+            let (history, tx_trees) = tx_trees.split_last().unwrap();
+            let mut revisions = revisions.iter();
+            let mut journal = |obj: StoreObj, key: &str, old: Option<IVec>, new: Option<&str>| {
+                let revision = *revisions.next().unwrap();
+                match HistoryEntry::new(revision, &transaction.changed_by, old, new) {
+                    Some(entry) => match serde_json::to_vec(&entry) {
+                        Ok(entry) => Ok(Some((history_key(obj, key, revision), entry))),
+                        Err(e) => Err(e.to_string()),
+                    },
+                    None => Ok(None),
+                }
+            };
+
+            for (obj, tree) in objs.iter().zip(tx_trees.iter()) {
+                let mut entries = vec![];
+                if let Some(keys_vals) = transaction.insert.get(obj) {
+                    for (key, val) in keys_vals {
+                        let old = tree.insert(key.as_str(), val.as_str())?;
+                        entries.push(journal(*obj, key, old, Some(val)));
+                    }
+                }
+                if let Some(keys_vals) = transaction.update.get(obj) {
+                    for (key, val) in keys_vals {
+                        let old = tree.insert(key.as_str(), val.as_str())?;
+                        // keys without a user value may still have a default
+                        if old.is_none() && self.resolve_default(*obj, key).is_none() {
+                            return abort(format!("Key {} not found in {}", key, obj));
+                        }
+                        entries.push(journal(*obj, key, old, Some(val)));
+                    }
+                }
+                if let Some(keys) = transaction.delete.get(obj) {
+                    for key in keys {
+                        let old = tree.remove(key.as_str())?;
+                        entries.push(journal(*obj, key, old, None));
+                    }
+                }
+                for entry in entries {
+                    match entry {
+                        Ok(Some((history_key, entry))) => {
+                            history.insert(history_key, entry)?;
+                        }
+                        Ok(None) => (),
+                        Err(e) => return abort(e),
+                    }
+                }
+            }
+            Ok(())
+        });
+
+        match res {
+            Ok(()) => Ok(()),
+            Err(TransactionError::Abort(e)) => bail!(e),
+            Err(TransactionError::Storage(e)) => bail!(e),
         }
-        let _ = tree.apply_batch(batch)?;
-        Ok(())
     }
 
     pub fn key_exists(&self, obj: StoreObj, key: &str) -> Result<bool> {
-        let tree = self.get_tree(obj.clone())?;
+        let tree = self.get_tree(obj)?;
 
         let key_exists_res = tree.contains_key(key);
 
@@ -148,33 +475,173 @@ impl Store {
         Ok(true)
     }
 
-    pub async fn watch(&self, obj: StoreObj, keys: Vec<&str>, sender: mpsc::Sender<StoreEvent>) {
-        let tree = self.get_tree(obj).unwrap();
-        let mut subscriber = tree.watch_prefix(vec![]);
+    /// Forwards every write to the tree whose key starts with `prefix`,
+    /// including writes that did not go through the D-Bus interface. sled does
+    /// not tell inserts and updates apart, so both are sent as `Insert`
+    pub async fn watch(
+        &self,
+        obj: StoreObj,
+        prefix: &str,
+        sender: mpsc::Sender<(StoreObj, StoreEvent)>,
+    ) -> Result<()> {
+        let tree = self.get_tree(obj)?;
+        let mut subscriber = tree.watch_prefix(prefix);
         while let Some(event) = (&mut subscriber).await {
-            match event {
+            let event = match event {
                 sled::Event::Insert { key, value } => {
-                    println!("Inserted key: {:?}", key);
-                    let key_str = std::str::from_utf8(&key).unwrap().to_string();
-                    let val_str = std::str::from_utf8(&value).unwrap().to_string();
-                    // let _ = sender
-                    //     .send(StoreEvent::Insert {
-                    //         key: key_str,
-                    //         value: val_str,
-                    //     })
-                    //     .await;
+                    let key_str = std::str::from_utf8(&key)?.to_string();
+                    let val_str = std::str::from_utf8(&value)?.to_string();
+                    StoreEvent::Insert {
+                        keys_vals: HashMap::from([(key_str, val_str)]),
+                    }
                 }
                 sled::Event::Remove { key } => {
-                    println!("Inserted key: {:?}", key);
-                    let key_str = std::str::from_utf8(&key).unwrap().to_string();
-                    // let _ = sender
-                    //     .send(StoreEvent::Remove {
-                    //         key: key_str,
-                    //         value: "".to_string(),
-                    //     })
-                    //     .await;
+                    let key_str = std::str::from_utf8(&key)?.to_string();
+                    StoreEvent::Delete {
+                        keys_vals: HashMap::from([(key_str, "".to_string())]),
+                    }
                 }
+            };
+            if sender.send((obj, event)).await.is_err() {
+                break;
             }
         }
+        Ok(())
+    }
+}
+
+/// Seconds since the unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn to_owned_map(keys_vals: &HashMap<&str, &str>) -> HashMap<String, String> {
+    keys_vals
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn temporary_store() -> Store {
+        Store::from_db(Config::new().temporary(true).open().unwrap())
+    }
+
+    #[test]
+    fn get_batch_reports_missing_keys() {
+        let mut store = temporary_store();
+        store
+            .insert(StoreObj::Settings, ("k1", "v1"), "test")
+            .unwrap();
+
+        let res = store.get_batch(StoreObj::Settings, &["k1", "k2"]).unwrap();
+        assert_eq!(res.keys_vals.get("k1").unwrap(), "v1");
+        assert_eq!(res.missing, vec!["k2".to_string()]);
+    }
+
+    #[test]
+    fn commit_is_atomic_across_trees() {
+        let mut store = temporary_store();
+        store
+            .insert(StoreObj::Theme, ("accent", "blue"), "test")
+            .unwrap();
+
+        let mut transaction = StoreTransaction::changed_by("test");
+        transaction.insert.insert(
+            StoreObj::Settings,
+            HashMap::from([("k1".to_string(), "v1".to_string())]),
+        );
+        transaction.update.insert(
+            StoreObj::Theme,
+            HashMap::from([("missing".to_string(), "red".to_string())]),
+        );
+        assert!(store.commit(&transaction).is_err());
+        assert!(store.get(StoreObj::Settings, "k1").is_err());
+
+        transaction.update.insert(
+            StoreObj::Theme,
+            HashMap::from([("accent".to_string(), "red".to_string())]),
+        );
+        store.commit(&transaction).unwrap();
+        assert_eq!(store.get(StoreObj::Settings, "k1").unwrap(), "v1");
+        assert_eq!(store.get(StoreObj::Theme, "accent").unwrap(), "red");
+    }
+
+    #[test]
+    fn get_resolves_through_layers() {
+        let system = DefaultsLayer::new(HashMap::from([(
+            StoreObj::Theme,
+            HashMap::from([("mode".to_string(), "light".to_string())]),
+        )]));
+        let vendor = DefaultsLayer::new(HashMap::from([(
+            StoreObj::Theme,
+            HashMap::from([
+                ("mode".to_string(), "dark".to_string()),
+                ("accent".to_string(), "blue".to_string()),
+            ]),
+        )]));
+        let mut store = temporary_store().with_layers(system, vendor);
+
+        assert_eq!(
+            store.resolve(StoreObj::Theme, "mode").unwrap(),
+            Some(("light".to_string(), Layer::System))
+        );
+        assert_eq!(
+            store.resolve(StoreObj::Theme, "accent").unwrap(),
+            Some(("blue".to_string(), Layer::Vendor))
+        );
+
+        store
+            .insert(StoreObj::Theme, ("mode", "dark"), "test")
+            .unwrap();
+        assert_eq!(
+            store.resolve(StoreObj::Theme, "mode").unwrap(),
+            Some(("dark".to_string(), Layer::User))
+        );
+        assert_eq!(
+            store.reset(StoreObj::Theme, "mode", "test").unwrap(),
+            "light"
+        );
+        assert_eq!(store.get(StoreObj::Theme, "mode").unwrap(), "light");
+        assert!(store.entries(StoreObj::Theme).unwrap().is_empty());
+    }
+
+    #[test]
+    fn update_sets_key_defined_only_in_defaults() {
+        let vendor = DefaultsLayer::new(HashMap::from([(
+            StoreObj::Theme,
+            HashMap::from([("accent".to_string(), "blue".to_string())]),
+        )]));
+        let mut store = temporary_store().with_layers(DefaultsLayer::default(), vendor);
+
+        store
+            .update(StoreObj::Theme, ("accent", "red"), "test")
+            .unwrap();
+        assert_eq!(
+            store.resolve(StoreObj::Theme, "accent").unwrap(),
+            Some(("red".to_string(), Layer::User))
+        );
+        assert!(store
+            .update(StoreObj::Theme, ("missing", "red"), "test")
+            .is_err());
+    }
+
+    #[test]
+    fn open_reports_locked_database() {
+        let path = std::env::temp_dir().join(format!("mechanix-store-lock-{}", std::process::id()));
+        let store = Store::open(&path).unwrap();
+
+        let err = Store::open(&path).unwrap_err();
+        let err = err.downcast_ref::<StoreOpenError>().unwrap();
+        assert_eq!(err.code, StoreOpenErrorCodes::Locked);
+
+        drop(store);
+        let _ = fs::remove_dir_all(&path);
     }
 }
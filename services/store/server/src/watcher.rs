@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+use zbus::{fdo::DBusProxy, names::OwnedUniqueName, Connection};
+
+use mechanix_store_server::store::{Store, StoreEvent, StoreObj};
+
+#[derive(Debug, Clone)]
+struct Watch {
+    owner: OwnedUniqueName,
+    obj: StoreObj,
+    prefix: String,
+}
+
+/// # Watchers
+///
+/// Watches registered by clients over D-Bus, every watch belongs to the
+/// unique bus name that registered it and is dropped when that name leaves the bus
+#[derive(Debug, Default)]
+pub struct Watchers {
+    next_id: u32,
+    watches: HashMap<u32, Watch>,
+}
+
+impl Watchers {
+    pub fn add(&mut self, owner: OwnedUniqueName, obj: StoreObj, prefix: String) -> u32 {
+        self.next_id = self.next_id.wrapping_add(1);
+        self.watches
+            .insert(self.next_id, Watch { owner, obj, prefix });
+        self.next_id
+    }
+
+    /// Removes the watch if it belongs to `owner`
+    pub fn remove(&mut self, owner: &str, id: u32) -> bool {
+        match self.watches.get(&id) {
+            Some(watch) if watch.owner.as_str() == owner => {
+                self.watches.remove(&id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove_owner(&mut self, owner: &str) {
+        self.watches
+            .retain(|_, watch| watch.owner.as_str() != owner);
+    }
+
+    /// Splits the event into the keys each watch is interested in
+    fn matching(
+        &self,
+        obj: StoreObj,
+        event: &StoreEvent,
+    ) -> Vec<(u32, OwnedUniqueName, StoreEvent)> {
+        let keys_vals = match event {
+            StoreEvent::Insert { keys_vals }
+            | StoreEvent::Update { keys_vals }
+            | StoreEvent::Delete { keys_vals } => keys_vals,
+        };
+
+        let mut matches = vec![];
+        for (id, watch) in self.watches.iter() {
+            if watch.obj != obj {
+                continue;
+            }
+            let keys_vals: HashMap<String, String> = keys_vals
+                .iter()
+                .filter(|(key, _)| key.starts_with(&watch.prefix))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            if keys_vals.is_empty() {
+                continue;
+            }
+            let event = match event {
+                StoreEvent::Insert { .. } => StoreEvent::Insert { keys_vals },
+                StoreEvent::Update { .. } => StoreEvent::Update { keys_vals },
+                StoreEvent::Delete { .. } => StoreEvent::Delete { keys_vals },
+            };
+            matches.push((*id, watch.owner.clone(), event));
+        }
+        matches
+    }
+}
+
+/// Forwards the changes of every tree to the clients watching them, the
+/// `WatchEvent` signal is sent only to the owner of the watch
+pub async fn watch_event_forwarder(
+    store: Store,
+    watchers: Arc<Mutex<Watchers>>,
+    conn: Connection,
+) -> anyhow::Result<()> {
+    let (event_tx, mut event_rx) = mpsc::channel(128);
+
+    for obj in StoreObj::ALL {
+        let store = store.clone();
+        let event_tx = event_tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = store.watch(obj, "", event_tx).await {
+                println!("Error while watching {} {:?}", obj, e);
+            }
+        });
+    }
+
+    let owner_watchers = watchers.clone();
+    let dbus_proxy = DBusProxy::new(&conn).await?;
+    let mut name_owner_changed = dbus_proxy.receive_name_owner_changed().await?;
+    tokio::spawn(async move {
+        while let Some(signal) = name_owner_changed.next().await {
+            if let Ok(args) = signal.args() {
+                if args.new_owner().is_none() {
+                    owner_watchers.lock().unwrap().remove_owner(args.name());
+                }
+            }
+        }
+    });
+
+    while let Some((obj, event)) = event_rx.recv().await {
+        let matches = watchers.lock().unwrap().matching(obj, &event);
+        for (id, owner, event) in matches {
+            let _ = conn
+                .emit_signal(
+                    Some(owner),
+                    "/org/mechanix/store",
+                    "org.mechanix.store",
+                    "WatchEvent",
+                    &(id, obj.name(), event),
+                )
+                .await;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_filters_by_obj_and_prefix() {
+        let owner = OwnedUniqueName::try_from(":1.42").unwrap();
+        let mut watchers = Watchers::default();
+        let id = watchers.add(owner.clone(), StoreObj::Settings, "launcher.".to_string());
+        watchers.add(owner.clone(), StoreObj::Theme, "".to_string());
+
+        let event = StoreEvent::Insert {
+            keys_vals: HashMap::from([
+                ("launcher.clock".to_string(), "true".to_string()),
+                ("keyboard.layout".to_string(), "us".to_string()),
+            ]),
+        };
+        let matches = watchers.matching(StoreObj::Settings, &event);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, id);
+        assert_eq!(
+            matches[0].2,
+            StoreEvent::Insert {
+                keys_vals: HashMap::from([("launcher.clock".to_string(), "true".to_string())]),
+            }
+        );
+
+        assert!(!watchers.remove(":1.7", id));
+        watchers.remove_owner(":1.42");
+        assert!(watchers.matching(StoreObj::Theme, &event).is_empty());
+    }
+}
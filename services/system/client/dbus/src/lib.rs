@@ -11,24 +11,36 @@ pub mod wireless {
 
 pub mod bluetooth {
     use crate::proxies;
-    pub use proxies::bluetooth_proxy::{BluetoothService, NotificationStream};
+    pub use mechanix_system_dbus_server::system_interfaces::BluetoothPairingRequest;
+    pub use proxies::bluetooth_proxy::{
+        BluetoothService, NotificationStream, PairingCanceledStream, PairingRequestedStream,
+    };
 }
 
 pub mod host_metrics {
     use crate::proxies;
-    pub use mechanix_system_dbus_server::system_interfaces::MemoryInfoResponse;
-    pub use proxies::host_metrics::{HostMetrics, NotificationStream};
+    pub use mechanix_system_dbus_server::system_interfaces::{
+        DiskInfoResponse, HistorySampleResponse, MemoryInfoResponse, NetworkDataInfo,
+        NetworkInterfacesEvent, ProcessInfoResponse, ThermalZoneResponse,
+    };
+    pub use proxies::host_metrics::{
+        HostMetrics, NetworkInterfacesChangedStream, NotificationStream,
+    };
 }
 
 pub mod display {
     use crate::proxies;
+    pub use mechanix_system_dbus_server::system_interfaces::{
+        BacklightDeviceResponse, DisplayNotificationEvent,
+    };
     pub use proxies::display_proxy::{Display, NotificationStream};
 }
 
 pub mod hardware_buttons {
     use crate::proxies;
-    pub use mechanix_system_dbus_server::{Key, KeyEvent};
-    pub use proxies::hardware_button::{HwButton, NotificationStream};
+    pub use mechanix_system_dbus_server::system_interfaces::ButtonDeviceResponse;
+    pub use mechanix_system_dbus_server::{Gesture, GestureEvent, Key, KeyEvent};
+    pub use proxies::hardware_button::{GestureStream, HwButton, NotificationStream};
 }
 
 pub mod security {
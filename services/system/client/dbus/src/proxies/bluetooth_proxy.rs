@@ -1,4 +1,6 @@
-use mechanix_system_dbus_server::system_interfaces::BluetoothNotificationEvent;
+use mechanix_system_dbus_server::system_interfaces::{
+    BluetoothNotificationEvent, BluetoothPairingRequest,
+};
 use serde::{Deserialize, Serialize};
 use zbus::{proxy, zvariant::Type, Connection, Result};
 
@@ -12,8 +14,16 @@ trait Bluetooth {
     async fn is_connected(&self) -> Result<i8>;
     async fn enable(&self) -> Result<()>;
     async fn disable(&self) -> Result<()>;
+    async fn accept_pairing_request(&self, id: u32) -> Result<()>;
+    async fn reject_pairing_request(&self, id: u32) -> Result<()>;
+    async fn reply_pairing_passkey(&self, id: u32, passkey: u32) -> Result<()>;
+    async fn reply_pairing_pin_code(&self, id: u32, pin_code: String) -> Result<()>;
     #[zbus(signal)]
     async fn notification(&self, event: BluetoothNotificationEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn pairing_requested(&self, request: BluetoothPairingRequest) -> Result<()>;
+    #[zbus(signal)]
+    async fn pairing_canceled(&self, id: u32) -> Result<()>;
 }
 
 pub struct BluetoothService;
@@ -53,4 +63,47 @@ impl BluetoothService {
         let stream = proxy.receive_notification().await?;
         Ok(stream)
     }
+
+    pub async fn get_pairing_requested_stream() -> Result<PairingRequestedStream<'static>> {
+        let connection = Connection::system().await?;
+        let proxy = BluetoothProxy::new(&connection).await?;
+        let stream = proxy.receive_pairing_requested().await?;
+        Ok(stream)
+    }
+
+    pub async fn get_pairing_canceled_stream() -> Result<PairingCanceledStream<'static>> {
+        let connection = Connection::system().await?;
+        let proxy = BluetoothProxy::new(&connection).await?;
+        let stream = proxy.receive_pairing_canceled().await?;
+        Ok(stream)
+    }
+
+    /// Confirms the passkey or authorizes the device or service of request `id`
+    pub async fn accept_pairing_request(id: u32) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = BluetoothProxy::new(&connection).await?;
+        let reply = proxy.accept_pairing_request(id).await?;
+        Ok(reply)
+    }
+
+    pub async fn reject_pairing_request(id: u32) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = BluetoothProxy::new(&connection).await?;
+        let reply = proxy.reject_pairing_request(id).await?;
+        Ok(reply)
+    }
+
+    pub async fn reply_pairing_passkey(id: u32, passkey: u32) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = BluetoothProxy::new(&connection).await?;
+        let reply = proxy.reply_pairing_passkey(id, passkey).await?;
+        Ok(reply)
+    }
+
+    pub async fn reply_pairing_pin_code(id: u32, pin_code: String) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = BluetoothProxy::new(&connection).await?;
+        let reply = proxy.reply_pairing_pin_code(id, pin_code).await?;
+        Ok(reply)
+    }
 }
@@ -1,11 +1,8 @@
-use serde::{Deserialize, Serialize};
+use mechanix_system_dbus_server::system_interfaces::{
+    BacklightDeviceResponse, DisplayNotificationEvent as NotificationEvent,
+};
 use tracing::info;
-use zbus::{proxy, zvariant::Type, Connection, Result};
-
-#[derive(Deserialize, Serialize, Type, PartialEq, Debug)]
-pub struct NotificationEvent {
-    pub brightness_percentage: u8,
-}
+use zbus::{proxy, Connection, Result};
 
 #[proxy(
     interface = "org.mechanix.services.Display",
@@ -14,7 +11,15 @@ pub struct NotificationEvent {
 )]
 trait DisplayBusInterface {
     async fn get_brightness(&self) -> Result<u8>;
+    async fn get_actual_brightness(&self) -> Result<u8>;
     async fn set_brightness(&self, value: u8) -> Result<()>;
+    async fn set_brightness_animated(&self, target: u8, duration_ms: u32) -> Result<()>;
+    async fn dim(&self, target: u8, duration_ms: u32) -> Result<()>;
+    async fn list_devices(&self) -> Result<Vec<BacklightDeviceResponse>>;
+    async fn has_ambient_light_sensor(&self) -> Result<bool>;
+    async fn get_illuminance(&self) -> Result<f64>;
+    async fn get_auto_brightness(&self) -> Result<bool>;
+    async fn set_auto_brightness(&self, enabled: bool) -> Result<()>;
     // async fn get_screen_timeout(&self) -> Result<u32>;
     // async fn set_screen_timeout(&self, value: u32) ->Result<u32>;
     async fn set_backlight_on(&self) -> Result<()>;
@@ -34,6 +39,13 @@ impl Display {
         Ok(reply)
     }
 
+    pub async fn get_actual_brightness_percentage() -> Result<u8> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_actual_brightness().await?;
+        Ok(reply)
+    }
+
     pub async fn set_brightness_percentage(value: u8) -> Result<()> {
         let connection = Connection::system().await?;
         let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
@@ -41,6 +53,55 @@ impl Display {
         Ok(())
     }
 
+    pub async fn set_brightness_percentage_animated(value: u8, duration_ms: u32) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        proxy.set_brightness_animated(value, duration_ms).await?;
+        Ok(())
+    }
+
+    pub async fn dim(value: u8, duration_ms: u32) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        proxy.dim(value, duration_ms).await?;
+        Ok(())
+    }
+
+    pub async fn list_devices() -> Result<Vec<BacklightDeviceResponse>> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.list_devices().await?;
+        Ok(reply)
+    }
+
+    pub async fn has_ambient_light_sensor() -> Result<bool> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.has_ambient_light_sensor().await?;
+        Ok(reply)
+    }
+
+    pub async fn get_illuminance() -> Result<f64> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_illuminance().await?;
+        Ok(reply)
+    }
+
+    pub async fn get_auto_brightness() -> Result<bool> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_auto_brightness().await?;
+        Ok(reply)
+    }
+
+    pub async fn set_auto_brightness(enabled: bool) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
+        proxy.set_auto_brightness(enabled).await?;
+        Ok(())
+    }
+
     pub async fn set_backlight_on() -> Result<()> {
         let connection = Connection::system().await?;
         let proxy = DisplayBusInterfaceProxy::new(&connection).await?;
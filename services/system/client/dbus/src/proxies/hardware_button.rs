@@ -1,4 +1,6 @@
-use mechanix_system_dbus_server::KeyEvent;
+use mechanix_system_dbus_server::{
+    system_interfaces::ButtonDeviceResponse, GestureEvent, KeyEvent,
+};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 use zbus::{proxy, zvariant::Type, Connection, Result};
@@ -11,17 +13,39 @@ pub struct NotificationEvent {}
     default_service = "org.mechanix.services.HwButton"
 )]
 trait HwButtonInterface {
+    async fn get_status(&self) -> Result<Vec<ButtonDeviceResponse>>;
     #[zbus(signal)]
     async fn notification(&self, event: KeyEvent) -> Result<(), zbus::Error>;
+    #[zbus(signal)]
+    async fn gesture(&self, event: GestureEvent) -> Result<(), zbus::Error>;
 }
 
 pub struct HwButton;
 
 impl HwButton {
+    /// Button devices and whether they are present
+    pub async fn get_status() -> Result<Vec<ButtonDeviceResponse>> {
+        let connection = Connection::system().await?;
+        let proxy =
+            HwButtonInterfaceProxy::new(&connection, "/org/mechanix/services/HwButton").await?;
+        let reply = proxy.get_status().await?;
+        info!("Button devices: {:?}", reply);
+        Ok(reply)
+    }
+
     pub async fn get_notification_stream(path: String) -> Result<NotificationStream<'static>> {
         let connection = Connection::system().await?;
         let proxy = HwButtonInterfaceProxy::new(&connection, path).await?;
         let stream = proxy.receive_notification().await?;
         Ok(stream)
     }
+
+    /// Gestures of all the buttons, emitted on `/org/mechanix/services/HwButton`
+    pub async fn get_gesture_stream() -> Result<GestureStream<'static>> {
+        let connection = Connection::system().await?;
+        let proxy =
+            HwButtonInterfaceProxy::new(&connection, "/org/mechanix/services/HwButton").await?;
+        let stream = proxy.receive_gesture().await?;
+        Ok(stream)
+    }
 }
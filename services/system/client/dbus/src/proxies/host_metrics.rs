@@ -1,4 +1,8 @@
-use mechanix_system_dbus_server::system_interfaces::{HostMetricsNotificationEvent, MemoryInfoResponse};
+use mechanix_system_dbus_server::system_interfaces::{
+    DiskInfoResponse, HistorySampleResponse, HostMetricsNotificationEvent, MemoryInfoResponse,
+    NetworkDataInfo, NetworkInterfacesEvent, ProcessInfoResponse,
+    ThermalZoneResponse,
+};
 use serde::{Deserialize, Serialize};
 use zbus::{proxy, zvariant::Type, Connection, Result};
 
@@ -9,9 +13,25 @@ use zbus::{proxy, zvariant::Type, Connection, Result};
 )]
 trait HostMetricsBusInterface {
     async fn get_cpu_usage(&self) -> Result<f32>;
+    async fn get_cpu_core_usage(&self) -> Result<Vec<f32>>;
     async fn get_memory_info(&self) -> Result<MemoryInfoResponse>;
+    async fn get_disks(&self) -> Result<Vec<DiskInfoResponse>>;
+    async fn get_thermal_zones(&self) -> Result<Vec<ThermalZoneResponse>>;
+    async fn get_battery_temperature(&self) -> Result<f32>;
+    async fn get_top_processes(&self, count: u32, sort_by: &str)
+        -> Result<Vec<ProcessInfoResponse>>;
+    async fn get_history(
+        &self,
+        metric: &str,
+        since: u64,
+        resolution: u64,
+    ) -> Result<Vec<HistorySampleResponse>>;
+    async fn get_network_usage(&self) -> Result<Vec<NetworkDataInfo>>;
+    async fn reset_network_mark(&self, interface_name: &str) -> Result<()>;
     #[zbus(signal)]
     async fn notification(&self, event: HostMetricsNotificationEvent) -> Result<()>;
+    #[zbus(signal)]
+    async fn network_interfaces_changed(&self, event: NetworkInterfacesEvent) -> Result<()>;
 }
 
 pub struct HostMetrics;
@@ -31,6 +51,80 @@ impl HostMetrics {
         Ok(reply)
     }
 
+    pub async fn get_cpu_core_usage() -> Result<Vec<f32>> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_cpu_core_usage().await?;
+        Ok(reply)
+    }
+
+    pub async fn get_disks() -> Result<Vec<DiskInfoResponse>> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_disks().await?;
+        Ok(reply)
+    }
+
+    pub async fn get_thermal_zones() -> Result<Vec<ThermalZoneResponse>> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_thermal_zones().await?;
+        Ok(reply)
+    }
+
+    pub async fn get_battery_temperature() -> Result<f32> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_battery_temperature().await?;
+        Ok(reply)
+    }
+
+    /// `sort_by` is `cpu` or `memory`
+    pub async fn get_top_processes(count: u32, sort_by: &str) -> Result<Vec<ProcessInfoResponse>> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_top_processes(count, sort_by).await?;
+        Ok(reply)
+    }
+
+    /// `metric` is one of `cpu`, `memory`, `network_received`,
+    /// `network_transmitted` or `battery`, samples from the unix timestamp
+    /// `since` are averaged over `resolution` seconds
+    pub async fn get_history(
+        metric: &str,
+        since: u64,
+        resolution: u64,
+    ) -> Result<Vec<HistorySampleResponse>> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_history(metric, since, resolution).await?;
+        Ok(reply)
+    }
+
+    pub async fn get_network_usage() -> Result<Vec<NetworkDataInfo>> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let reply = proxy.get_network_usage().await?;
+        Ok(reply)
+    }
+
+    /// Starts counting the bytes since now, for all interfaces when
+    /// `interface_name` is empty
+    pub async fn reset_network_mark(interface_name: &str) -> Result<()> {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        proxy.reset_network_mark(interface_name).await?;
+        Ok(())
+    }
+
+    pub async fn get_network_interfaces_stream() -> Result<NetworkInterfacesChangedStream<'static>>
+    {
+        let connection = Connection::system().await?;
+        let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
+        let stream = proxy.receive_network_interfaces_changed().await?;
+        Ok(stream)
+    }
+
     pub async fn get_notification_stream() -> Result<NotificationStream<'static>> {
         let connection = Connection::system().await?;
         let proxy = HostMetricsBusInterfaceProxy::new(&connection).await?;
@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
+use mechanix_bluetooth_ctl::PAIRING_TIMEOUT;
 use serde::{Deserialize, Serialize};
-use std::{env, fs::File, path::PathBuf};
+use std::{collections::HashMap, env, fs::File, path::PathBuf};
 use tracing::{debug, info};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,14 +12,77 @@ pub struct BaseConfig {
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Interfaces {
     pub network: Network,
+    #[serde(default)]
     pub display: Display,
+    #[serde(default)]
+    pub host_metrics: HostMetrics,
+    #[serde(default)]
     pub hw_buttons: HwButtons,
+    #[serde(default)]
+    pub bluetooth: Bluetooth,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Bluetooth {
+    /// Pairing requests the user does not answer in time are canceled, at
+    /// most 20 seconds since BlueZ gives up on the agent after 25
+    pub pairing_timeout_secs: u64,
+}
+
+impl Default for Bluetooth {
+    fn default() -> Self {
+        Self {
+            pairing_timeout_secs: PAIRING_TIMEOUT.as_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct HwButtons {
     pub power: Power,
     pub home: Home,
+    /// More input devices to listen on, by path
+    pub devices: Vec<String>,
+    /// Also listen on any input device with a key of the key map
+    pub discover: bool,
+    /// evdev key name (`KEY_VOLUMEUP`) or code to action, the built-in map
+    /// is used when not set
+    pub keymap: Option<HashMap<String, String>>,
+    pub gestures: Gestures,
+}
+
+impl Default for HwButtons {
+    fn default() -> Self {
+        Self {
+            power: Power::default(),
+            home: Home::default(),
+            devices: vec![],
+            discover: true,
+            keymap: None,
+            gestures: Gestures::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Gestures {
+    pub long_press_ms: u64,
+    pub double_press_ms: u64,
+    /// Actions held together that form a chord
+    pub chords: Vec<Vec<String>>,
+}
+
+impl Default for Gestures {
+    fn default() -> Self {
+        Self {
+            long_press_ms: 800,
+            double_press_ms: 300,
+            chords: vec![vec!["power".to_string(), "volume_down".to_string()]],
+        }
+    }
 }
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Power {
@@ -32,7 +96,90 @@ pub struct Home {
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Display {
-    pub device: String,
+    /// Backlight device directory or its `brightness` file, the default
+    /// device of the system is used when not set
+    #[serde(default)]
+    pub device: Option<String>,
+    #[serde(default)]
+    pub auto_brightness: AutoBrightness,
+}
+
+/// Brightness from the ambient light sensor, `curve` maps illuminance in lux
+/// to brightness in percent
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AutoBrightness {
+    pub enabled: bool,
+    pub curve: Vec<CurvePoint>,
+    pub hysteresis: f64,
+    pub interval_ms: u64,
+    pub duration_ms: u32,
+}
+
+impl Default for AutoBrightness {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            curve: vec![
+                CurvePoint {
+                    lux: 0.,
+                    brightness: 10,
+                },
+                CurvePoint {
+                    lux: 10.,
+                    brightness: 20,
+                },
+                CurvePoint {
+                    lux: 100.,
+                    brightness: 40,
+                },
+                CurvePoint {
+                    lux: 1000.,
+                    brightness: 70,
+                },
+                CurvePoint {
+                    lux: 10000.,
+                    brightness: 100,
+                },
+            ],
+            hysteresis: 0.2,
+            interval_ms: 1000,
+            duration_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct CurvePoint {
+    pub lux: f64,
+    pub brightness: u8,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct HostMetrics {
+    #[serde(default)]
+    pub history: History,
+}
+
+/// In-memory history of the metrics, persisted to `path` when set
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct History {
+    pub interval_secs: u64,
+    pub capacity: usize,
+    pub path: Option<String>,
+    pub persist_interval_secs: u64,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            interval_secs: 10,
+            capacity: 8640,
+            path: None,
+            persist_interval_secs: 300,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Default)]
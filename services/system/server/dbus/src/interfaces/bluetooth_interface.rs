@@ -1,16 +1,31 @@
+use std::collections::HashMap;
 use zbus::{
     fdo::Error as ZbusError,
     interface,
-    zvariant::{DeserializeDict, SerializeDict, Type},
-    SignalContext,
+    message::Header,
+    zvariant::{DeserializeDict, SerializeDict, Type, Value},
+    Connection, SignalContext,
 };
 
+use policykit::{
+    authority::AuthorityProxy,
+    types::{Subject, CHECK_AUTHORIZATION_ALLOW_USER_INTERACTION},
+};
 use tokio::time::{self, Duration};
 
-use mechanix_bluetooth_ctl::Bluetooth;
+use mechanix_bluetooth_ctl::{
+    Bluetooth, PairingAgent, PairingEvent, PairingReply, PairingRequest, PairingRequestKind,
+};
+use tokio::sync::mpsc;
+
+/// polkit action needed to answer pairing requests, defined in
+/// `org.mechanix.services.bluetooth.policy`
+const POLKIT_PAIR_ACTION: &str = "org.mechanix.services.bluetooth.pair";
 
-#[derive(Clone, Copy)]
-pub struct BluetoothBusInterface {}
+#[derive(Clone)]
+pub struct BluetoothBusInterface {
+    pub pairing_agent: PairingAgent,
+}
 
 #[derive(DeserializeDict, SerializeDict, Type)]
 // `Type` treats `ScanResultResponse` is an alias for `a{sv}`.
@@ -71,6 +86,67 @@ pub struct BluetoothNotificationEvent {
     pub is_enabled: bool,
 }
 
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `BluetoothPairingRequest` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct BluetoothPairingRequest {
+    pub id: u32,
+    pub address: String,
+    /// `request_pin_code`, `display_pin_code`, `request_passkey`,
+    /// `display_passkey`, `request_confirmation`, `request_authorization` or
+    /// `authorize_service`
+    pub kind: String,
+    /// Shown by `display_pin_code`, empty otherwise
+    pub pin_code: String,
+    /// Shown by `display_passkey` and `request_confirmation`, 0 otherwise
+    pub passkey: u32,
+    /// Digits of the passkey typed on the device so far
+    pub entered: u16,
+    /// UUID of the service of `authorize_service`, empty otherwise
+    pub service: String,
+    /// `display_*` requests are only shown and take no reply
+    pub needs_reply: bool,
+}
+
+impl From<&PairingRequest> for BluetoothPairingRequest {
+    fn from(request: &PairingRequest) -> Self {
+        let mut response = BluetoothPairingRequest {
+            id: request.id,
+            address: request.device.clone(),
+            kind: String::new(),
+            pin_code: String::new(),
+            passkey: 0,
+            entered: 0,
+            service: String::new(),
+            needs_reply: request.kind.needs_reply(),
+        };
+        response.kind = match &request.kind {
+            PairingRequestKind::RequestPinCode => "request_pin_code",
+            PairingRequestKind::DisplayPinCode(pin_code) => {
+                response.pin_code = pin_code.clone();
+                "display_pin_code"
+            }
+            PairingRequestKind::RequestPasskey => "request_passkey",
+            PairingRequestKind::DisplayPasskey { passkey, entered } => {
+                response.passkey = *passkey;
+                response.entered = *entered;
+                "display_passkey"
+            }
+            PairingRequestKind::RequestConfirmation(passkey) => {
+                response.passkey = *passkey;
+                "request_confirmation"
+            }
+            PairingRequestKind::RequestAuthorization => "request_authorization",
+            PairingRequestKind::AuthorizeService(service) => {
+                response.service = service.clone();
+                "authorize_service"
+            }
+        }
+        .to_string();
+        response
+    }
+}
+
 #[interface(name = "org.mechanix.services.Bluetooth")]
 impl BluetoothBusInterface {
     pub async fn status(&self) -> Result<i8, ZbusError> {
@@ -183,6 +259,61 @@ impl BluetoothBusInterface {
         event: BluetoothNotificationEvent,
     ) -> Result<(), zbus::Error>;
 
+    // BlueZ asks the pairing agent, answered with the reply methods below
+    #[zbus(signal)]
+    async fn pairing_requested(
+        &self,
+        ctxt: &SignalContext<'_>,
+        request: BluetoothPairingRequest,
+    ) -> Result<(), zbus::Error>;
+
+    // the request timed out or BlueZ canceled it
+    #[zbus(signal)]
+    async fn pairing_canceled(&self, ctxt: &SignalContext<'_>, id: u32) -> Result<(), zbus::Error>;
+
+    // `request_confirmation`, `request_authorization` and `authorize_service`
+    pub async fn accept_pairing_request(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        id: u32,
+    ) -> Result<(), ZbusError> {
+        authorize_pairing(&hdr, conn).await?;
+        self.reply_pairing_request(id, PairingReply::Accept)
+    }
+
+    pub async fn reject_pairing_request(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        id: u32,
+    ) -> Result<(), ZbusError> {
+        authorize_pairing(&hdr, conn).await?;
+        self.reply_pairing_request(id, PairingReply::Reject)
+    }
+
+    pub async fn reply_pairing_passkey(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        id: u32,
+        passkey: u32,
+    ) -> Result<(), ZbusError> {
+        authorize_pairing(&hdr, conn).await?;
+        self.reply_pairing_request(id, PairingReply::Passkey(passkey))
+    }
+
+    pub async fn reply_pairing_pin_code(
+        &self,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(connection)] conn: &Connection,
+        id: u32,
+        pin_code: String,
+    ) -> Result<(), ZbusError> {
+        authorize_pairing(&hdr, conn).await?;
+        self.reply_pairing_request(id, PairingReply::PinCode(pin_code))
+    }
+
     pub async fn get_bluetooth_properties(
         &self,
     ) -> Result<BluetoothAdapterInfoListResponse, ZbusError> {
@@ -220,6 +351,70 @@ impl BluetoothBusInterface {
     }
 }
 
+impl BluetoothBusInterface {
+    fn reply_pairing_request(&self, id: u32, reply: PairingReply) -> Result<(), ZbusError> {
+        match self.pairing_agent.reply(id, reply) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ZbusError::InvalidArgs(e.to_string())),
+        }
+    }
+}
+
+/// Only callers that polkit grants `POLKIT_PAIR_ACTION` may answer pairing
+/// requests, the caller is identified by its unique bus name so that it
+/// cannot be confused with another process
+async fn authorize_pairing(hdr: &Header<'_>, conn: &Connection) -> Result<(), ZbusError> {
+    let sender = match hdr.sender() {
+        Some(sender) => sender,
+        None => return Err(ZbusError::AccessDenied("Unknown caller".to_string())),
+    };
+    let subject = Subject {
+        subject_kind: "system-bus-name",
+        subject_details: HashMap::from([("name", Value::from(sender.as_str()))]),
+    };
+
+    let authority = AuthorityProxy::new(conn)
+        .await
+        .map_err(|e| ZbusError::Failed(e.to_string()))?;
+    let result = authority
+        .check_authorization(
+            subject,
+            POLKIT_PAIR_ACTION,
+            HashMap::new(),
+            CHECK_AUTHORIZATION_ALLOW_USER_INTERACTION,
+            "",
+        )
+        .await
+        .map_err(|e| ZbusError::Failed(e.to_string()))?;
+
+    if !result.is_authorized {
+        return Err(ZbusError::AccessDenied(
+            "Not authorized to answer pairing requests".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn bluetooth_pairing_stream(
+    bluetooth_bus: &BluetoothBusInterface,
+    conn: &zbus::Connection,
+    mut events: mpsc::UnboundedReceiver<PairingEvent>,
+) -> Result<(), ZbusError> {
+    let ctxt = SignalContext::new(conn, "/org/mechanix/services/Bluetooth")?;
+    while let Some(event) = events.recv().await {
+        match event {
+            PairingEvent::Request(request) => {
+                bluetooth_bus
+                    .pairing_requested(&ctxt, BluetoothPairingRequest::from(&request))
+                    .await?
+            }
+            PairingEvent::Canceled { id } => bluetooth_bus.pairing_canceled(&ctxt, id).await?,
+        }
+    }
+
+    Err(ZbusError::Failed("Pairing agent stopped".to_string()))
+}
+
 pub async fn bluetooth_event_notification_stream(
     bluetooth_bus: &BluetoothBusInterface,
     conn: &zbus::Connection,
@@ -1,15 +1,150 @@
-use mechanix_display_ctl::Display;
-use zbus::{fdo::Error as ZbusError, interface};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
+use mechanix_display_ctl::{
+    AmbientLightSensor, AutoBrightness, BrightnessCurve, BrightnessWatcher, Display,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{task::JoinHandle, time};
+use zbus::{
+    fdo::Error as ZbusError,
+    interface,
+    zvariant::{DeserializeDict, SerializeDict, Type},
+    SignalContext,
+};
+
+#[derive(Clone)]
 pub struct DisplayBusInterface {
+    /// Backlight device from the config, the default device of the system
+    /// is discovered when not set
+    pub path: Option<String>,
+    auto_brightness: AutoBrightnessSettings,
+    state: Arc<Mutex<DisplayState>>,
+}
+
+/// How the brightness follows the ambient light sensor
+#[derive(Debug, Clone)]
+pub struct AutoBrightnessSettings {
+    /// Follows the sensor from startup
+    pub enabled: bool,
+    pub curve: BrightnessCurve,
+    /// Fraction of the illuminance the reading must move by to change the
+    /// brightness
+    pub hysteresis: f64,
+    /// Interval between two readings of the sensor
+    pub interval: Duration,
+    /// Duration of the brightness change that follows a reading
+    pub duration_ms: u32,
+}
+
+impl Default for AutoBrightnessSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            curve: BrightnessCurve::default(),
+            hysteresis: 0.2,
+            interval: Duration::from_secs(1),
+            duration_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DisplayState {
+    /// Brightness last set by a client, restored when the backlight comes
+    /// back on after being dimmed or turned off
+    user_brightness: Option<u8>,
+    /// Running animated change, a newer change cancels it
+    animation: Option<JoinHandle<()>>,
+    /// Brightness follows the ambient light sensor
+    auto_brightness: bool,
+    /// Dimmed or turned off by the idle handler, the sensor is not followed
+    /// until the backlight is back on
+    idle: bool,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `BacklightDeviceResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct BacklightDeviceResponse {
+    pub name: String,
     pub path: String,
+    pub backlight_type: String,
+    pub max_brightness: u32,
+    pub in_use: bool,
+}
+
+#[derive(Deserialize, Serialize, Type, Debug, Clone, PartialEq)]
+pub struct DisplayNotificationEvent {
+    pub brightness_percentage: u8,
+}
+
+impl DisplayBusInterface {
+    pub fn new(path: Option<String>, auto_brightness: AutoBrightnessSettings) -> Self {
+        let has_sensor = matches!(AmbientLightSensor::discover(), Ok(Some(_)));
+        let display_bus = DisplayBusInterface {
+            path,
+            state: Arc::new(Mutex::new(DisplayState {
+                auto_brightness: auto_brightness.enabled && has_sensor,
+                ..Default::default()
+            })),
+            auto_brightness,
+        };
+        // the level at startup is the one to come back to until a client
+        // sets another one
+        if let Ok(brightness) = display_bus.display().and_then(|display| {
+            display
+                .get_brightness()
+                .map_err(|e| ZbusError::Failed(e.to_string()))
+        }) {
+            if brightness > 0 {
+                display_bus.state.lock().unwrap().user_brightness = Some(brightness);
+            }
+        }
+        display_bus
+    }
+
+    fn cancel_animation(&self) {
+        if let Some(animation) = self.state.lock().unwrap().animation.take() {
+            animation.abort();
+        }
+    }
+
+    fn animate(&self, display: Display, brightness: u8, duration_ms: u32) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(animation) = state.animation.take() {
+            animation.abort();
+        }
+        state.animation = Some(tokio::spawn(async move {
+            let duration = Duration::from_millis(duration_ms as u64);
+            if let Err(e) = display.set_brightness_animated(brightness, duration).await {
+                println!("Error while animating brightness: {}", e);
+            }
+        }));
+    }
+
+    fn display(&self) -> Result<Display, ZbusError> {
+        let display = match &self.path {
+            Some(path) => Display::new(path).map_err(|e| e.to_string()),
+            None => Display::discover().map_err(|e| e.to_string()),
+        };
+        match display {
+            Ok(display) => Ok(display),
+            Err(e) => Err(ZbusError::Failed(format!(
+                "Failed to open backlight device: {}",
+                e
+            ))),
+        }
+    }
 }
 
 #[interface(name = "org.mechanix.services.Display")]
 impl DisplayBusInterface {
+    /// Brightness in percent of the maximum of the device
     pub async fn get_brightness(&self) -> Result<u8, ZbusError> {
-        let display_path = self.path.as_str();
-        let display = Display::new(display_path).unwrap();
+        let display = self.display()?;
         let brightness = match display.get_brightness() {
             Ok(brightness) => brightness,
             Err(_) => return Err(ZbusError::Failed("Failed to get brightness".to_string())),
@@ -17,31 +152,123 @@ impl DisplayBusInterface {
         Ok(brightness)
     }
 
+    /// Brightness the hardware reports in percent of the maximum
+    pub async fn get_actual_brightness(&self) -> Result<u8, ZbusError> {
+        let display = self.display()?;
+        let brightness = match display.get_actual_brightness() {
+            Ok(brightness) => brightness,
+            Err(_) => {
+                return Err(ZbusError::Failed(
+                    "Failed to get actual brightness".to_string(),
+                ))
+            }
+        };
+        Ok(brightness)
+    }
+
+    /// Sets the brightness in percent of the maximum of the device
     pub async fn set_brightness(&self, brightness: u8) -> Result<(), ZbusError> {
-        let display_path = self.path.as_str();
-        let display = Display::new(display_path).unwrap();
+        let display = self.display()?;
+        self.cancel_animation();
         let _ = match display.set_brightness(brightness) {
             Ok(brightness) => brightness,
             Err(_) => return Err(ZbusError::Failed("Failed to set brightness".to_string())),
         };
+        let mut state = self.state.lock().unwrap();
+        state.user_brightness = Some(brightness);
+        state.idle = false;
+
+        Ok(())
+    }
+
+    /// Moves the brightness to `target` percent over `duration_ms`, returns
+    /// once the change has started
+    pub async fn set_brightness_animated(
+        &self,
+        target: u8,
+        duration_ms: u32,
+    ) -> Result<(), ZbusError> {
+        if target > 100 {
+            return Err(ZbusError::InvalidArgs(
+                "Brightness must be between 0 and 100".to_string(),
+            ));
+        }
+        let display = self.display()?;
+        {
+            let mut state = self.state.lock().unwrap();
+            state.user_brightness = Some(target);
+            state.idle = false;
+        }
+        self.animate(display, target, duration_ms);
+
+        Ok(())
+    }
+
+    /// Lowers the brightness to `target` percent over `duration_ms` without
+    /// forgetting the level set by the user, `set_backlight_on` restores it
+    pub async fn dim(&self, target: u8, duration_ms: u32) -> Result<(), ZbusError> {
+        if target > 100 {
+            return Err(ZbusError::InvalidArgs(
+                "Brightness must be between 0 and 100".to_string(),
+            ));
+        }
+        let display = self.display()?;
+        let brightness = match display.get_brightness() {
+            Ok(brightness) => brightness,
+            Err(_) => return Err(ZbusError::Failed("Failed to get brightness".to_string())),
+        };
+        self.state.lock().unwrap().idle = true;
+        if brightness <= target {
+            return Ok(());
+        }
+        self.animate(display, target, duration_ms);
 
         Ok(())
     }
 
+    pub async fn list_devices(&self) -> Result<Vec<BacklightDeviceResponse>, ZbusError> {
+        let in_use = self.display().ok().map(|display| display.device.path);
+        let devices = match Display::list_devices() {
+            Ok(devices) => devices,
+            Err(_) => {
+                return Err(ZbusError::Failed(
+                    "Failed to list backlight devices".to_string(),
+                ))
+            }
+        };
+
+        let devices = devices
+            .into_iter()
+            .map(|device| BacklightDeviceResponse {
+                in_use: in_use.as_ref() == Some(&device.path),
+                name: device.name,
+                path: device.path.to_string_lossy().to_string(),
+                backlight_type: device.backlight_type.to_string(),
+                max_brightness: device.max_brightness,
+            })
+            .collect();
+        Ok(devices)
+    }
+
+    /// Restores the brightness last set by a client, full brightness when
+    /// no client set one yet
     pub async fn set_backlight_on(&self) -> Result<(), ZbusError> {
-        let display_path = self.path.as_str();
-        let display = Display::new(display_path).unwrap();
-        let _ = match display.set_backlight_on() {
+        let display = self.display()?;
+        self.cancel_animation();
+        let brightness = self.state.lock().unwrap().user_brightness.unwrap_or(100);
+        let _ = match display.set_brightness(brightness) {
             Ok(brightness) => brightness,
             Err(_) => return Err(ZbusError::Failed("Failed to set backlight on".to_string())),
         };
+        self.state.lock().unwrap().idle = false;
 
         Ok(())
     }
 
     pub async fn set_backlight_off(&self) -> Result<(), ZbusError> {
-        let display_path = self.path.as_str();
-        let display = Display::new(display_path).unwrap();
+        let display = self.display()?;
+        self.cancel_animation();
+        self.state.lock().unwrap().idle = true;
         let _ = match display.set_backlight_off() {
             Ok(brightness) => brightness,
             Err(_) => return Err(ZbusError::Failed("Failed to set backlight off".to_string())),
@@ -49,4 +276,129 @@ impl DisplayBusInterface {
 
         Ok(())
     }
+
+    pub async fn has_ambient_light_sensor(&self) -> Result<bool, ZbusError> {
+        Ok(matches!(AmbientLightSensor::discover(), Ok(Some(_))))
+    }
+
+    /// Illuminance in lux read from the ambient light sensor
+    pub async fn get_illuminance(&self) -> Result<f64, ZbusError> {
+        let sensor = match AmbientLightSensor::discover() {
+            Ok(Some(sensor)) => sensor,
+            _ => {
+                return Err(ZbusError::Failed(
+                    "No ambient light sensor found".to_string(),
+                ))
+            }
+        };
+        match sensor.read_lux() {
+            Ok(lux) => Ok(lux),
+            Err(_) => Err(ZbusError::Failed("Failed to read illuminance".to_string())),
+        }
+    }
+
+    pub async fn get_auto_brightness(&self) -> Result<bool, ZbusError> {
+        Ok(self.state.lock().unwrap().auto_brightness)
+    }
+
+    /// Follows the ambient light sensor, brightness set by a client in the
+    /// meantime holds until the illuminance changes
+    pub async fn set_auto_brightness(&self, enabled: bool) -> Result<(), ZbusError> {
+        if enabled && !matches!(AmbientLightSensor::discover(), Ok(Some(_))) {
+            return Err(ZbusError::Failed(
+                "No ambient light sensor found".to_string(),
+            ));
+        }
+        self.state.lock().unwrap().auto_brightness = enabled;
+
+        Ok(())
+    }
+
+    #[zbus(signal)]
+    pub async fn notification(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: DisplayNotificationEvent,
+    ) -> Result<(), zbus::Error>;
+}
+
+/// Emits `notification` whenever the brightness of the display changes,
+/// whether a client, another process or a brightness key changed it
+pub async fn display_event_notification_stream(
+    display_bus: &DisplayBusInterface,
+    conn: &zbus::Connection,
+) -> Result<(), ZbusError> {
+    let display = display_bus.display()?;
+    let mut watcher = match BrightnessWatcher::new(display) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            return Err(ZbusError::Failed(format!(
+                "Failed to watch brightness: {}",
+                e
+            )))
+        }
+    };
+    let ctxt = SignalContext::new(conn, "/org/mechanix/services/Display")?;
+
+    loop {
+        let brightness_percentage = match watcher.next().await {
+            Ok(brightness_percentage) => brightness_percentage,
+            Err(e) => return Err(ZbusError::Failed(format!("Brightness watch ended: {}", e))),
+        };
+
+        display_bus
+            .notification(
+                &ctxt,
+                DisplayNotificationEvent {
+                    brightness_percentage,
+                },
+            )
+            .await?;
+    }
+}
+
+/// Sets the brightness from the ambient light sensor while auto brightness
+/// is enabled and the display is not idle, returns when there is no sensor
+pub async fn display_auto_brightness_stream(
+    display_bus: &DisplayBusInterface,
+) -> Result<(), ZbusError> {
+    let sensor = match AmbientLightSensor::discover() {
+        Ok(Some(sensor)) => sensor,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            return Err(ZbusError::Failed(format!(
+                "Failed to find an ambient light sensor: {}",
+                e
+            )))
+        }
+    };
+    let settings = display_bus.auto_brightness.clone();
+    let mut auto_brightness = AutoBrightness::new(settings.curve, settings.hysteresis);
+    let mut interval = time::interval(settings.interval);
+
+    loop {
+        interval.tick().await;
+
+        let (enabled, idle) = {
+            let state = display_bus.state.lock().unwrap();
+            (state.auto_brightness, state.idle)
+        };
+        if !enabled || idle {
+            // apply the first reading once enabled or back on
+            auto_brightness.reset();
+            continue;
+        }
+
+        let lux = match sensor.read_lux() {
+            Ok(lux) => lux,
+            Err(e) => {
+                println!("Error while reading illuminance: {}", e);
+                continue;
+            }
+        };
+        if let Some(brightness) = auto_brightness.update(lux) {
+            let display = display_bus.display()?;
+            display_bus.animate(display, brightness, settings.duration_ms);
+        }
+    }
 }
@@ -1,4 +1,3 @@
-use serde::{Deserialize, Serialize};
 use zbus::{
     fdo::Error as ZbusError,
     interface,
@@ -6,55 +5,154 @@ use zbus::{
     SignalContext,
 };
 
-use tokio::time::{self, Duration};
+use anyhow::Result;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tokio::time::{self, Instant};
+use tracing::{debug, info};
 
-use mechanix_hw_buttons::{HwButton, Key, KeyEvent};
+use mechanix_hw_buttons::{
+    ButtonDeviceStatus, ButtonDevices, ButtonDevicesEvent, ButtonEvent, GestureDetector,
+    GestureEvent, GestureSettings, Key, KeyEvent, KeyMap,
+};
 
-#[derive(Clone, Copy)]
-pub struct HwButtonInterface {}
+/// Shares the devices the notification stream listens on with `get_status`
+#[derive(Clone, Default)]
+pub struct HwButtonInterface {
+    status: Arc<Mutex<Vec<ButtonDeviceStatus>>>,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `ButtonDeviceResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct ButtonDeviceResponse {
+    pub path: String,
+    pub name: String,
+    pub available: bool,
+    pub actions: Vec<String>,
+}
+
+impl HwButtonInterface {
+    fn with_status<T>(&self, f: impl FnOnce(&mut Vec<ButtonDeviceStatus>) -> T) -> T {
+        let mut status = self
+            .status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut status)
+    }
+}
 
 #[interface(name = "org.mechanix.services.HwButton")]
 impl HwButtonInterface {
+    // configured devices, present or not, and the discovered ones
+    pub async fn get_status(&self) -> Result<Vec<ButtonDeviceResponse>, ZbusError> {
+        let status = self.with_status(|status| status.clone());
+        Ok(status
+            .into_iter()
+            .map(|device| ButtonDeviceResponse {
+                path: device.path.to_string_lossy().to_string(),
+                name: device.name,
+                available: device.available,
+                actions: device.actions,
+            })
+            .collect())
+    }
+
     #[zbus(signal)]
     async fn notification(
         &self,
         ctxt: &SignalContext<'_>,
         event: KeyEvent,
     ) -> Result<(), zbus::Error>;
+
+    // short, long and double presses and chords of the mapped keys
+    #[zbus(signal)]
+    async fn gesture(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: GestureEvent,
+    ) -> Result<(), zbus::Error>;
 }
 
 pub async fn hw_buttons_notification_stream(
     hw_button_bus: &HwButtonInterface,
     conn: &zbus::Connection,
-    power_button_path: String,
-    home_button_path: String,
+    device_paths: Vec<PathBuf>,
+    discover: bool,
+    keymap: KeyMap,
+    gesture_settings: GestureSettings,
 ) -> Result<(), ZbusError> {
-    let mut power_button = HwButton::new(power_button_path);
-    let mut home_button = HwButton::new(home_button_path);
+    let mut devices = ButtonDevices::new(keymap, device_paths, discover);
+    hw_button_bus.with_status(|status| *status = devices.status());
+    // one detector for all devices so chords can span them
+    let mut detector = GestureDetector::new(gesture_settings);
+
+    let power_ctxt = SignalContext::new(conn, "/org/mechanix/services/HwButton/Power")?;
+    let home_ctxt = SignalContext::new(conn, "/org/mechanix/services/HwButton/Home")?;
+    let gesture_ctxt = SignalContext::new(conn, "/org/mechanix/services/HwButton")?;
 
     loop {
-        tokio::select! {
-            event = power_button.poll() => {
-                println!("power button event is {:?}", event);
-                let ctxt = SignalContext::new(conn, "/org/mechanix/services/HwButton/Power")?;
-                hw_button_bus
-                    .notification(
-                        &ctxt,
-                        event,
-                    )
-                    .await?;
+        let deadline = detector.next_deadline();
+        let event = tokio::select! {
+            event = devices.next() => event,
+            _ = sleep_until(deadline) => {
+                for gesture in detector.tick(Instant::now().into_std()) {
+                    emit_gesture(hw_button_bus, &gesture_ctxt, gesture).await?;
+                }
+                continue;
             }
+        };
 
-            event = home_button.poll() => {
-                println!("home button event is {:?}", event);
-                let ctxt = SignalContext::new(conn, "/org/mechanix/services/HwButton/Home")?;
-                hw_button_bus
-                    .notification(
-                        &ctxt,
-                        event,
-                    )
-                    .await?;
+        let ButtonEvent { action, event } = match event {
+            ButtonDevicesEvent::Button(button_event) => button_event,
+            ButtonDevicesEvent::Changed => {
+                let status = devices.status();
+                info!(task = "hw_buttons", "button devices changed {:?}", status);
+                hw_button_bus.with_status(|current| *current = status);
+                continue;
             }
+        };
+        debug!(task = "hw_buttons", "button event {:?} ({})", event, action);
+
+        // power and home keep their own paths, the other keys share the
+        // gesture path
+        let key = match event {
+            KeyEvent::Pressed(key) | KeyEvent::Released(key) | KeyEvent::Pressing(key) => key,
+        };
+        let ctxt = match key {
+            Key::Power => &power_ctxt,
+            Key::Home => &home_ctxt,
+            _ => &gesture_ctxt,
+        };
+        hw_button_bus.notification(ctxt, event).await?;
+
+        let now = Instant::now().into_std();
+        let gestures = match event {
+            KeyEvent::Pressed(_) => detector.key_down(&action, now),
+            KeyEvent::Released(_) => detector.key_up(&action, now),
+            KeyEvent::Pressing(_) => vec![],
+        };
+        for gesture in gestures {
+            emit_gesture(hw_button_bus, &gesture_ctxt, gesture).await?;
         }
     }
 }
+
+async fn sleep_until(deadline: Option<std::time::Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(Instant::from_std(deadline)).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn emit_gesture(
+    hw_button_bus: &HwButtonInterface,
+    ctxt: &SignalContext<'_>,
+    gesture: GestureEvent,
+) -> Result<(), ZbusError> {
+    info!(task = "hw_buttons", "gesture {:?}", gesture);
+    hw_button_bus.gesture(ctxt, gesture).await?;
+    Ok(())
+}
@@ -5,12 +5,96 @@ use zbus::{
     SignalContext,
 };
 
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 use tokio::time::{self, Duration};
+use tracing::{error, info, warn};
 
-use mechanix_host_metrics::HostMetrics;
+use mechanix_host_metrics::{
+    DiskInfo, HostMetrics, Metric, MetricsHistory, MetricsSample, ProcessSort,
+};
 
+/// Shares one sampler between the methods and the notification stream so
+/// CPU usage is measured between calls instead of from a fresh sample
 #[derive(Clone)]
-pub struct HostMetricsBusInterface {}
+pub struct HostMetricsBusInterface {
+    host_metrics: Arc<Mutex<HostMetrics>>,
+    history: Arc<Mutex<MetricsHistory>>,
+}
+
+/// Sampling of the metrics history, `path` persists it across restarts
+#[derive(Debug, Clone)]
+pub struct HistorySettings {
+    pub interval: Duration,
+    pub capacity: usize,
+    pub path: Option<PathBuf>,
+    pub persist_interval: Duration,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            // a day of samples at the default interval
+            capacity: 8640,
+            path: None,
+            persist_interval: Duration::from_secs(300),
+        }
+    }
+}
+
+impl HostMetricsBusInterface {
+    /// Starts from the persisted history when there is one
+    pub fn new(history_settings: &HistorySettings) -> Self {
+        let history = match &history_settings.path {
+            Some(path) if path.exists() => {
+                match MetricsHistory::load(path, history_settings.capacity) {
+                    Ok(history) => {
+                        info!(
+                            task = "host_metrics_history",
+                            "loaded {} samples from {:?}",
+                            history.len(),
+                            path
+                        );
+                        history
+                    }
+                    Err(e) => {
+                        warn!(
+                            task = "host_metrics_history",
+                            "cannot load {:?} - {}", path, e
+                        );
+                        MetricsHistory::new(history_settings.capacity)
+                    }
+                }
+            }
+            _ => MetricsHistory::new(history_settings.capacity),
+        };
+
+        HostMetricsBusInterface {
+            host_metrics: Arc::new(Mutex::new(HostMetrics::new())),
+            history: Arc::new(Mutex::new(history)),
+        }
+    }
+
+    fn with_host_metrics<T>(&self, f: impl FnOnce(&mut HostMetrics) -> T) -> T {
+        let mut host_metrics = self
+            .host_metrics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut host_metrics)
+    }
+
+    fn with_history<T>(&self, f: impl FnOnce(&mut MetricsHistory) -> T) -> T {
+        let mut history = self
+            .history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut history)
+    }
+}
 
 #[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
 // `Type` treats `HostMetricsResponse` is an alias for `a{sv}`.
@@ -26,6 +110,10 @@ pub struct MemoryInfoResponse {
 // `Type` treats `HostMetricsResponse` is an alias for `a{sv}`.
 #[zvariant(signature = "a{sv}")]
 pub struct DiskInfoResponse {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub is_removable: bool,
     pub available_space: u64,
     pub total_space: u64,
     pub used_space: u64,
@@ -40,14 +128,53 @@ pub struct LoadAverageResponse {
     pub fifteen: f64,
 }
 
-#[derive(DeserializeDict, SerializeDict, Type)]
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `ThermalZoneResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct ThermalZoneResponse {
+    pub name: String,
+    pub temperature: f32,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `ProcessInfoResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct ProcessInfoResponse {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
+// `Type` treats `HistorySampleResponse` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct HistorySampleResponse {
+    pub timestamp: u64,
+    pub value: f64,
+}
+
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
 // `Type` treats `HostMetricsResponse` is an alias for `a{sv}`.
 #[zvariant(signature = "a{sv}")]
 pub struct NetworkDataInfo {
     pub interface_name: String,
     pub mac_address: String,
-    pub received: u64,
-    pub transmitted: u64,
+    /// Bytes per second
+    pub received_rate: u64,
+    pub transmitted_rate: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub received_since_mark: u64,
+    pub transmitted_since_mark: u64,
+}
+
+#[derive(Debug, DeserializeDict, SerializeDict, Type)]
+// `Type` treats `NetworkInterfacesEvent` is an alias for `a{sv}`.
+#[zvariant(signature = "a{sv}")]
+pub struct NetworkInterfacesEvent {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
 }
 
 #[derive(Debug, DeserializeDict, SerializeDict, Type)]
@@ -62,60 +189,132 @@ pub struct HostMetricsNotificationEvent {
 #[interface(name = "org.mechanix.services.HostMetrics")]
 impl HostMetricsBusInterface {
     pub async fn get_cpu_usage(&self) -> Result<f32, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let cpu_usage = host_metrics.cpu_usage();
+        let cpu_usage = self.with_host_metrics(|host_metrics| host_metrics.cpu_usage());
         Ok(cpu_usage)
     }
 
+    //usage of each core
+    pub async fn get_cpu_core_usage(&self) -> Result<Vec<f32>, ZbusError> {
+        let cpu_core_usage = self.with_host_metrics(|host_metrics| host_metrics.cpu_core_usage());
+        Ok(cpu_core_usage)
+    }
+
     pub async fn get_memory_usage(&self) -> Result<u64, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let memory_usage = host_metrics.memory_usage();
+        let memory_usage = self.with_host_metrics(|host_metrics| host_metrics.memory_usage());
         Ok(memory_usage)
     }
 
     pub async fn get_disk_info(&self) -> Result<DiskInfoResponse, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let disk_usage = host_metrics.disk_info();
+        let disk_info = match self.with_host_metrics(|host_metrics| host_metrics.disk_info()) {
+            Some(disk_info) => disk_info,
+            None => return Err(ZbusError::Failed("No disk found".to_string())),
+        };
+
+        Ok(to_disk_info_response(disk_info))
+    }
+
+    //all mounted disks
+    pub async fn get_disks(&self) -> Result<Vec<DiskInfoResponse>, ZbusError> {
+        let disks = self.with_host_metrics(|host_metrics| host_metrics.disks());
+        Ok(disks.into_iter().map(to_disk_info_response).collect())
+    }
+
+    pub async fn get_thermal_zones(&self) -> Result<Vec<ThermalZoneResponse>, ZbusError> {
+        let thermal_zones = self.with_host_metrics(|host_metrics| host_metrics.thermal_zones());
+
+        let thermal_zones = thermal_zones
+            .into_iter()
+            .map(|zone| ThermalZoneResponse {
+                name: zone.name,
+                temperature: zone.temperature,
+            })
+            .collect();
 
-        let disk_info = DiskInfoResponse {
-            available_space: disk_usage.available_space,
-            total_space: disk_usage.total_space,
-            used_space: disk_usage.used_space,
+        Ok(thermal_zones)
+    }
+
+    pub async fn get_battery_temperature(&self) -> Result<f32, ZbusError> {
+        match self.with_host_metrics(|host_metrics| host_metrics.battery_temperature()) {
+            Some(temperature) => Ok(temperature),
+            None => Err(ZbusError::NotSupported(
+                "No battery temperature found".to_string(),
+            )),
+        }
+    }
+
+    //top processes, sorted by "cpu" or "memory"
+    pub async fn get_top_processes(
+        &self,
+        count: u32,
+        sort_by: String,
+    ) -> Result<Vec<ProcessInfoResponse>, ZbusError> {
+        let sort = match sort_by.as_str() {
+            "cpu" => ProcessSort::Cpu,
+            "memory" => ProcessSort::Memory,
+            _ => {
+                return Err(ZbusError::InvalidArgs(format!(
+                    "Unknown sort {}, expected cpu or memory",
+                    sort_by
+                )))
+            }
         };
+        let processes =
+            self.with_host_metrics(|host_metrics| host_metrics.top_processes(count as usize, sort));
+
+        let processes = processes
+            .into_iter()
+            .map(|process| ProcessInfoResponse {
+                pid: process.pid,
+                name: process.name,
+                cpu_usage: process.cpu_usage,
+                memory: process.memory,
+            })
+            .collect();
 
-        Ok(disk_info)
+        Ok(processes)
     }
 
+    //throughput and byte counts of every interface
     pub async fn get_network_usage(&self) -> Result<Vec<NetworkDataInfo>, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let network_usage = host_metrics.network_usage();
-
-        // convert network_usage to NetworkDataInfo
-        let network_usage: Vec<NetworkDataInfo> = network_usage
-            .iter()
-            .map(|data| NetworkDataInfo {
-                interface_name: data.interface_name.clone(),
-                mac_address: data.mac_address.to_string(),
-                received: data.received,
-                transmitted: data.transmitted,
+        let network_usage = self.with_host_metrics(|host_metrics| host_metrics.network_usage());
+
+        let network_usage = network_usage
+            .into_iter()
+            .map(|usage| NetworkDataInfo {
+                interface_name: usage.interface_name,
+                mac_address: usage.mac_address,
+                received_rate: usage.received_rate,
+                transmitted_rate: usage.transmitted_rate,
+                total_received: usage.total_received,
+                total_transmitted: usage.total_transmitted,
+                received_since_mark: usage.received_since_mark,
+                transmitted_since_mark: usage.transmitted_since_mark,
             })
             .collect();
 
         Ok(network_usage)
     }
 
+    //starts counting the bytes since now, for all interfaces when empty
+    pub async fn reset_network_mark(&self, interface_name: String) -> Result<(), ZbusError> {
+        let interface_name = match interface_name.is_empty() {
+            true => None,
+            false => Some(interface_name.as_str()),
+        };
+        self.with_host_metrics(|host_metrics| host_metrics.reset_network_mark(interface_name));
+        Ok(())
+    }
+
     //cpu frequency
     pub async fn get_cpu_freq(&self) -> Result<u64, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let cpu_freq = host_metrics.cpu_freq();
+        let cpu_freq = self.with_host_metrics(|host_metrics| host_metrics.cpu_freq());
 
         Ok(cpu_freq)
     }
 
     //memory info
     pub async fn get_memory_info(&self) -> Result<MemoryInfoResponse, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let memory = host_metrics.memory_info();
+        let memory = self.with_host_metrics(|host_metrics| host_metrics.memory_info());
 
         let memory_info = MemoryInfoResponse {
             total_memory: memory.total_memory,
@@ -129,16 +328,14 @@ impl HostMetricsBusInterface {
 
     // uptime
     pub async fn get_uptime(&self) -> Result<u64, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let uptime = host_metrics.uptime();
+        let uptime = self.with_host_metrics(|host_metrics| host_metrics.uptime());
 
         Ok(uptime)
     }
 
     //load average
     pub async fn get_load_average(&self) -> Result<LoadAverageResponse, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let load_average = host_metrics.load_average();
+        let load_average = self.with_host_metrics(|host_metrics| host_metrics.load_average());
 
         let load_average = LoadAverageResponse {
             one: load_average.one,
@@ -149,23 +346,26 @@ impl HostMetricsBusInterface {
         Ok(load_average)
     }
 
-    //network usage
-    pub async fn get_network_data(&self) -> Result<Vec<NetworkDataInfo>, ZbusError> {
-        let host_metrics = HostMetrics::new();
-        let network_data = host_metrics.network_usage();
-
-        let mut network_data_info = Vec::new();
-        for data in network_data {
-            let network_data = NetworkDataInfo {
-                interface_name: data.interface_name,
-                mac_address: data.mac_address.to_string(),
-                received: data.received,
-                transmitted: data.transmitted,
-            };
-            network_data_info.push(network_data);
-        }
+    //history of a metric (cpu, memory, network_received, network_transmitted or
+    //battery) since a unix timestamp, averaged over `resolution` seconds
+    pub async fn get_history(
+        &self,
+        metric: String,
+        since: u64,
+        resolution: u64,
+    ) -> Result<Vec<HistorySampleResponse>, ZbusError> {
+        let metric = match metric.parse::<Metric>() {
+            Ok(metric) => metric,
+            Err(e) => return Err(ZbusError::InvalidArgs(e.to_string())),
+        };
+        let history = self.with_history(|history| history.query(metric, since, resolution));
 
-        Ok(network_data_info)
+        let history = history
+            .into_iter()
+            .map(|(timestamp, value)| HistorySampleResponse { timestamp, value })
+            .collect();
+
+        Ok(history)
     }
 
     // notification signal
@@ -175,6 +375,26 @@ impl HostMetricsBusInterface {
         ctxt: &SignalContext<'_>,
         event: HostMetricsNotificationEvent,
     ) -> Result<(), zbus::Error>;
+
+    // interfaces came up or went away
+    #[zbus(signal)]
+    pub async fn network_interfaces_changed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        event: NetworkInterfacesEvent,
+    ) -> Result<(), zbus::Error>;
+}
+
+fn to_disk_info_response(disk_info: DiskInfo) -> DiskInfoResponse {
+    DiskInfoResponse {
+        name: disk_info.name,
+        mount_point: disk_info.mount_point,
+        file_system: disk_info.file_system,
+        is_removable: disk_info.is_removable,
+        available_space: disk_info.available_space,
+        total_space: disk_info.total_space,
+        used_space: disk_info.used_space,
+    }
 }
 
 #[allow(dead_code)]
@@ -183,7 +403,6 @@ pub async fn host_metrics_event_notification_stream(
     conn: &zbus::Connection,
 ) -> Result<(), ZbusError> {
     let mut interval = time::interval(Duration::from_secs(10));
-    let host_metrics = HostMetrics::new(); // Create once and reuse
     let ctxt = SignalContext::new(conn, "/org/mechanix/services/HostMetrics")?; // Create once outside the loop
 
     let mut previous_cpu_usage: Option<f32> = None;
@@ -192,8 +411,9 @@ pub async fn host_metrics_event_notification_stream(
 
     loop {
         interval.tick().await;
-        let cpu_usage = host_metrics.cpu_usage();
-        let memory_info = host_metrics.memory_info();
+        let (cpu_usage, memory_info) = host_metrics_bus.with_host_metrics(|host_metrics| {
+            (host_metrics.cpu_usage(), host_metrics.memory_info())
+        });
         let total_memory = memory_info.total_memory;
         let available_memory = memory_info.used_memory;
 
@@ -221,3 +441,102 @@ pub async fn host_metrics_event_notification_stream(
         }
     }
 }
+
+/// Samples the metrics into the history every `interval` and persists it
+/// every `persist_interval` when a path is set
+pub async fn host_metrics_history_stream(
+    host_metrics_bus: &HostMetricsBusInterface,
+    settings: HistorySettings,
+) -> Result<(), ZbusError> {
+    let mut interval = time::interval(settings.interval);
+    let mut last_persist = Instant::now();
+
+    loop {
+        interval.tick().await;
+
+        let (cpu_usage, memory_info, network_usage, battery_percentage) = host_metrics_bus
+            .with_host_metrics(|host_metrics| {
+                (
+                    host_metrics.cpu_usage(),
+                    host_metrics.memory_info(),
+                    host_metrics.network_usage(),
+                    host_metrics.battery_percentage(),
+                )
+            });
+        let (network_received, network_transmitted) =
+            network_usage
+                .iter()
+                .fold((0, 0), |(received, transmitted), usage| {
+                    (
+                        received + usage.received_rate,
+                        transmitted + usage.transmitted_rate,
+                    )
+                });
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        host_metrics_bus.with_history(|history| {
+            history.push(MetricsSample {
+                timestamp,
+                cpu_usage,
+                used_memory: memory_info.used_memory,
+                total_memory: memory_info.total_memory,
+                network_received,
+                network_transmitted,
+                battery_percentage,
+            })
+        });
+
+        let path = match &settings.path {
+            Some(path) if last_persist.elapsed() >= settings.persist_interval => path,
+            _ => continue,
+        };
+        last_persist = Instant::now();
+        let history = host_metrics_bus.with_history(|history| history.clone());
+        let path = path.clone();
+        let res = tokio::task::spawn_blocking(move || history.save(&path)).await;
+        match res {
+            Ok(Err(e)) => error!(task = "host_metrics_history", "cannot persist - {}", e),
+            Err(e) => error!(task = "host_metrics_history", "cannot persist - {}", e),
+            Ok(Ok(())) => (),
+        };
+    }
+}
+
+/// Keeps the network rates current and signals interfaces coming up or going
+/// away
+pub async fn host_metrics_network_stream(
+    host_metrics_bus: &HostMetricsBusInterface,
+    conn: &zbus::Connection,
+) -> Result<(), ZbusError> {
+    let mut interval = time::interval(Duration::from_secs(2));
+    let ctxt = SignalContext::new(conn, "/org/mechanix/services/HostMetrics")?;
+
+    // every interface is new on the first refresh
+    host_metrics_bus.with_host_metrics(|host_metrics| host_metrics.refresh_networks());
+
+    loop {
+        interval.tick().await;
+        let changes =
+            host_metrics_bus.with_host_metrics(|host_metrics| host_metrics.refresh_networks());
+        if changes.is_empty() {
+            continue;
+        }
+
+        info!(
+            task = "host_metrics_network",
+            "interfaces added {:?} removed {:?}", changes.added, changes.removed
+        );
+        host_metrics_bus
+            .network_interfaces_changed(
+                &ctxt,
+                NetworkInterfacesEvent {
+                    added: changes.added,
+                    removed: changes.removed,
+                },
+            )
+            .await?;
+    }
+}
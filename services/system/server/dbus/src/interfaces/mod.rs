@@ -1,6 +1,7 @@
 mod bluetooth_interface;
 pub use bluetooth_interface::{
-    bluetooth_event_notification_stream, BluetoothBusInterface, BluetoothNotificationEvent,
+    bluetooth_event_notification_stream, bluetooth_pairing_stream, BluetoothBusInterface,
+    BluetoothNotificationEvent, BluetoothPairingRequest,
 };
 
 mod wireless_interface;
@@ -11,16 +12,23 @@ pub use wireless_interface::{
 };
 
 mod display_interface;
-pub use display_interface::DisplayBusInterface;
+pub use display_interface::{
+    display_auto_brightness_stream, display_event_notification_stream, AutoBrightnessSettings,
+    BacklightDeviceResponse, DisplayBusInterface, DisplayNotificationEvent,
+};
 
 mod host_metrics;
 pub use host_metrics::{
-    host_metrics_event_notification_stream, HostMetricsBusInterface, HostMetricsNotificationEvent,
-    MemoryInfoResponse,
+    host_metrics_event_notification_stream, host_metrics_history_stream,
+    host_metrics_network_stream, DiskInfoResponse, HistorySampleResponse, HistorySettings,
+    HostMetricsBusInterface, HostMetricsNotificationEvent, MemoryInfoResponse, NetworkDataInfo,
+    NetworkInterfacesEvent, ProcessInfoResponse, ThermalZoneResponse,
 };
 
 mod hardware_buttons;
-pub use hardware_buttons::{hw_buttons_notification_stream, HwButtonInterface};
+pub use hardware_buttons::{
+    hw_buttons_notification_stream, ButtonDeviceResponse, HwButtonInterface,
+};
 
 mod security_interface;
 pub use security_interface::SecurityBusInterface;
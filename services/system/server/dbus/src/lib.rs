@@ -1,11 +1,12 @@
 pub mod interfaces;
 pub use interfaces as system_interfaces;
-pub use mechanix_hw_buttons::{Key, KeyEvent};
+pub use mechanix_hw_buttons::{Gesture, GestureEvent, Key, KeyEvent};
 pub use system_interfaces::{
     //bluetooth interface
     bluetooth_event_notification_stream,
     BluetoothBusInterface,
     BluetoothNotificationEvent,
+    BluetoothPairingRequest,
 
     //wireless interface
     KnownNetworkListResponse,
@@ -1,4 +1,8 @@
 use anyhow::Result;
+use mechanix_bluetooth_ctl::{PairingAgent, PAIRING_TIMEOUT};
+use mechanix_display_ctl::BrightnessCurve;
+use mechanix_hw_buttons::{GestureSettings, KeyMap};
+use std::{path::PathBuf, time::Duration};
 use tokio::task::JoinHandle;
 use zbus::connection;
 mod config;
@@ -6,13 +10,15 @@ mod interfaces;
 use config::read_configs_yml;
 
 use interfaces::{
-    hw_buttons_notification_stream, BluetoothBusInterface, DisplayBusInterface,
-    HostMetricsBusInterface, HwButtonInterface, SecurityBusInterface, WirelessBusInterface,
+    hw_buttons_notification_stream, AutoBrightnessSettings, BluetoothBusInterface,
+    DisplayBusInterface, HistorySettings, HostMetricsBusInterface, HwButtonInterface,
+    SecurityBusInterface, WirelessBusInterface,
 };
 
 use interfaces::{
-    bluetooth_event_notification_stream, host_metrics_event_notification_stream,
-    wireless_event_notification_stream,
+    bluetooth_event_notification_stream, bluetooth_pairing_stream, display_auto_brightness_stream,
+    display_event_notification_stream, host_metrics_event_notification_stream,
+    host_metrics_history_stream, host_metrics_network_stream, wireless_event_notification_stream,
 };
 
 #[tokio::main]
@@ -26,13 +32,40 @@ async fn main() -> Result<()> {
     };
     let mut handles: Vec<JoinHandle<()>> = Vec::new();
 
-    let bluetooth_bus = BluetoothBusInterface {};
+    // longer would let BlueZ time out the request before it is canceled
+    let pairing_timeout =
+        Duration::from_secs(config.interfaces.bluetooth.pairing_timeout_secs).min(PAIRING_TIMEOUT);
+    let (pairing_agent, pairing_events) = PairingAgent::new(pairing_timeout);
+    let bluetooth_bus = BluetoothBusInterface {
+        pairing_agent: pairing_agent.clone(),
+    };
     let _bluetooth_bus_connection = connection::Builder::system()?
         .name("org.mechanix.services.Bluetooth")?
-        .serve_at("/org/mechanix/services/Bluetooth", bluetooth_bus)?
+        .serve_at("/org/mechanix/services/Bluetooth", bluetooth_bus.clone())?
         .build()
         .await?;
 
+    // registered with BlueZ as long as the handle lives
+    let _pairing_agent_handle = match pairing_agent.register().await {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            println!("Error registering bluetooth pairing agent: {}", e);
+            None
+        }
+    };
+
+    let pairing_bus = bluetooth_bus.clone();
+    let pairing_connection = _bluetooth_bus_connection.clone();
+    let _pairing_handle = tokio::spawn(async move {
+        if let Err(e) =
+            bluetooth_pairing_stream(&pairing_bus, &pairing_connection, pairing_events).await
+        {
+            println!("Error in bluetooth pairing stream: {}", e);
+        }
+    });
+
+    handles.push(_pairing_handle);
+
     let _bluetooth_handle = tokio::spawn(async move {
         if let Err(e) =
             bluetooth_event_notification_stream(&bluetooth_bus, &_bluetooth_bus_connection).await
@@ -66,16 +99,62 @@ async fn main() -> Result<()> {
 
     // handles.push(wireless_handle);
 
-    let display_bus = DisplayBusInterface {
-        path: config.interfaces.display.device.clone(),
+    let auto_brightness = &config.interfaces.display.auto_brightness;
+    let curve = auto_brightness
+        .curve
+        .iter()
+        .map(|point| (point.lux, point.brightness))
+        .collect();
+    let auto_brightness = AutoBrightnessSettings {
+        enabled: auto_brightness.enabled,
+        curve: match BrightnessCurve::new(curve) {
+            Ok(curve) => curve,
+            Err(e) => {
+                eprintln!("Invalid auto brightness curve, using the default: {}", e);
+                BrightnessCurve::default()
+            }
+        },
+        hysteresis: auto_brightness.hysteresis,
+        interval: Duration::from_millis(auto_brightness.interval_ms.max(1)),
+        duration_ms: auto_brightness.duration_ms,
     };
+    let display_bus =
+        DisplayBusInterface::new(config.interfaces.display.device.clone(), auto_brightness);
     let _display_bus_connection = connection::Builder::system()?
         .name("org.mechanix.services.Display")?
-        .serve_at("/org/mechanix/services/Display", display_bus)?
+        .serve_at("/org/mechanix/services/Display", display_bus.clone())?
         .build()
         .await?;
+    let auto_brightness_bus = display_bus.clone();
 
-    let host_metrics_bus = HostMetricsBusInterface {};
+    let _display_handle = tokio::spawn(async move {
+        if let Err(e) =
+            display_event_notification_stream(&display_bus, &_display_bus_connection).await
+        {
+            println!("Error in display notification stream: {}", e);
+        }
+    });
+
+    handles.push(_display_handle);
+
+    let _auto_brightness_handle = tokio::spawn(async move {
+        if let Err(e) = display_auto_brightness_stream(&auto_brightness_bus).await {
+            println!("Error in auto brightness stream: {}", e);
+        }
+    });
+
+    handles.push(_auto_brightness_handle);
+
+    let history = &config.interfaces.host_metrics.history;
+    let history_settings = HistorySettings {
+        interval: Duration::from_secs(history.interval_secs.max(1)),
+        capacity: history.capacity,
+        path: history.path.as_ref().map(PathBuf::from),
+        persist_interval: Duration::from_secs(history.persist_interval_secs),
+    };
+    let host_metrics_bus = HostMetricsBusInterface::new(&history_settings);
+    let host_metrics_history_bus = host_metrics_bus.clone();
+    let host_metrics_network_bus = host_metrics_bus.clone();
     let host_metrics_bus_connection = connection::Builder::system()?
         .name("org.mechanix.services.HostMetrics")?
         .serve_at(
@@ -85,6 +164,18 @@ async fn main() -> Result<()> {
         .build()
         .await?;
 
+    let host_metrics_network_connection = host_metrics_bus_connection.clone();
+    let _host_metrics_network_handle = tokio::spawn(async move {
+        if let Err(e) =
+            host_metrics_network_stream(&host_metrics_network_bus, &host_metrics_network_connection)
+                .await
+        {
+            println!("Error in host metrics network stream: {}", e)
+        }
+    });
+
+    handles.push(_host_metrics_network_handle);
+
     let _host_metrics_handle = tokio::spawn(async move {
         if let Err(e) =
             host_metrics_event_notification_stream(&host_metrics_bus, &host_metrics_bus_connection)
@@ -94,10 +185,20 @@ async fn main() -> Result<()> {
         }
     });
 
-    let hw_button_bus = HwButtonInterface {};
+    let _host_metrics_history_handle = tokio::spawn(async move {
+        if let Err(e) =
+            host_metrics_history_stream(&host_metrics_history_bus, history_settings).await
+        {
+            println!("Error in host metrics history stream: {}", e)
+        }
+    });
+
+    handles.push(_host_metrics_history_handle);
+
+    let hw_button_bus = HwButtonInterface::default();
     let _hw_button_bus_connection = connection::Builder::system()?
         .name("org.mechanix.services.HwButton")?
-        .serve_at("/org/mechanix/services/HwButton", hw_button_bus)?
+        .serve_at("/org/mechanix/services/HwButton", hw_button_bus.clone())?
         .build()
         .await?;
 
@@ -108,15 +209,35 @@ async fn main() -> Result<()> {
         .build()
         .await?;
 
-    let power_button_path = config.interfaces.hw_buttons.power.path.clone();
-    let home_button_path = config.interfaces.hw_buttons.home.path.clone();
+    let hw_buttons = &config.interfaces.hw_buttons;
+    let hw_button_paths: Vec<PathBuf> = [&hw_buttons.power.path, &hw_buttons.home.path]
+        .into_iter()
+        .chain(hw_buttons.devices.iter())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    let discover_hw_buttons = hw_buttons.discover;
+    let keymap = match &hw_buttons.keymap {
+        Some(entries) => KeyMap::new(entries).unwrap_or_else(|e| {
+            println!("Invalid hw buttons keymap, using the default: {}", e);
+            KeyMap::default()
+        }),
+        None => KeyMap::default(),
+    };
+    let gesture_settings = GestureSettings {
+        long_press: Duration::from_millis(hw_buttons.gestures.long_press_ms),
+        double_press: Duration::from_millis(hw_buttons.gestures.double_press_ms),
+        chords: hw_buttons.gestures.chords.clone(),
+    };
 
     let _hw_button_handle = tokio::spawn(async move {
         if let Err(e) = hw_buttons_notification_stream(
             &hw_button_bus,
             &_hw_button_bus_connection,
-            power_button_path,
-            home_button_path,
+            hw_button_paths,
+            discover_hw_buttons,
+            keymap,
+            gesture_settings,
         )
         .await
         {
@@ -59,6 +59,7 @@ pub enum Message {
     SearchTextChanged(String),
     RunApp { name: String, exec: String },
     ChangeRoute { route: Routes },
+    SettingsUpdated { settings: AppDrawerSettings },
 }
 
 #[derive(Default, Debug, Clone, Hash)]
@@ -134,6 +135,9 @@ impl Component for AppDrawer {
                     let _ = spawn_command("sh".to_string(), args);
                 }
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
             _ => (),
         }
 
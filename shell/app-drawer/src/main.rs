@@ -7,6 +7,8 @@ mod theme;
 mod constants;
 
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
 use gui::AppDrawer;
@@ -28,7 +30,9 @@ use mctk_smithay::{layer_shell::layer_window::LayerWindow, WindowInfo};
 
 use desktop_entries::DesktopEntries;
 use settings::AppDrawerSettings;
+use settings_watcher::watch_settings;
 use theme::AppDrawerTheme;
+use tokio::runtime::Builder;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
@@ -38,7 +42,9 @@ use crate::gui::Message;
 pub struct AppParams {}
 
 #[derive(Debug)]
-enum AppMessage {}
+enum AppMessage {
+    SettingsUpdated { settings: AppDrawerSettings },
+}
 
 fn main() -> anyhow::Result<()> {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or(EnvFilter::new("debug"));
@@ -154,20 +160,24 @@ fn main() -> anyhow::Result<()> {
     let handle = event_loop.handle();
 
     //subscribe to events channel
-    let (_, channel_rx) = calloop::channel::channel::<Message>();
+    let (channel_tx, channel_rx) = calloop::channel::channel::<AppMessage>();
     let window_tx_2 = window_tx.clone();
     let _ = handle.insert_source(channel_rx, move |event, _, app| {
         let _ = match event {
             // calloop::channel::Event::Msg(msg) => app.app.push_message(msg),
-            calloop::channel::Event::Msg(msg) => {
-                let _ = window_tx_2
-                    .clone()
-                    .send(WindowMessage::Send { message: msg!(msg) });
-            }
+            calloop::channel::Event::Msg(msg) => match msg {
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+            },
             calloop::channel::Event::Closed => {}
         };
     });
 
+    run_settings_watcher(channel_tx, settings);
+
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
     }
@@ -175,3 +185,37 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Reloads the settings.yml when it changes, the desktop entries are only
+/// loaded at startup
+fn run_settings_watcher(
+    app_channel: Sender<AppMessage>,
+    settings: AppDrawerSettings,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let file_path = match settings::find_config_path() {
+            Some(file_path) => file_path,
+            None => {
+                println!("no settings.yml to watch");
+                return;
+            }
+        };
+
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let settings = Arc::new(RwLock::new(settings));
+        let result = runtime.block_on(watch_settings(
+            file_path,
+            settings,
+            settings::read_settings_yml,
+            move |settings| {
+                let _ = app_channel.send(AppMessage::SettingsUpdated {
+                    settings: settings.clone(),
+                });
+            },
+        ));
+
+        if let Err(e) = result {
+            println!("error while watching settings {}", e);
+        }
+    })
+}
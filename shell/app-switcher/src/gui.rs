@@ -30,6 +30,7 @@ pub enum AppMessage {
     AppInstanceClicked(ToplevelKey),
     AppInstanceCloseClicked(ToplevelKey),
     CloseAllApps,
+    SettingsUpdated { settings: AppSwitcherSettings },
 }
 
 /// ## Message
@@ -43,6 +44,7 @@ pub enum Message {
     AppInstanceCloseClicked(ToplevelKey),
     CloseAllApps,
     BackPressed,
+    SettingsUpdated { settings: AppSwitcherSettings },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -319,6 +321,9 @@ impl Component for AppSwitcher {
                     let _ = app_channel.send(AppMessage::CloseAllApps);
                 };
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
             _ => (),
         }
         vec![]
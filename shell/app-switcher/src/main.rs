@@ -6,6 +6,7 @@ mod settings;
 mod theme;
 mod constants;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
@@ -18,6 +19,7 @@ use services::app_manager::{AppManagerMessage, AppManagerService};
 use smithay_client_toolkit::reexports::calloop::{self, channel::Sender};
 
 use settings::AppSwitcherSettings;
+use settings_watcher::watch_settings;
 use tokio::runtime::Builder;
 use tokio::sync::{mpsc, oneshot};
 use tracing::{error, info};
@@ -186,13 +188,17 @@ fn main() -> anyhow::Result<()> {
                         }
                     })
                 }
-                _ => (),
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
             },
             calloop::channel::Event::Closed => {}
         };
     });
 
-    init_services(app_manager_msg_rx, app_channel2);
+    init_services(app_manager_msg_rx, settings, app_channel2);
 
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
@@ -204,6 +210,7 @@ fn main() -> anyhow::Result<()> {
 
 fn init_services(
     app_manager_msg_rx: mpsc::Receiver<AppManagerMessage>,
+    settings: AppSwitcherSettings,
     app_channel: Sender<AppMessage>,
 ) -> JoinHandle<()> {
     thread::spawn(move || {
@@ -213,10 +220,11 @@ fn init_services(
             .build()
             .unwrap();
 
-        let future1 = run_app_manager_handler(app_manager_msg_rx, app_channel);
+        let future1 = run_app_manager_handler(app_manager_msg_rx, app_channel.clone());
+        let future2 = run_settings_watch_handler(app_channel, settings);
 
         runtime
-            .block_on(runtime.spawn(async move { tokio::join!(future1) }))
+            .block_on(runtime.spawn(async move { tokio::join!(future1, future2) }))
             .unwrap();
     })
 }
@@ -231,3 +239,33 @@ async fn run_app_manager_handler(
     // start the app manager handler
     let _ = app_manager_handler.run(msg_rx, app_channel_tx).await;
 }
+
+async fn run_settings_watch_handler(
+    app_channel: Sender<AppMessage>,
+    settings: AppSwitcherSettings,
+) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
@@ -0,0 +1,167 @@
+//! Hot reloading of the `settings.yml` and `theme.yml` of the shell components.
+
+use std::{
+    ffi::OsStr,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use futures::{
+    channel::mpsc::{channel, Receiver},
+    SinkExt, StreamExt,
+};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{error, info};
+
+/// Editors write a file in several steps (truncate, write, rename), the
+/// events of one save arriving within this window are handled as one change
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// # Watch Settings
+///
+/// Re-reads the file at `path` with `read` whenever it changes and stores the
+/// result in `settings`, then calls `on_change` so the component can redraw.
+///
+/// A file that fails to read or parse is reported and the previous value is
+/// kept, so a half-written file never takes the component down. The parent
+/// directory is watched rather than the file so that saves which replace the
+/// file (as most editors do) are picked up as well.
+///
+/// Runs until the watcher fails, meant to be joined with the other handlers
+/// of the component.
+pub async fn watch_settings<T, R, C>(
+    path: PathBuf,
+    settings: Arc<RwLock<T>>,
+    read: R,
+    on_change: C,
+) -> Result<()>
+where
+    T: Send + Sync,
+    R: Fn() -> Result<T> + Send,
+    C: Fn(&T) + Send,
+{
+    let (dir, file_name) = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(file_name)) => (dir.to_path_buf(), file_name.to_os_string()),
+        _ => bail!("cannot watch the path - {:?}", path),
+    };
+    let dir = match dir.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => dir,
+    };
+
+    let (mut watcher, mut rx) = async_watcher()?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    info!(task = "watch_settings", "watching - {:?}", path);
+
+    while let Some(res) = rx.next().await {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                error!(task = "watch_settings", "watch error - {:?}", e);
+                continue;
+            }
+        };
+        if !is_change_of(&event, &file_name) {
+            continue;
+        }
+
+        // let the rest of the save land before reading
+        while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, rx.next()).await {}
+
+        if !path.exists() {
+            continue;
+        }
+
+        let new_settings = match read() {
+            Ok(new_settings) => new_settings,
+            Err(e) => {
+                error!(
+                    task = "watch_settings",
+                    "keeping the previous settings, error reading {:?} - {}", path, e
+                );
+                continue;
+            }
+        };
+
+        info!(task = "watch_settings", "reloaded - {:?}", path);
+        match settings.write() {
+            Ok(mut settings) => *settings = new_settings,
+            Err(e) => bail!("settings lock poisoned - {}", e),
+        };
+        if let Ok(settings) = settings.read() {
+            on_change(&settings);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_change_of(event: &Event, file_name: &OsStr) -> bool {
+    let is_write = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any
+    );
+    is_write
+        && event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == Some(file_name))
+}
+
+fn async_watcher() -> notify::Result<(RecommendedWatcher, Receiver<notify::Result<Event>>)> {
+    let (mut tx, rx) = channel(16);
+
+    let watcher = RecommendedWatcher::new(
+        move |res| {
+            futures::executor::block_on(async {
+                let _ = tx.send(res).await;
+            })
+        },
+        Config::default(),
+    )?;
+
+    Ok((watcher, rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[tokio::test]
+    async fn reloads_settings_when_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("settings-watcher-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.yml");
+        fs::write(&path, "1").unwrap();
+
+        let read_path = path.clone();
+        let read = move || -> Result<u32> { Ok(fs::read_to_string(&read_path)?.trim().parse()?) };
+        let settings = Arc::new(RwLock::new(read().unwrap()));
+        let (changed_tx, mut changed_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let watch = tokio::spawn(watch_settings(
+            path.clone(),
+            settings.clone(),
+            read,
+            move |value: &u32| {
+                let _ = changed_tx.send(*value);
+            },
+        ));
+
+        // give the watcher time to register before saving
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        fs::write(&path, "2").unwrap();
+
+        let changed = tokio::time::timeout(Duration::from_secs(5), changed_rx.recv())
+            .await
+            .expect("no change reported");
+        assert_eq!(changed, Some(2));
+        assert_eq!(*settings.read().unwrap(), 2);
+
+        watch.abort();
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
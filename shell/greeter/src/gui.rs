@@ -55,6 +55,8 @@ pub enum Message {
     Wireless { status: WirelessStatus },
     Bluetooth { status: BluetoothStatus },
     Battery { level: u8, status: BatteryStatus },
+    SettingsUpdated { settings: GreeterSettings },
+    ThemeUpdated { theme: GreeterTheme },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -420,6 +422,12 @@ impl Component for Greeter {
                     _ => (),
                 }
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
+            Some(Message::ThemeUpdated { theme }) => {
+                self.state_mut().custom_theme = theme.clone();
+            }
             _ => (),
         }
 
@@ -9,6 +9,7 @@ mod users;
 mod constants;
 
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
@@ -34,6 +35,7 @@ use smithay_client_toolkit::reexports::calloop::{self, channel::Sender};
 
 use mechanix_status_bar_components::types::{BatteryStatus, BluetoothStatus, WirelessStatus};
 use settings::GreeterSettings;
+use settings_watcher::watch_settings;
 use smithay_client_toolkit::shell::wlr_layer;
 use theme::GreeterTheme;
 use tokio::runtime::Builder;
@@ -71,6 +73,8 @@ pub enum LoginHandlerEvents {
 pub enum AppMessage {
     LoginEvents(LoginHandlerEvents),
     AuthSubmit(AuthSubmit),
+    SettingsUpdated { settings: GreeterSettings },
+    ThemeUpdated { theme: GreeterTheme },
 }
 
 #[derive(Debug, Clone)]
@@ -309,6 +313,16 @@ fn main() -> anyhow::Result<()> {
                             message: msg!(LoginHandlerEvents::from(login_event)),
                         });
                     }
+                    AppMessage::SettingsUpdated { settings } => {
+                        let _ = window_tx_2.clone().send(WindowMessage::Send {
+                            message: msg!(Message::SettingsUpdated { settings }),
+                        });
+                    }
+                    AppMessage::ThemeUpdated { theme } => {
+                        let _ = window_tx_2.clone().send(WindowMessage::Send {
+                            message: msg!(Message::ThemeUpdated { theme }),
+                        });
+                    }
                 }
 
                 // AppMessage::Test => {
@@ -355,7 +369,13 @@ fn main() -> anyhow::Result<()> {
             calloop::channel::Event::Closed => {}
         };
     });
-    init_services(greeter_msg_rx, settings, app_channel2, status_bar_channel);
+    init_services(
+        greeter_msg_rx,
+        settings,
+        custom_theme,
+        app_channel2,
+        status_bar_channel,
+    );
 
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
@@ -368,6 +388,7 @@ fn main() -> anyhow::Result<()> {
 fn init_services(
     greeter_msg_rx: mpsc::Receiver<LoginHandlerMessage>,
     settings: GreeterSettings,
+    theme: GreeterTheme,
     app_channel: Sender<AppMessage>,
     status_bar_channel: Sender<StatusBarMessage>,
 ) -> JoinHandle<()> {
@@ -384,10 +405,20 @@ fn init_services(
         let wireless_f = run_wireless_handler(status_bar_channel.clone());
         let bluetooth_f = run_bluetooth_handler(status_bar_channel.clone());
         let battery_f = run_battery_handler(status_bar_channel.clone());
+        let settings_watch_f = run_settings_watch_handler(app_channel.clone(), settings.clone());
+        let theme_watch_f = run_theme_watch_handler(app_channel.clone(), theme);
 
         runtime
             .block_on(runtime.spawn(async move {
-                tokio::join!(login_f, clock_f, wireless_f, bluetooth_f, battery_f)
+                tokio::join!(
+                    login_f,
+                    clock_f,
+                    wireless_f,
+                    bluetooth_f,
+                    battery_f,
+                    settings_watch_f,
+                    theme_watch_f
+                )
             }))
             .unwrap();
     })
@@ -423,3 +454,45 @@ async fn run_battery_handler(status_bar_channel: Sender<StatusBarMessage>) {
     let mut battery_service_handle = BatteryServiceHandle::new(status_bar_channel);
     battery_service_handle.run().await;
 }
+
+async fn run_settings_watch_handler(app_channel: Sender<AppMessage>, settings: GreeterSettings) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
+
+async fn run_theme_watch_handler(app_channel: Sender<AppMessage>, theme: GreeterTheme) {
+    let file_path = theme::find_theme_path();
+    let theme = Arc::new(RwLock::new(theme));
+    let result = watch_settings(file_path, theme, theme::read_theme_yml, move |theme| {
+        let _ = app_channel.send(AppMessage::ThemeUpdated {
+            theme: theme.clone(),
+        });
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching theme {}", e);
+    }
+}
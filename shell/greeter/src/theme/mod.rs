@@ -148,12 +148,11 @@ pub fn read_theme_path_from_args() -> Option<String> {
     None
 }
 
-/// # Reads Theme YML
-///
-/// Reads the `theme.yml` and parsers to GreeterTheme
+/// # Finds Theme path
 ///
-/// **Important**: Ensure all fields are present in the yml due to strict parsing
-pub fn read_theme_yml() -> Result<GreeterTheme> {
+/// Path of the `theme.yml` in use, from the args or the env and the working
+/// directory otherwise
+pub fn find_theme_path() -> PathBuf {
     let mut file_path = PathBuf::from(
         std::env::var("MECHA_GREETER_THEME_PATH").unwrap_or(String::from("theme.yml")),
     ); // Get path of the library
@@ -164,6 +163,17 @@ pub fn read_theme_yml() -> Result<GreeterTheme> {
         file_path = PathBuf::from(file_path_in_args.unwrap());
     }
 
+    file_path
+}
+
+/// # Reads Theme YML
+///
+/// Reads the `theme.yml` and parsers to GreeterTheme
+///
+/// **Important**: Ensure all fields are present in the yml due to strict parsing
+pub fn read_theme_yml() -> Result<GreeterTheme> {
+    let file_path = find_theme_path();
+
     info!(task = "read_theme", "theme file location - {:?}", file_path);
 
     // open file
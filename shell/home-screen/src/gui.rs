@@ -32,6 +32,8 @@ pub enum Message {
     AppClicked { app_id: String },
     Show,
     Hide,
+    SettingsUpdated { settings: HomescreenSettings },
+    ThemeUpdated { theme: HomescreenTheme },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -138,6 +140,12 @@ impl Component for Homescreen {
                     let _ = spawn_command(command, args);
                 }
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
+            Some(Message::ThemeUpdated { theme }) => {
+                self.state_mut().custom_theme = theme.clone();
+            }
             _ => (),
         }
         vec![]
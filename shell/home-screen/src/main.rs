@@ -5,6 +5,8 @@ mod settings;
 mod theme;
 mod constants;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
 use gui::Homescreen;
@@ -28,7 +30,9 @@ use mctk_smithay::{layer_shell::layer_surface::LayerOptions, WindowMessage};
 use mctk_smithay::{layer_shell::layer_window::LayerWindow, WindowInfo};
 
 use settings::HomescreenSettings;
+use settings_watcher::watch_settings;
 use theme::HomescreenTheme;
+use tokio::runtime::Builder;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
@@ -38,7 +42,10 @@ use crate::gui::Message;
 pub struct AppParams {}
 
 #[derive(Debug)]
-enum AppMessage {}
+enum AppMessage {
+    SettingsUpdated { settings: HomescreenSettings },
+    ThemeUpdated { theme: HomescreenTheme },
+}
 
 fn main() -> anyhow::Result<()> {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or(EnvFilter::new("debug"));
@@ -134,15 +141,24 @@ fn main() -> anyhow::Result<()> {
     let _ = handle.insert_source(channel_rx, move |event: Event<AppMessage>, _, app| {
         let _ = match event {
             // calloop::channel::Event::Msg(msg) => app.app.push_message(msg),
-            calloop::channel::Event::Msg(msg) => {
-                let _ = window_tx_2
-                    .clone()
-                    .send(WindowMessage::Send { message: msg!(msg) });
-            }
+            calloop::channel::Event::Msg(msg) => match msg {
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+                AppMessage::ThemeUpdated { theme } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::ThemeUpdated { theme }),
+                    });
+                }
+            },
             calloop::channel::Event::Closed => {}
         };
     });
 
+    run_settings_watcher(channel_tx, settings, custom_theme);
+
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
     }
@@ -150,3 +166,60 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Reloads the settings.yml and theme.yml when they change, the app icons and
+/// the background are only loaded at startup
+fn run_settings_watcher(
+    app_channel: Sender<AppMessage>,
+    settings: HomescreenSettings,
+    theme: HomescreenTheme,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let settings_f = run_settings_watch_handler(app_channel.clone(), settings);
+        let theme_f = run_theme_watch_handler(app_channel, theme);
+        runtime.block_on(async move { tokio::join!(settings_f, theme_f) });
+    })
+}
+
+async fn run_settings_watch_handler(app_channel: Sender<AppMessage>, settings: HomescreenSettings) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
+
+async fn run_theme_watch_handler(app_channel: Sender<AppMessage>, theme: HomescreenTheme) {
+    let file_path = theme::find_theme_path();
+    let theme = Arc::new(RwLock::new(theme));
+    let result = watch_settings(file_path, theme, theme::read_theme_yml, move |theme| {
+        let _ = app_channel.send(AppMessage::ThemeUpdated {
+            theme: theme.clone(),
+        });
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching theme {}", e);
+    }
+}
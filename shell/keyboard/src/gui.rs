@@ -38,6 +38,9 @@ pub enum Message {
         status: bool,
     },
     Maximize,
+    SettingsUpdated {
+        settings: KeyboardSettings,
+    },
     // UpdateKeyboardWindow {
     //     keyboard_window: KeyboardWindow,
     // },
@@ -227,6 +230,9 @@ impl Component for Keyboard {
                     self.state_mut().key_pressed = None;
                 }
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
             Some(Message::UpdateSuggestions {
                 suggestions,
                 suggested_for,
@@ -30,6 +30,7 @@ use mctk_smithay::WindowOptions;
 use mctk_smithay::{layer_shell::layer_surface::LayerOptions, WindowMessage};
 use mctk_smithay::{layer_shell::layer_window::LayerWindow, WindowInfo};
 use model::KeyboardModel;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 use std::{collections::HashMap, io::SeekFrom};
@@ -54,6 +55,7 @@ use wayland_protocols_async::{
 use crate::gui::Message;
 use mctk_core::context::Model;
 use settings::{Icons, KeyboardSettings, TrieConfigs};
+use settings_watcher::watch_settings;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
@@ -79,6 +81,9 @@ enum AppMessage {
     ApplyModifiers {
         mods: HashSet<Modifier>,
     },
+    SettingsUpdated {
+        settings: KeyboardSettings,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -267,6 +272,14 @@ fn main() -> anyhow::Result<()> {
                             let _ = input_method_msg_tx.send(InputMethodMessage::Commit).await;
                         });
                     }
+                    AppMessage::SettingsUpdated { settings } => {
+                        KeyboardModel::get()
+                            .trie_configs
+                            .set(Some(settings.trie.clone()));
+                        let _ = window_tx_2.clone().send(WindowMessage::Send {
+                            message: msg!(Message::SettingsUpdated { settings }),
+                        });
+                    }
                     AppMessage::SuggestionsChanged {
                         suggestions,
                         suggested_for,
@@ -286,6 +299,8 @@ fn main() -> anyhow::Result<()> {
         };
     });
 
+    run_settings_watcher(app_channel, settings);
+
     loop {
         if app.is_exited {
             break;
@@ -298,6 +313,40 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Reloads the settings.yml when it changes, the layouts and icons are only
+/// loaded at startup
+fn run_settings_watcher(
+    app_channel: Sender<AppMessage>,
+    settings: KeyboardSettings,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let file_path = match settings::find_config_path() {
+            Some(file_path) => file_path,
+            None => {
+                println!("no settings.yml to watch");
+                return;
+            }
+        };
+
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let settings = Arc::new(RwLock::new(settings));
+        let result = runtime.block_on(watch_settings(
+            file_path,
+            settings,
+            settings::read_settings_yml,
+            move |settings| {
+                let _ = app_channel.send(AppMessage::SettingsUpdated {
+                    settings: settings.clone(),
+                });
+            },
+        ));
+
+        if let Err(e) = result {
+            println!("error while watching settings {}", e);
+        }
+    })
+}
+
 fn load_keymap(layout_path: String) -> (i32, u32) {
     let layout = match crate::layout::Layout::from_file(layout_path) {
         Ok(layout) => layout,
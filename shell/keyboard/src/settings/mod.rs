@@ -201,7 +201,11 @@ fn is_valid_file(path: &str) -> Option<PathBuf> {
     }
 }
 
-fn find_config_path() -> Option<PathBuf> {
+/// # Finds Settings path
+///
+/// Path of the `settings.yml` in use, looked up from the env, args, working
+/// directory, home config and `/usr/share` in that order
+pub fn find_config_path() -> Option<PathBuf> {
     // from env
     if let Ok(env_path) = std::env::var("MECHANIX_KEYBOARD_SETTINGS_PATH") {
         if let Some(path) = is_valid_file(&env_path) {
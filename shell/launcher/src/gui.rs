@@ -10,6 +10,7 @@ use crate::pages::settings_panel::SettingsPanel;
 use crate::pages::splash_screen::SplashScreen;
 use crate::pages::status_bar::StatusBar;
 use crate::settings::{self, LauncherSettings};
+use crate::theme::LauncherTheme;
 use crate::types::{BluetoothStatus, RestartState, ShutdownState, WirelessStatus};
 use crate::utils::cubic_bezier;
 use crate::{AppMessage, AppParams, BluetoothMessage, BrightnessMessage, SoundMessage};
@@ -120,6 +121,12 @@ pub enum Message {
     Shutdown(ShutdownState),
     Restart(RestartState),
     ChangeLayer(Layer),
+    SettingsUpdated {
+        settings: LauncherSettings,
+    },
+    ThemeUpdated {
+        theme: LauncherTheme,
+    },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -753,6 +760,9 @@ impl Component for Launcher {
         // println!("App was sent: {:?}", message);
         if let Some(msg) = message.downcast_ref::<Message>() {
             match msg {
+                Message::SettingsUpdated { settings } => {
+                    self.state_mut().settings = settings.clone();
+                }
                 Message::AppOpen { app_id, layer } => {
                     println!("app clicked {:?}", app_id);
 
@@ -237,6 +237,13 @@ pub fn launch_homescreen(ui_params: UiParams) -> anyhow::Result<()> {
                     let _ = PowerOptionsService::restart();
                 }
                 AppMessage::Unlock => {}
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+                // the home screen does not use the theme
+                AppMessage::ThemeUpdated { .. } => {}
                 AppMessage::AppOpen { app_id } => {
                     let app_manager_msg_tx2 = app_manager_msg_tx.clone();
                     futures::executor::block_on(async move {
@@ -88,6 +88,12 @@ impl Component for Lockscreen {
                 Message::Bluetooth { status } => {
                     self.state_mut().bluetooth_status = status.clone();
                 }
+                Message::SettingsUpdated { settings } => {
+                    self.state_mut().settings = settings.clone();
+                }
+                Message::ThemeUpdated { theme } => {
+                    self.state_mut().custom_theme = theme.clone();
+                }
                 Message::Unlock => {
                     if let Some(app_channel) = &self.state_ref().app_channel {
                         let _ = app_channel.send(AppMessage::Unlock);
@@ -117,6 +117,16 @@ pub fn launch_lockscreen(ui_params: UiParams) -> anyhow::Result<()> {
                     }
                     _ => (),
                 },
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+                AppMessage::ThemeUpdated { theme } => {
+                    let _ = window_tx_2.send(WindowMessage::Send {
+                        message: msg!(Message::ThemeUpdated { theme }),
+                    });
+                }
                 AppMessage::Unlock => {
                     zbus::block_on(async move {
                         println!("getting session to unlock");
@@ -138,6 +148,7 @@ pub fn launch_lockscreen(ui_params: UiParams) -> anyhow::Result<()> {
 
     init_services_lock(InitServicesParamsLock {
         settings,
+        theme,
         app_channel: app_channel_tx,
         // wireless_msg_rx,
         bluetooth_msg_rx,
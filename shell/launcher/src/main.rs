@@ -34,6 +34,7 @@ use modules::uptime::handler::UptimeHandle;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use tokio::runtime::Builder;
 use tokio::select;
@@ -53,6 +54,7 @@ use mctk_core::{
     types::AssetParams,
 };
 use settings::LauncherSettings;
+use settings_watcher::watch_settings;
 use theme::LauncherTheme;
 use tracing_subscriber::EnvFilter;
 
@@ -140,6 +142,12 @@ enum AppMessage {
     AppClose {
         app_id: String,
     },
+    SettingsUpdated {
+        settings: LauncherSettings,
+    },
+    ThemeUpdated {
+        theme: LauncherTheme,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -365,6 +373,7 @@ pub struct InitServicesParamsHome {
 }
 pub struct InitServicesParamsLock {
     pub settings: LauncherSettings,
+    pub theme: LauncherTheme,
     pub app_channel: Sender<AppMessage>,
     // pub wireless_msg_rx: Receiver<WirelessMessage>,
     pub bluetooth_msg_rx: Receiver<BluetoothMessage>,
@@ -398,6 +407,7 @@ fn init_services_home(init_params: InitServicesParamsHome) -> JoinHandle<()> {
         // let running_apps_f = run_running_apps_handler(app_channel.clone());
         let app_manager_f = run_app_manager_handler(app_manager_msg_rx, app_channel.clone());
         let home_button_f = run_home_button_handler(app_channel.clone());
+        let settings_watch_f = run_settings_watch_handler(app_channel.clone(), settings);
 
         runtime
             .block_on(runtime.spawn(async move {
@@ -414,7 +424,8 @@ fn init_services_home(init_params: InitServicesParamsHome) -> JoinHandle<()> {
                     net_f,
                     // running_apps_f,
                     app_manager_f,
-                    home_button_f
+                    home_button_f,
+                    settings_watch_f
                 )
             }))
             .unwrap();
@@ -424,6 +435,7 @@ fn init_services_home(init_params: InitServicesParamsHome) -> JoinHandle<()> {
 fn init_services_lock(init_params: InitServicesParamsLock) -> JoinHandle<()> {
     let InitServicesParamsLock {
         settings,
+        theme,
         app_channel,
         // wireless_msg_rx,
         bluetooth_msg_rx,
@@ -436,12 +448,16 @@ fn init_services_lock(init_params: InitServicesParamsLock) -> JoinHandle<()> {
             .unwrap();
 
         let bluetooth_f = run_bluetooth_handler(app_channel.clone(), bluetooth_msg_rx);
+        let settings_watch_f = run_settings_watch_handler(app_channel.clone(), settings);
+        let theme_watch_f = run_theme_watch_handler(app_channel.clone(), theme);
 
         runtime
             .block_on(runtime.spawn(async move {
                 tokio::join!(
                     // wireless_f,
                     bluetooth_f,
+                    settings_watch_f,
+                    theme_watch_f,
                 )
             }))
             .unwrap();
@@ -503,6 +519,48 @@ async fn run_home_button_handler(app_channel: Sender<AppMessage>) {
     home_button_handle.run().await;
 }
 
+async fn run_settings_watch_handler(app_channel: Sender<AppMessage>, settings: LauncherSettings) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
+
+async fn run_theme_watch_handler(app_channel: Sender<AppMessage>, theme: LauncherTheme) {
+    let file_path = theme::find_theme_path();
+    let theme = Arc::new(RwLock::new(theme));
+    let result = watch_settings(file_path, theme, theme::read_theme_yml, move |theme| {
+        let _ = app_channel.send(AppMessage::ThemeUpdated {
+            theme: theme.clone(),
+        });
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching theme {}", e);
+    }
+}
+
 async fn run_app_manager_handler(
     msg_rx: mpsc::Receiver<AppManagerMessage>,
     app_channel_tx: calloop::channel::Sender<AppMessage>,
@@ -2,7 +2,8 @@ use std::time::Duration;
 
 use crate::AppMessage;
 use mctk_core::reexports::smithay_client_toolkit::reexports::calloop::channel::Sender;
-use sysinfo::System;
+use mechanix_system_dbus_client::host_metrics::HostMetrics;
+use tracing::error;
 
 pub struct CPUServiceHandle {
     app_channel: Sender<AppMessage>,
@@ -14,14 +15,19 @@ impl CPUServiceHandle {
     }
 
     pub async fn run(&self) {
-        let mut sys = System::new();
+        let task = "run";
 
         loop {
-            sys.refresh_cpu_usage(); // Refreshing CPU usage.
-            let usage = sys.global_cpu_usage();
-            let _ = &self.app_channel.send(AppMessage::CPUUsage { usage });
-            // Sleeping to let time for the system to run for long
-            // enough to have useful information.
+            // the service keeps sampling between calls, so the usage covers
+            // the time since the previous one
+            match HostMetrics::get_cpu_usage().await {
+                Ok(usage) => {
+                    let _ = &self.app_channel.send(AppMessage::CPUUsage { usage });
+                }
+                Err(e) => {
+                    error!(task, "error while getting cpu usage {}", e);
+                }
+            };
             tokio::time::sleep(Duration::from_secs(5)).await;
         }
     }
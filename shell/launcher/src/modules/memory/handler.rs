@@ -2,7 +2,8 @@ use std::time::Duration;
 
 use crate::AppMessage;
 use mctk_core::reexports::smithay_client_toolkit::reexports::calloop::channel::Sender;
-use sysinfo::System;
+use mechanix_system_dbus_client::host_metrics::HostMetrics;
+use tracing::error;
 
 pub struct MemoryHandle {
     app_channel: Sender<AppMessage>,
@@ -14,15 +15,20 @@ impl MemoryHandle {
     }
 
     pub async fn run(&self) {
-        let mut sys = System::new();
+        let task = "run";
 
         loop {
-            sys.refresh_memory(); // Refreshing Memory usage.
-            let total = sys.total_memory();
-            let used = sys.used_memory();
-            let _ = &self.app_channel.send(AppMessage::Memory { total, used });
-            // Sleeping to let time for the system to run for long
-            // enough to have useful information.
+            match HostMetrics::get_memory_info().await {
+                Ok(memory_info) => {
+                    let _ = &self.app_channel.send(AppMessage::Memory {
+                        total: memory_info.total_memory,
+                        used: memory_info.used_memory,
+                    });
+                }
+                Err(e) => {
+                    error!(task, "error while getting memory info {}", e);
+                }
+            };
             tokio::time::sleep(Duration::from_secs(5)).await;
         }
     }
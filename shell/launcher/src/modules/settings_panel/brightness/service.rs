@@ -28,17 +28,13 @@ impl BrightnessService {
             }
         };
 
-        Ok((brightness as f32 / 254. * 100.) as u8)
+        Ok(brightness)
     }
 
     pub async fn set_brightness_value(value: u8) -> Result<()> {
         let task = "set_brightness_value";
-        println!(
-            "BrightnessService::set_brightness_value() {:?} converted value {:?}",
-            value,
-            (value as f32 / 100. * 254.) as u8
-        );
-        match Display::set_brightness_percentage((value as f32 / 100. * 254.).max(5.) as u8).await {
+        println!("BrightnessService::set_brightness_value() {:?}", value);
+        match Display::set_brightness_percentage(value.max(2)).await {
             Ok(v) => v,
             Err(e) => bail!(LauncherError::new(
                 LauncherErrorCodes::SetBrightnessError,
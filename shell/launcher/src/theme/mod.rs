@@ -168,12 +168,11 @@ pub fn read_theme_path_from_args() -> Option<String> {
     None
 }
 
-/// # Reads Theme YML
-///
-/// Reads the `settings.yml` and parsers to LauncherTheme
+/// # Finds Theme path
 ///
-/// **Important**: Ensure all fields are present in the yml due to strict parsing
-pub fn read_theme_yml() -> Result<LauncherTheme> {
+/// Path of the `theme.yml` in use, from the args or the env and the working
+/// directory otherwise
+pub fn find_theme_path() -> PathBuf {
     let mut file_path = PathBuf::from(
         std::env::var("MECHA_LAUNCHER_THEME_PATH").unwrap_or(String::from("theme.yml")),
     ); // Get path of the library
@@ -184,6 +183,17 @@ pub fn read_theme_yml() -> Result<LauncherTheme> {
         file_path = PathBuf::from(file_path_in_args.unwrap());
     }
 
+    file_path
+}
+
+/// # Reads Theme YML
+///
+/// Reads the `settings.yml` and parsers to LauncherTheme
+///
+/// **Important**: Ensure all fields are present in the yml due to strict parsing
+pub fn read_theme_yml() -> Result<LauncherTheme> {
+    let file_path = find_theme_path();
+
     info!(task = "read_theme", "theme file location - {:?}", file_path);
 
     // open file
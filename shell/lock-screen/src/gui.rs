@@ -59,6 +59,8 @@ pub enum Message {
     Wireless { status: WirelessStatus },
     Bluetooth { status: BluetoothStatus },
     Battery { level: u8, status: BatteryStatus },
+    SettingsUpdated { settings: LockScreenSettings },
+    ThemeUpdated { theme: LockScreenTheme },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -79,7 +81,7 @@ pub enum Routes {
 #[derive(Debug, Default)]
 pub struct LockScreenState {
     settings: LockScreenSettings,
-    // custom_theme: LockScreenTheme,
+    custom_theme: LockScreenTheme,
     unlock_pressing_time: u128,
     unlock_pressing: bool,
     unlock_pressed_at: Option<Instant>,
@@ -125,13 +127,13 @@ impl Component for LockScreen {
 
         let pin_enabled = is_pin_enabled();
 
-        // let custom_theme = match theme::read_theme_yml() {
-        //     Ok(theme) => theme,
-        //     Err(_) => LockScreenTheme::default(),
-        // };
+        let custom_theme = match theme::read_theme_yml() {
+            Ok(theme) => theme,
+            Err(_) => LockScreenTheme::default(),
+        };
         self.state = Some(LockScreenState {
             settings,
-            // custom_theme,
+            custom_theme,
             unlock_pressing_time: 0,
             unlock_pressed_at: None,
             unlock_pressing: false,
@@ -283,6 +285,12 @@ impl Component for LockScreen {
                 let battery_level = get_formatted_battery_level(level, status);
                 self.state_mut().battery_level = battery_level;
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
+            Some(Message::ThemeUpdated { theme }) => {
+                self.state_mut().custom_theme = theme.clone();
+            }
             _ => (),
         }
         vec![]
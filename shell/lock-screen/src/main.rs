@@ -44,6 +44,8 @@ use mechanix_status_bar_components::{
     StatusBarMessage,
 };
 use settings::LockScreenSettings;
+use settings_watcher::watch_settings;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use theme::LockScreenTheme;
 use tokio::runtime::Builder;
@@ -56,7 +58,10 @@ use crate::gui::Message;
 pub struct AppParams {}
 
 #[derive(Debug)]
-pub enum AppMessage {}
+pub enum AppMessage {
+    SettingsUpdated { settings: LockScreenSettings },
+    ThemeUpdated { theme: LockScreenTheme },
+}
 
 // Layer Surface App
 // #[tokio::main]
@@ -139,6 +144,7 @@ fn main() -> anyhow::Result<()> {
 
     let (session_lock_tx, session_lock_rx) = calloop::channel::channel();
     let (status_bar_channel, status_bar_receiver) = calloop::channel::channel();
+    let (app_channel, app_receiver) = calloop::channel::channel();
     let (mut app, mut event_loop, window_tx) =
         SessionLockWindow::open_blocking::<LockScreen, AppParams>(
             SessionLockWindowParams {
@@ -187,7 +193,26 @@ fn main() -> anyhow::Result<()> {
         };
     });
 
-    init_services(settings.clone(), status_bar_channel);
+    let window_tx_3 = window_tx.clone();
+    let _ = handle.insert_source(app_receiver, move |event, _, _| {
+        let _ = match event {
+            calloop::channel::Event::Msg(msg) => match msg {
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_3.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+                AppMessage::ThemeUpdated { theme } => {
+                    let _ = window_tx_3.clone().send(WindowMessage::Send {
+                        message: msg!(Message::ThemeUpdated { theme }),
+                    });
+                }
+            },
+            calloop::channel::Event::Closed => {}
+        };
+    });
+
+    init_services(settings.clone(), status_bar_channel, app_channel);
 
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
@@ -204,6 +229,7 @@ fn main() -> anyhow::Result<()> {
 fn init_services(
     settings: LockScreenSettings,
     status_bar_channel: Sender<StatusBarMessage>,
+    app_channel: Sender<AppMessage>,
 ) -> JoinHandle<()> {
     thread::spawn(move || {
         let runtime = Builder::new_multi_thread()
@@ -217,13 +243,20 @@ fn init_services(
         let wireless_f = run_wireless_handler(status_bar_channel.clone());
         let bluetooth_f = run_bluetooth_handler(status_bar_channel.clone());
         let battery_f = run_battery_handler(status_bar_channel.clone());
+        let settings_watch_f = run_settings_watch_handler(app_channel.clone(), settings.clone());
+        let theme_watch_f = run_theme_watch_handler(app_channel.clone());
 
         runtime
-            .block_on(
-                runtime.spawn(
-                    async move { tokio::join!(clock_f, wireless_f, bluetooth_f, battery_f) },
-                ),
-            )
+            .block_on(runtime.spawn(async move {
+                tokio::join!(
+                    clock_f,
+                    wireless_f,
+                    bluetooth_f,
+                    battery_f,
+                    settings_watch_f,
+                    theme_watch_f
+                )
+            }))
             .unwrap();
     })
 }
@@ -247,3 +280,45 @@ async fn run_battery_handler(status_bar_channel: Sender<StatusBarMessage>) {
     let mut battery_service_handle = BatteryServiceHandle::new(status_bar_channel);
     battery_service_handle.run().await;
 }
+
+async fn run_settings_watch_handler(app_channel: Sender<AppMessage>, settings: LockScreenSettings) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
+
+async fn run_theme_watch_handler(app_channel: Sender<AppMessage>) {
+    let file_path = theme::find_theme_path();
+    let theme = Arc::new(RwLock::new(theme::read_theme_yml().unwrap_or_default()));
+    let result = watch_settings(file_path, theme, theme::read_theme_yml, move |theme| {
+        let _ = app_channel.send(AppMessage::ThemeUpdated {
+            theme: theme.clone(),
+        });
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching theme {}", e);
+    }
+}
@@ -46,6 +46,8 @@ pub enum Message {
     AppClicked { app_id: String },
     Show,
     Hide,
+    SettingsUpdated { settings: NotificationSettings },
+    ThemeUpdated { theme: NotificationTheme },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -150,6 +152,12 @@ impl Component for Notification {
     fn update(&mut self, message: component::Message) -> Vec<component::Message> {
         println!("App was sent: {:?}", message.downcast_ref::<Message>());
         match message.downcast_ref::<Message>() {
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
+            Some(Message::ThemeUpdated { theme }) => {
+                self.state_mut().custom_theme = theme.clone();
+            }
             _ => (),
         }
         vec![]
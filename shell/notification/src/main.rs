@@ -5,6 +5,8 @@ mod theme;
 mod constants;
 
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
 use gui::Notification;
@@ -31,7 +33,9 @@ use serde::{Deserialize, Serialize};
 use crate::gui::Message;
 use clap::Parser;
 use settings::NotificationSettings;
+use settings_watcher::watch_settings;
 use theme::NotificationTheme;
+use tokio::runtime::Builder;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
@@ -51,7 +55,10 @@ struct NotificationArgs {
 pub struct AppParams {}
 
 #[derive(Debug)]
-enum AppMessage {}
+enum AppMessage {
+    SettingsUpdated { settings: NotificationSettings },
+    ThemeUpdated { theme: NotificationTheme },
+}
 
 fn main() -> anyhow::Result<()> {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or(EnvFilter::new("debug"));
@@ -128,15 +135,24 @@ fn main() -> anyhow::Result<()> {
     let _ = handle.insert_source(channel_rx, move |event: Event<AppMessage>, _, app| {
         let _ = match event {
             // calloop::channel::Event::Msg(msg) => app.app.push_message(msg),
-            calloop::channel::Event::Msg(msg) => {
-                let _ = window_tx_2
-                    .clone()
-                    .send(WindowMessage::Send { message: msg!(msg) });
-            }
+            calloop::channel::Event::Msg(msg) => match msg {
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+                AppMessage::ThemeUpdated { theme } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::ThemeUpdated { theme }),
+                    });
+                }
+            },
             calloop::channel::Event::Closed => {}
         };
     });
 
+    run_settings_watcher(channel_tx, settings, custom_theme);
+
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
     }
@@ -144,3 +160,62 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Reloads the settings.yml and theme.yml when they change
+fn run_settings_watcher(
+    app_channel: Sender<AppMessage>,
+    settings: NotificationSettings,
+    theme: NotificationTheme,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let settings_f = run_settings_watch_handler(app_channel.clone(), settings);
+        let theme_f = run_theme_watch_handler(app_channel, theme);
+        runtime.block_on(async move { tokio::join!(settings_f, theme_f) });
+    })
+}
+
+async fn run_settings_watch_handler(
+    app_channel: Sender<AppMessage>,
+    settings: NotificationSettings,
+) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
+
+async fn run_theme_watch_handler(app_channel: Sender<AppMessage>, theme: NotificationTheme) {
+    let file_path = theme::find_theme_path();
+    let theme = Arc::new(RwLock::new(theme));
+    let result = watch_settings(file_path, theme, theme::read_theme_yml, move |theme| {
+        let _ = app_channel.send(AppMessage::ThemeUpdated {
+            theme: theme.clone(),
+        });
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching theme {}", e);
+    }
+}
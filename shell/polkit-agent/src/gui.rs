@@ -59,6 +59,7 @@ pub enum Message {
     Next,
     Submit,
     Init(String),
+    SettingsUpdated { settings: PolkitAgentSettings },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -164,6 +165,9 @@ impl Component for PolkitAgent {
                         });
                     }
                 }
+                Message::SettingsUpdated { settings } => {
+                    self.state_mut().settings = settings.clone();
+                }
             }
         }
         vec![]
@@ -8,9 +8,10 @@ mod settings;
 mod types;
 
 use agent::register_polkit_agent;
-use gui::PolkitAgent;
+use gui::{Message, PolkitAgent};
 use keyring::Entry;
 use mctk_core::{
+    msg,
     reexports::{
         cosmic_text,
         smithay_client_toolkit::{
@@ -26,9 +27,12 @@ use mctk_smithay::{layer_shell::layer_surface::LayerOptions, WindowMessage};
 use mctk_smithay::{layer_shell::layer_window::LayerWindow, WindowInfo};
 use mechanix_system_dbus_client::security::Security;
 use settings::PolkitAgentSettings;
+use settings_watcher::watch_settings;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
+use tokio::runtime::Builder;
 use tokio::sync::{mpsc, oneshot};
 use tokio::{select, time};
 use tracing::info;
@@ -44,6 +48,7 @@ pub struct AppParams {
 enum AppMessage {
     Authenticate { password: String },
     Cancel,
+    SettingsUpdated { settings: PolkitAgentSettings },
 }
 
 #[tokio::main]
@@ -141,6 +146,7 @@ fn launch_auth_ui(params: Params) -> anyhow::Result<()> {
     };
 
     let (app_channel_tx, app_channel_rx) = calloop::channel::channel();
+    let app_channel = app_channel_tx.clone();
     let (mut app, mut event_loop, window_tx) = LayerWindow::open_blocking::<PolkitAgent, AppParams>(
         LayerWindowParams {
             window_info,
@@ -220,6 +226,11 @@ fn launch_auth_ui(params: Params) -> anyhow::Result<()> {
                     });
                     println!("Window closed");
                 }
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
             },
             calloop::channel::Event::Closed => {
                 println!("calloop::event::closed");
@@ -227,6 +238,13 @@ fn launch_auth_ui(params: Params) -> anyhow::Result<()> {
         };
     });
 
+    // dropped with the dialog, which stops the watcher
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    runtime.spawn(run_settings_watch_handler(app_channel, settings));
+
     loop {
         // println!("event_loop_dispatch");
         let _ = event_loop.dispatch(None, &mut app);
@@ -241,3 +259,26 @@ fn launch_auth_ui(params: Params) -> anyhow::Result<()> {
     println!("UI loop ended");
     Ok(())
 }
+
+async fn run_settings_watch_handler(
+    app_channel: calloop::channel::Sender<AppMessage>,
+    settings: PolkitAgentSettings,
+) {
+    let file_path = settings::find_config_path();
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
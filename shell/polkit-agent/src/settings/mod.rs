@@ -113,12 +113,11 @@ pub fn read_settings_path_from_args() -> Option<String> {
     None
 }
 
-/// # Reads Settings YML
-///
-/// Reads the `settings.yml` and parsers to PolkitAgentSettings
+/// # Finds Settings path
 ///
-/// **Important**: Ensure all fields are present in the yml due to strict parsing
-pub fn read_settings_yml() -> Result<PolkitAgentSettings> {
+/// Path of the `settings.yml` in use, from the args or the env and the working
+/// directory otherwise
+pub fn find_config_path() -> PathBuf {
     let mut file_path = PathBuf::from(
         std::env::var("MECHANIX_POLKIT_AGENT_SETTINGS_PATH")
             .unwrap_or(String::from("settings.yml")),
@@ -130,6 +129,17 @@ pub fn read_settings_yml() -> Result<PolkitAgentSettings> {
         file_path = PathBuf::from(file_path_in_args.unwrap());
     }
 
+    file_path
+}
+
+/// # Reads Settings YML
+///
+/// Reads the `settings.yml` and parsers to PolkitAgentSettings
+///
+/// **Important**: Ensure all fields are present in the yml due to strict parsing
+pub fn read_settings_yml() -> Result<PolkitAgentSettings> {
+    let file_path = find_config_path();
+
     info!(
         task = "read_settings",
         "settings file location - {:?}", file_path
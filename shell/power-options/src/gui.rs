@@ -27,6 +27,7 @@ pub enum Message {
     ShutdownClicked,
     RestartClicked,
     LogoutClicked,
+    SettingsUpdated { settings: PowerOptionsSettings },
 }
 
 #[derive(Debug)]
@@ -125,6 +126,9 @@ impl Component for PowerOptions {
             Some(Message::LogoutClicked) => {
                 let _ = PowerOptionsService::suspend();
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
             _ => (),
         };
         vec![]
@@ -6,26 +6,39 @@ mod constants;
 
 use mctk_core::ImgFilter;
 use settings::PowerOptionsSettings;
+use settings_watcher::watch_settings;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
-use gui::PowerOptions;
+use gui::{Message, PowerOptions};
 use mctk_core::{
-    reexports::{cosmic_text, smithay_client_toolkit::shell::wlr_layer},
+    msg,
+    reexports::{
+        cosmic_text,
+        smithay_client_toolkit::{
+            reexports::calloop::{self, channel::Sender},
+            shell::wlr_layer,
+        },
+    },
     types::AssetParams,
 };
 use mctk_smithay::layer_shell::layer_surface::LayerOptions;
 use mctk_smithay::layer_shell::layer_window::LayerWindow;
 use mctk_smithay::layer_shell::layer_window::LayerWindowParams;
-use mctk_smithay::{WindowInfo, WindowOptions};
+use mctk_smithay::{WindowInfo, WindowMessage, WindowOptions};
 
+use tokio::runtime::Builder;
 use tracing_subscriber::EnvFilter;
 
 #[derive(Debug, Clone)]
 pub struct AppParams {}
 
 #[derive(Debug)]
-pub enum AppMessage {}
+pub enum AppMessage {
+    SettingsUpdated { settings: PowerOptionsSettings },
+}
 
 // Layer Surface App
 fn main() -> anyhow::Result<()> {
@@ -116,6 +129,26 @@ fn main() -> anyhow::Result<()> {
         AppParams {},
     );
 
+    let handle = event_loop.handle();
+
+    //subscribe to events channel
+    let (app_channel, app_receiver) = calloop::channel::channel();
+    let window_tx_2 = window_tx.clone();
+    let _ = handle.insert_source(app_receiver, move |event, _, _| {
+        let _ = match event {
+            calloop::channel::Event::Msg(msg) => match msg {
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_2.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+            },
+            calloop::channel::Event::Closed => {}
+        };
+    });
+
+    run_settings_watcher(app_channel, settings);
+
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
     }
@@ -123,3 +156,37 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Reloads the settings.yml when it changes, the icons and the background are
+/// only loaded at startup
+fn run_settings_watcher(
+    app_channel: Sender<AppMessage>,
+    settings: PowerOptionsSettings,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let file_path = match settings::find_config_path() {
+            Some(file_path) => file_path,
+            None => {
+                println!("no settings.yml to watch");
+                return;
+            }
+        };
+
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let settings = Arc::new(RwLock::new(settings));
+        let result = runtime.block_on(watch_settings(
+            file_path,
+            settings,
+            settings::read_settings_yml,
+            move |settings| {
+                let _ = app_channel.send(AppMessage::SettingsUpdated {
+                    settings: settings.clone(),
+                });
+            },
+        ));
+
+        if let Err(e) = result {
+            println!("error while watching settings {}", e);
+        }
+    })
+}
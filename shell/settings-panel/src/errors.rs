@@ -19,6 +19,8 @@ pub enum SettingsPanelErrorCodes {
     GetMemoryInfoError,
     GetBrightnessError,
     SetBrightnessError,
+    GetAutoBrightnessError,
+    SetAutoBrightnessError,
     GetSoundError,
     SetSoundError,
     GetBatteryStatusError,
@@ -45,6 +47,8 @@ impl fmt::Display for SettingsPanelErrorCodes {
             SettingsPanelErrorCodes::GetMemoryInfoError => write!(f, "GetMemoryInfoError"),
             SettingsPanelErrorCodes::GetBrightnessError => write!(f, "GetBrightnessError"),
             SettingsPanelErrorCodes::SetBrightnessError => write!(f, "SetBrightnessError"),
+            SettingsPanelErrorCodes::GetAutoBrightnessError => write!(f, "GetAutoBrightnessError"),
+            SettingsPanelErrorCodes::SetAutoBrightnessError => write!(f, "SetAutoBrightnessError"),
             SettingsPanelErrorCodes::GetSoundError => write!(f, "GetSoundError"),
             SettingsPanelErrorCodes::SetSoundError => write!(f, "SetSoundError"),
             SettingsPanelErrorCodes::EnableWireless => write!(f, "EnableWireless"),
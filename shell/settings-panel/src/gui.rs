@@ -36,6 +36,7 @@ pub enum SettingNames {
     Bluetooth,
     Rotation,
     Settings,
+    AutoBrightness,
 }
 #[derive(Debug, Clone)]
 pub enum SliderSettingsNames {
@@ -58,7 +59,9 @@ pub enum Message {
     Memory { usage: u64 },
     Sound { value: i32 },
     Brightness { value: i32 },
+    AutoBrightness { enabled: Option<bool> },
     Window { title: String },
+    SettingsUpdated,
     Show,
     Hide,
     SettingClicked(SettingNames),
@@ -95,6 +98,7 @@ pub struct SettingsPanelState {
     memory_usage: u64,
     sound_value: i32,
     brightness_value: i32,
+    auto_brightness: Option<bool>,
     loading: Loading,
     app_channel: Option<Sender<AppMessage>>,
     visible: bool,
@@ -124,6 +128,7 @@ impl Component for SettingsPanel {
             memory_usage: 0,
             sound_value: 0,
             brightness_value: 0,
+            auto_brightness: None,
             loading: Loading::default(),
             app_channel: None,
             visible: true,
@@ -206,6 +211,7 @@ impl Component for SettingsPanel {
             .push(node!(
                 BrightnessComponent {
                     value: self.state_ref().brightness_value,
+                    auto_brightness: self.state_ref().auto_brightness,
                 },
                 lay![margin: rect!(5.5, 7., 5.5, 0.)]
             ))
@@ -269,6 +275,9 @@ impl Component for SettingsPanel {
             Some(Message::Brightness { value }) => {
                 self.state_mut().brightness_value = *value as i32;
             }
+            Some(Message::AutoBrightness { enabled }) => {
+                self.state_mut().auto_brightness = *enabled;
+            }
             Some(Message::SettingClicked(settings_name)) => {
                 println!("setting clicked: {:?}", settings_name);
                 match settings_name {
@@ -315,6 +324,18 @@ impl Component for SettingsPanel {
                         }
                     }
                     SettingNames::Rotation => {}
+                    SettingNames::AutoBrightness => {
+                        let auto_brightness = self.state_ref().auto_brightness;
+                        let app_channel = self.state_ref().app_channel.clone();
+                        if let (Some(enabled), Some(app_channel)) = (auto_brightness, app_channel) {
+                            self.state_mut().auto_brightness = Some(!enabled);
+                            let _ = app_channel.send(AppMessage::Brightness {
+                                message: BrightnessMessage::SetAutoBrightness {
+                                    enabled: !enabled,
+                                },
+                            });
+                        }
+                    }
                     SettingNames::Settings => {
                         let settings = self.state_ref().settings.read().unwrap();
 
@@ -360,6 +381,11 @@ impl Component for SettingsPanel {
             Some(Message::RunningApps { count }) => {
                 self.state_mut().running_apps_count = *count;
             }
+            Some(Message::SettingsUpdated) => {
+                // settings are shared with the watcher, marking the state
+                // dirty redraws with the reloaded values
+                self.state_mut();
+            }
             Some(Message::Show) => {
                 self.state_mut().visible = true;
             }
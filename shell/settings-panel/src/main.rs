@@ -57,6 +57,7 @@ use modules::{
 };
 use modules::{sound::handler::SoundServiceHandle, wireless::handler::WirelessServiceHandle};
 use settings::SettingsPanelSettings;
+use settings_watcher::watch_settings;
 use std::thread::{self, JoinHandle};
 use theme::SettingsPanelTheme;
 use tokio::sync::mpsc;
@@ -115,6 +116,9 @@ pub enum SoundMessage {
 pub enum BrightnessMessage {
     Value { value: u8 },
     Change { value: u8 },
+    /// `None` when the display has no ambient light sensor
+    AutoBrightness { enabled: Option<bool> },
+    SetAutoBrightness { enabled: bool },
 }
 
 #[derive(Debug)]
@@ -127,6 +131,7 @@ pub enum AppMessage {
     Sound { message: SoundMessage },
     Brightness { message: BrightnessMessage },
     RunningApps { message: RunningAppsMessage },
+    SettingsUpdated,
     Show,
     Hide,
 }
@@ -308,7 +313,13 @@ fn main() -> anyhow::Result<()> {
                             }),
                         });
                     }
-                    BrightnessMessage::Change { .. } => {
+                    BrightnessMessage::AutoBrightness { enabled } => {
+                        let _ = window_tx_2.send(WindowMessage::Send {
+                            message: msg!(Message::AutoBrightness { enabled }),
+                        });
+                    }
+                    BrightnessMessage::Change { .. }
+                    | BrightnessMessage::SetAutoBrightness { .. } => {
                         let brightness_msg_tx_cloned = brightness_msg_tx.clone();
                         futures::executor::block_on(async move {
                             //let (tx, rx) = oneshot::channel();
@@ -341,6 +352,11 @@ fn main() -> anyhow::Result<()> {
                         });
                     }
                 },
+                AppMessage::SettingsUpdated => {
+                    let _ = window_tx_2.send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated),
+                    });
+                }
                 AppMessage::Show => {
                     println!("AppMessage::Show");
                     let _ = window_tx_2
@@ -402,7 +418,8 @@ fn init_services(
         let memory_f = run_memory_handler(app_channel.clone());
         let brightness_f = run_brightness_handler(app_channel.clone(), brightness_msg_rx);
         let sound_f = run_sound_handler(app_channel.clone(), sound_msg_rx);
-        let settings_sync_f = run_settings_sync_handler(settings);
+        let settings_sync_f = run_settings_sync_handler(settings.clone());
+        let settings_watch_f = run_settings_watch_handler(app_channel.clone(), settings);
 
         runtime
             .block_on(runtime.spawn(async move {
@@ -415,7 +432,8 @@ fn init_services(
                     memory_f,
                     brightness_f,
                     sound_f,
-                    settings_sync_f
+                    settings_sync_f,
+                    settings_watch_f
                 )
             }))
             .unwrap();
@@ -469,48 +487,59 @@ async fn run_sound_handler(app_channel: Sender<AppMessage>, sound_msg_rx: Receiv
     sound_service_handle.run(sound_msg_rx).await;
 }
 
-async fn run_settings_sync_handler(settings: Arc<RwLock<SettingsPanelSettings>>) {
-    let mut stream_res = StoreClient::get_notify().await;
+async fn run_settings_watch_handler(
+    app_channel: Sender<AppMessage>,
+    settings: Arc<RwLock<SettingsPanelSettings>>,
+) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
 
-    if let Err(e) = stream_res.as_ref() {
-        println!("error while getting notify stream {}", e);
-        return;
+    let result = watch_settings(file_path, settings, settings::read_settings_yml, move |_| {
+        let _ = app_channel.send(AppMessage::SettingsUpdated);
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
     }
+}
 
-    loop {
-        if let Some(signal) = stream_res.as_mut().unwrap().next().await {
-            if let Ok(args) = signal.args() {
-                let event = args.event;
-                match event {
-                    StoreEvent::Insert { keys_vals } => {
-                        for (key, _) in keys_vals {
-                            match key.as_str() {
-                                // "k1" => {
-                                //     if let Ok(settings) = settings.write().as_mut() {
-                                //         settings.bg_color = (0., 0., 255., 1.);
-                                //     }
-                                // }
-                                _ => (),
-                            }
-                        }
-                    }
-                    StoreEvent::Update { keys_vals } => {
-                        for (key, _) in keys_vals {
-                            match key.as_str() {
-                                _ => (),
-                            }
-                        }
+async fn run_settings_sync_handler(settings: Arc<RwLock<SettingsPanelSettings>>) {
+    let mut stream = match StoreClient::watch("settings", "settings_panel.").await {
+        Ok(stream) => stream,
+        Err(e) => {
+            println!("error while watching settings {}", e);
+            return;
+        }
+    };
+
+    while let Some(event) = stream.next().await {
+        match event {
+            StoreEvent::Insert { keys_vals } | StoreEvent::Update { keys_vals } => {
+                for (key, _) in keys_vals {
+                    match key.as_str() {
+                        // "settings_panel.bg_color" => {
+                        //     if let Ok(settings) = settings.write().as_mut() {
+                        //         settings.bg_color = (0., 0., 255., 1.);
+                        //     }
+                        // }
+                        _ => (),
                     }
-                    StoreEvent::Delete { keys_vals } => {
-                        for (key, _) in keys_vals {
-                            match key.as_str() {
-                                _ => (),
-                            }
-                        }
+                }
+            }
+            StoreEvent::Delete { keys_vals } => {
+                for (key, _) in keys_vals {
+                    match key.as_str() {
+                        _ => (),
                     }
                 }
             }
-        };
+        }
     }
 
     println!("settings sync ended");
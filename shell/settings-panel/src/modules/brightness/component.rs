@@ -8,7 +8,7 @@ use mctk_core::{
 };
 
 use crate::{
-    gui::{Message, SliderSettingsNames},
+    gui::{Message, SettingNames, SliderSettingsNames},
     settings::BrightnessIconPaths,
     widgets::slidable_setting::SlidableSetting,
 };
@@ -37,6 +37,8 @@ pub enum BrightnessMessage {
 #[derive(Debug)]
 pub struct BrightnessComponent {
     pub value: i32,
+    /// `None` when the display has no ambient light sensor
+    pub auto_brightness: Option<bool>,
 }
 
 impl Component for BrightnessComponent {
@@ -47,14 +49,25 @@ impl Component for BrightnessComponent {
             BrightnessValue::Medium.to_string()
         };
 
-        Some(node!(SlidableSetting::new(
-            icon,
-            "Brightness".to_string(),
-            self.value,
-        )
-        .on_slide(Box::new(|value| msg!(Message::SliderChanged(
-            SliderSettingsNames::Brightness { value }
-        ))))))
+        let text = match self.auto_brightness {
+            Some(true) => "Brightness (Auto)".to_string(),
+            _ => "Brightness".to_string(),
+        };
+
+        let mut setting =
+            SlidableSetting::new(icon, text, self.value).on_slide(Box::new(|value| {
+                msg!(Message::SliderChanged(SliderSettingsNames::Brightness {
+                    value
+                }))
+            }));
+        // tapping the icon toggles auto brightness when there is a sensor
+        if self.auto_brightness.is_some() {
+            setting = setting.on_icon_click(Box::new(|| {
+                msg!(Message::SettingClicked(SettingNames::AutoBrightness))
+            }));
+        }
+
+        Some(node!(setting))
     }
 }
 
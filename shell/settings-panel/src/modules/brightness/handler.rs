@@ -34,6 +34,16 @@ impl BrightnessServiceHandle {
                 error!(task, "error while getting brightness value {}", e);
             }
         };
+        match BrightnessService::get_auto_brightness().await {
+            Ok(enabled) => {
+                let _ = self.app_channel.send(AppMessage::Brightness {
+                    message: BrightnessMessage::AutoBrightness { enabled },
+                });
+            }
+            Err(e) => {
+                error!(task, "error while getting auto brightness {}", e);
+            }
+        };
         let mut stream_res = BrightnessService::get_notification_stream().await;
         let mut interval = time::interval(Duration::from_secs(5));
         if let Err(e) = stream_res.as_ref() {
@@ -70,6 +80,17 @@ impl BrightnessServiceHandle {
                             interval.reset();
                             let _ = BrightnessService::set_brightness_value(value as u8).await;
                         }
+                        BrightnessMessage::SetAutoBrightness { enabled } => {
+                            if let Err(e) = BrightnessService::set_auto_brightness(enabled).await {
+                                error!(task, "error while setting auto brightness {}", e);
+                            }
+                            // report the state the service ended up in
+                            if let Ok(enabled) = BrightnessService::get_auto_brightness().await {
+                                let _ = self.app_channel.send(AppMessage::Brightness {
+                                    message: BrightnessMessage::AutoBrightness { enabled },
+                                });
+                            }
+                        }
                         _ => ()
                     };
                 },
@@ -28,17 +28,13 @@ impl BrightnessService {
             }
         };
 
-        Ok((brightness as f32 / 254. * 100.) as u8)
+        Ok(brightness)
     }
 
     pub async fn set_brightness_value(value: u8) -> Result<()> {
         let task = "set_brightness_value";
-        println!(
-            "BrightnessService::set_brightness_value() {:?} converted value {:?}",
-            value,
-            (value as f32 / 100. * 254.) as u8
-        );
-        match Display::set_brightness_percentage((value as f32 / 100. * 254.).max(5.) as u8).await {
+        println!("BrightnessService::set_brightness_value() {:?}", value);
+        match Display::set_brightness_percentage(value.max(2)).await {
             Ok(v) => v,
             Err(e) => bail!(SettingsPanelError::new(
                 SettingsPanelErrorCodes::SetBrightnessError,
@@ -49,6 +45,40 @@ impl BrightnessService {
         Ok(())
     }
 
+    /// Auto brightness state, `None` when there is no ambient light sensor
+    pub async fn get_auto_brightness() -> Result<Option<bool>> {
+        let has_sensor = match Display::has_ambient_light_sensor().await {
+            Ok(v) => v,
+            Err(e) => bail!(SettingsPanelError::new(
+                SettingsPanelErrorCodes::GetAutoBrightnessError,
+                e.to_string(),
+            )),
+        };
+        if !has_sensor {
+            return Ok(None);
+        }
+
+        match Display::get_auto_brightness().await {
+            Ok(v) => Ok(Some(v)),
+            Err(e) => bail!(SettingsPanelError::new(
+                SettingsPanelErrorCodes::GetAutoBrightnessError,
+                e.to_string(),
+            )),
+        }
+    }
+
+    pub async fn set_auto_brightness(enabled: bool) -> Result<()> {
+        match Display::set_auto_brightness(enabled).await {
+            Ok(v) => v,
+            Err(e) => bail!(SettingsPanelError::new(
+                SettingsPanelErrorCodes::SetAutoBrightnessError,
+                e.to_string(),
+            )),
+        };
+
+        Ok(())
+    }
+
     pub async fn get_notification_stream() -> Result<NotificationStream<'static>> {
         let stream = Display::get_notification_stream().await?;
         Ok(stream)
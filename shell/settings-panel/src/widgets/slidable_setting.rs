@@ -1,5 +1,6 @@
 use mctk_core::{
     component::{Component, Message},
+    event::{Click, Event},
     lay, msg, node, rect, size, size_pct,
     style::{FontWeight, Styled, VerticalPosition},
     txt,
@@ -9,6 +10,31 @@ use mctk_core::{
 
 enum SlidableSettingMessage {
     ValueChanged(i32),
+    IconClicked,
+}
+
+/// Icon of the setting, emits a click when the setting has an icon action
+#[derive(Debug)]
+struct SlidableSettingIcon {
+    icon: String,
+    clickable: bool,
+}
+
+impl Component for SlidableSettingIcon {
+    fn on_click(&mut self, event: &mut Event<Click>) {
+        if self.clickable {
+            event.emit(msg!(SlidableSettingMessage::IconClicked));
+        }
+    }
+
+    fn view(&self) -> Option<Node> {
+        Some(node!(
+            Svg::new(self.icon.to_string()),
+            lay![
+                size: [32, 32],
+            ],
+        ))
+    }
 }
 
 pub struct SlidableSetting {
@@ -16,6 +42,7 @@ pub struct SlidableSetting {
     pub text: String,
     pub value: i32,
     pub on_slide: Option<Box<dyn Fn(i32) -> Message + Send + Sync>>,
+    pub on_icon_click: Option<Box<dyn Fn() -> Message + Send + Sync>>,
 }
 
 impl std::fmt::Debug for SlidableSetting {
@@ -33,6 +60,7 @@ impl SlidableSetting {
             text,
             value,
             on_slide: None,
+            on_icon_click: None,
         }
     }
 
@@ -40,6 +68,11 @@ impl SlidableSetting {
         self.on_slide = Some(on_slide);
         self
     }
+
+    pub fn on_icon_click(mut self, on_icon_click: Box<dyn Fn() -> Message + Send + Sync>) -> Self {
+        self.on_icon_click = Some(on_icon_click);
+        self
+    }
 }
 
 impl Component for SlidableSetting {
@@ -52,6 +85,11 @@ impl Component for SlidableSetting {
                     m.push(slide_fn(*value));
                 }
             }
+            Some(SlidableSettingMessage::IconClicked) => {
+                if let Some(click_fn) = &self.on_icon_click {
+                    m.push(click_fn());
+                }
+            }
             _ => (),
         }
         m
@@ -75,7 +113,10 @@ impl Component for SlidableSetting {
                 ]
             )
             .push(node!(
-                Svg::new(self.icon.to_string()),
+                SlidableSettingIcon {
+                    icon: self.icon.clone(),
+                    clickable: self.on_icon_click.is_some(),
+                },
                 lay![
                     size: [32, 32],
                     margin: [0, 0, 4, 0]
@@ -27,9 +27,9 @@ use mechanix_status_bar_components::{
         // clock::component::{ClockComponent, ClockMessage, ClockState},
         // window::component::{WindowTitleComponent, WindowTitleMessage},
     },
-    settings::{self, StatusBarSettings},
 };
 
+use crate::settings::{self, StatusBarSettings};
 use crate::theme::{self, StatusBarTheme};
 
 /// ## Message
@@ -43,6 +43,8 @@ pub enum Message {
     Bluetooth { status: BluetoothStatus },
     Battery { level: u8, status: BatteryStatus },
     Window { title: String, activated: bool },
+    SettingsUpdated { settings: StatusBarSettings },
+    ThemeUpdated { theme: StatusBarTheme },
     Show,
     Hide,
 }
@@ -57,6 +59,8 @@ pub struct Padding {
 
 #[derive(Debug)]
 pub struct StatusBarState {
+    settings: StatusBarSettings,
+    custom_theme: StatusBarTheme,
     battery_level: BatteryLevel,
     wireless_status: WirelessStatus,
     bluetooth_status: BluetoothStatus,
@@ -73,6 +77,8 @@ pub struct StatusBar {}
 impl Component for StatusBar {
     fn init(&mut self) {
         self.state = Some(StatusBarState {
+            settings: settings::read_settings_yml().unwrap_or_default(),
+            custom_theme: theme::read_theme_yml().unwrap_or_default(),
             battery_level: BatteryLevel::default(),
             wireless_status: WirelessStatus::default(),
             bluetooth_status: BluetoothStatus::default(),
@@ -133,6 +139,12 @@ impl Component for StatusBar {
                 self.state_mut().current_window_title = title.clone();
                 self.state_mut().is_any_window_maximized = *activated;
             }
+            Some(Message::SettingsUpdated { settings }) => {
+                self.state_mut().settings = settings.clone();
+            }
+            Some(Message::ThemeUpdated { theme }) => {
+                self.state_mut().custom_theme = theme.clone();
+            }
             _ => (),
         }
         vec![]
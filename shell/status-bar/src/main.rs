@@ -39,13 +39,19 @@ use tracing_subscriber::EnvFilter;
 
 use mechanix_status_bar_components::types::{BatteryStatus, BluetoothStatus, WirelessStatus};
 use settings::StatusBarSettings;
+use settings_watcher::watch_settings;
+use std::sync::{Arc, RwLock};
 use std::thread::{self, JoinHandle};
+use theme::StatusBarTheme;
 use tokio::runtime::Builder;
 
 use crate::gui::Message;
 
 #[derive(Debug, Clone)]
-pub struct AppMessage {}
+pub enum AppMessage {
+    SettingsUpdated { settings: StatusBarSettings },
+    ThemeUpdated { theme: StatusBarTheme },
+}
 
 #[derive(Debug, Clone)]
 pub struct AppParams {}
@@ -116,6 +122,7 @@ fn main() -> anyhow::Result<()> {
     };
 
     let (status_bar_channel, status_bar_receiver) = calloop::channel::channel();
+    let (app_channel, app_receiver) = calloop::channel::channel();
     let (mut app, mut event_loop, window_tx) = LayerWindow::open_blocking::<StatusBar, AppParams>(
         LayerWindowParams {
             window_info,
@@ -169,7 +176,26 @@ fn main() -> anyhow::Result<()> {
         };
     });
 
-    init_services(settings.clone(), status_bar_channel);
+    let window_tx_3 = window_tx.clone();
+    let _ = handle.insert_source(app_receiver, move |event, _, _| {
+        let _ = match event {
+            calloop::channel::Event::Msg(msg) => match msg {
+                AppMessage::SettingsUpdated { settings } => {
+                    let _ = window_tx_3.clone().send(WindowMessage::Send {
+                        message: msg!(Message::SettingsUpdated { settings }),
+                    });
+                }
+                AppMessage::ThemeUpdated { theme } => {
+                    let _ = window_tx_3.clone().send(WindowMessage::Send {
+                        message: msg!(Message::ThemeUpdated { theme }),
+                    });
+                }
+            },
+            calloop::channel::Event::Closed => {}
+        };
+    });
+
+    init_services(settings.clone(), status_bar_channel, app_channel);
 
     loop {
         event_loop.dispatch(None, &mut app).unwrap();
@@ -182,6 +208,7 @@ fn main() -> anyhow::Result<()> {
 fn init_services(
     settings: StatusBarSettings,
     status_bar_channel: Sender<StatusBarMessage>,
+    app_channel: Sender<AppMessage>,
 ) -> JoinHandle<()> {
     thread::spawn(move || {
         let runtime = Builder::new_multi_thread()
@@ -196,10 +223,20 @@ fn init_services(
         let wireless_f = run_wireless_handler(status_bar_channel.clone());
         let bluetooth_f = run_bluetooth_handler(status_bar_channel.clone());
         let battery_f = run_battery_handler(status_bar_channel.clone());
+        let settings_watch_f = run_settings_watch_handler(app_channel.clone(), settings.clone());
+        let theme_watch_f = run_theme_watch_handler(app_channel.clone());
 
         runtime
             .block_on(runtime.spawn(async move {
-                tokio::join!(clock_f, wireless_f, window_f, bluetooth_f, battery_f)
+                tokio::join!(
+                    clock_f,
+                    wireless_f,
+                    window_f,
+                    bluetooth_f,
+                    battery_f,
+                    settings_watch_f,
+                    theme_watch_f
+                )
             }))
             .unwrap();
     })
@@ -229,3 +266,45 @@ async fn run_battery_handler(status_bar_channel: Sender<StatusBarMessage>) {
     let mut battery_service_handle = BatteryServiceHandle::new(status_bar_channel);
     battery_service_handle.run().await;
 }
+
+async fn run_settings_watch_handler(app_channel: Sender<AppMessage>, settings: StatusBarSettings) {
+    let file_path = match settings::find_config_path() {
+        Some(file_path) => file_path,
+        None => {
+            println!("no settings.yml to watch");
+            return;
+        }
+    };
+
+    let settings = Arc::new(RwLock::new(settings));
+    let result = watch_settings(
+        file_path,
+        settings,
+        settings::read_settings_yml,
+        move |settings| {
+            let _ = app_channel.send(AppMessage::SettingsUpdated {
+                settings: settings.clone(),
+            });
+        },
+    )
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching settings {}", e);
+    }
+}
+
+async fn run_theme_watch_handler(app_channel: Sender<AppMessage>) {
+    let file_path = theme::find_theme_path();
+    let theme = Arc::new(RwLock::new(theme::read_theme_yml().unwrap_or_default()));
+    let result = watch_settings(file_path, theme, theme::read_theme_yml, move |theme| {
+        let _ = app_channel.send(AppMessage::ThemeUpdated {
+            theme: theme.clone(),
+        });
+    })
+    .await;
+
+    if let Err(e) = result {
+        println!("error while watching theme {}", e);
+    }
+}